@@ -0,0 +1,108 @@
+//! The offline pipeline end to end, feed to CSV, over a corpus of 20
+//! filings: `documents()`, `filing_summaries()`, `master_reports()`, the
+//! balance sheet and income statement, `to_numeric()` and `to_csv()`.
+//!
+//! The corpus is Apple's 10-Q fixture served from 20 filing folders, each
+//! with its own index.json, so every filing is fetched and parsed on its
+//! own. Run with `cargo bench --bench pipeline`.
+//!
+//! About 36 ms per run before FilingSummary.xml and the feed went through
+//! quick-xml and the statement parser stopped matching selectors per row,
+//! about 21 ms after.
+
+#![allow(clippy::needless_return)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use sec_filings::{balance_sheet_first, documents, filing_summaries, income_statement,
+                  master_reports, DocumentsQuery, FilingType, FixtureClient, StatementData};
+
+
+const FILINGS: usize = 20;
+
+const FEED_URL: &str = "https://www.sec.gov/cgi-bin/browse-edgar?action=getcompany&ticker=aapl\
+                        &type=10-Q&datea=&dateb=&owner=exclude&start=0&output=atom&count=100";
+const FOLDER: &str = "/Archives/edgar/data/320193/000032019324000006";
+const ACCESSION: &str = "0000320193-24-000006";
+
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aapl_10q").join(name);
+    return fs::read_to_string(path).unwrap();
+}
+
+
+/// The fixture filing copied under `FILINGS` accession numbers into `dir`,
+/// with a feed listing them all.
+fn corpus(dir: &Path) -> FixtureClient {
+    fs::create_dir_all(dir).unwrap();
+    let feed = fixture("browse_edgar.xml");
+    let (head, rest) = feed.split_once("<entry>").unwrap();
+    let (entry, tail) = rest.split_once("</entry>").unwrap();
+
+    let mut client = FixtureClient::new();
+    let mut entries = String::new();
+
+    for i in 0..FILINGS {
+        let accession = format!("0000320193-24-{:06}", 100 + i);
+        let folder = FOLDER.replace("000032019324000006", &accession.replace('-', ""));
+        let filing_dir = dir.join(accession.replace('-', ""));
+        fs::create_dir_all(&filing_dir).unwrap();
+
+        entries.push_str(&format!("<entry>{}</entry>", entry.replace(ACCESSION, &accession)
+                                  .replace("000032019324000006", &accession.replace('-', ""))));
+
+        let index = fixture("index.json").replace(FOLDER, &folder);
+        fs::write(filing_dir.join("index.json"), index).unwrap();
+
+        for name in ["index.json", "FilingSummary.xml", "R2.htm", "R4.htm"] {
+            if name != "index.json" {
+                fs::write(filing_dir.join(name), fixture(name)).unwrap();
+            }
+            let url = format!("https://www.sec.gov{}/{}", folder, name);
+            client = client.with_response(&url, filing_dir.join(name));
+        }
+    }
+
+    let feed_path = dir.join("browse_edgar.xml");
+    fs::write(&feed_path, format!("{}{}{}", head, entries, tail)).unwrap();
+    return client.with_response(FEED_URL, feed_path);
+}
+
+
+/// Run the pipeline, returning the CSV written so nothing is optimized out.
+fn pipeline(client: &FixtureClient) -> usize {
+    let filings = documents(client, "aapl", &DocumentsQuery::new(), FilingType::TenQ).unwrap();
+    let urls: Vec<String> = filings.into_iter().map(|filing| filing.url).collect();
+    let summaries = filing_summaries(client, &urls).unwrap();
+    assert_eq!(summaries.len(), FILINGS);
+
+    let mut written = 0;
+    for summary in summaries {
+        let reports = master_reports(client, &[summary]).unwrap();
+        let statements: [StatementData; 2] = [balance_sheet_first(client, &reports).unwrap(),
+                                              income_statement(client, &reports).unwrap()];
+        for statement in statements {
+            written += statement.to_numeric().len();
+            written += statement.to_csv().len();
+        }
+    }
+    return written;
+}
+
+
+fn bench_pipeline(c: &mut Criterion) {
+    let dir: PathBuf = std::env::temp_dir().join(format!("sec_filings_bench_{}",
+                                                         std::process::id()));
+    let client = corpus(&dir);
+
+    c.bench_function("pipeline_20_filings", |b| b.iter(|| pipeline(&client)));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+
+criterion_group!(benches, bench_pipeline);
+criterion_main!(benches);
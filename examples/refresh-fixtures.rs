@@ -0,0 +1,134 @@
+//! Download fixtures again from where tests/fixtures/manifest.toml says
+//! they came from, and update their entries.
+//!
+//! `cargo run --example refresh-fixtures -- [--accept] [path ...]`
+//!
+//! Every captured fixture is fetched unless paths (relative to
+//! tests/fixtures) are given. A fixture whose parse would change is left
+//! alone unless `--accept` is passed, so a refresh never changes what the
+//! tests see without a reviewed decision. Needs `SEC_USER_AGENT`.
+
+#![allow(clippy::needless_return)]
+
+#[path = "../tests/manifest/mod.rs"]
+mod manifest;
+
+use std::error::Error;
+use std::fs;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Days, NaiveDate};
+use sec_filings::client::USER_AGENT_ENV;
+use sec_filings::SecClient;
+
+use manifest::{fixtures_dir, load, save, sha256_hex, snapshot, Fixture};
+
+
+/// Today in UTC, "YYYY-MM-DD".
+fn today() -> String {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() / 86_400);
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    return (epoch + Days::new(days)).to_string();
+}
+
+
+/// Where `old` and `new` start to differ, by line.
+fn describe_diff(old: &str, new: &str) -> String {
+    let first = old.lines().zip(new.lines()).position(|(a, b)| a != b)
+                   .unwrap_or_else(|| old.lines().count().min(new.lines().count()));
+    return format!("{} -> {} lines, {} -> {} bytes, first difference on line {}",
+                   old.lines().count(), new.lines().count(), old.len(), new.len(), first + 1);
+}
+
+
+enum Outcome {
+    Unchanged,
+    Updated,
+    Refused,
+}
+
+
+/// Fetch `fixture` from its source and replace it, unless its snapshot
+/// changes and `accept` is false.
+fn refresh(client: &SecClient, fixture: &mut Fixture, accept: bool)
+           -> Result<Outcome, Box<dyn Error>> {
+    let source = fixture.source.clone().ok_or("hand-written, no source to refresh from")?;
+    let path = fixtures_dir().join(&fixture.path);
+
+    let body = client.get(&source)?;
+    if sha256_hex(body.as_bytes()) == fixture.sha256 {
+        return Ok(Outcome::Unchanged);
+    }
+    println!("  {}", describe_diff(&fs::read_to_string(&path)?, &body));
+
+    // parse the download where the stored copy is parsed, the file name
+    // picks nothing but the parser's input
+    let staged = path.with_extension("refresh");
+    fs::write(&staged, &body)?;
+    let changed = snapshot(fixture, &path)? != snapshot(fixture, &staged)?;
+
+    if changed && !accept {
+        fs::remove_file(&staged)?;
+        return Ok(Outcome::Refused);
+    }
+
+    fs::rename(&staged, &path)?;
+    fixture.sha256 = sha256_hex(body.as_bytes());
+    fixture.captured = today();
+    return Ok(Outcome::Updated);
+}
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let accept = args.iter().any(|arg| arg == "--accept");
+    let only: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+
+    let user_agent = std::env::var(USER_AGENT_ENV).unwrap_or_default();
+    let client = match SecClient::builder().user_agent(&user_agent).enable_logging().build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut manifest = load();
+    let mut refused = 0;
+
+    for fixture in manifest.fixtures.iter_mut() {
+        let wanted = if only.is_empty() { fixture.source.is_some() }
+                     else { only.contains(&&fixture.path) };
+        if !wanted {
+            continue;
+        }
+
+        println!("{}", fixture.path);
+        match refresh(&client, fixture, accept) {
+            Ok(Outcome::Unchanged) => println!("  unchanged"),
+            Ok(Outcome::Updated) => println!("  updated"),
+            Ok(Outcome::Refused) => {
+                println!("  parses differently, left alone; rerun with --accept to take it");
+                refused += 1;
+            }
+            Err(e) => {
+                println!("  failed: {}", e);
+                refused += 1;
+            }
+        }
+    }
+
+    save(&manifest);
+
+    let requests = client.request_log().unwrap_or_default();
+    println!("{} requests", requests.len());
+    for request in requests {
+        println!("  {} {} in {:?}", request.status, request.url, request.duration);
+    }
+
+    if refused > 0 {
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
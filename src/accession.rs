@@ -0,0 +1,80 @@
+//! EDGAR accession numbers, the ids EDGAR assigns to each filing.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+
+/// Accession number in its dashed form, e.g. "0000320193-24-000081":
+/// the filer agent's CIK, the year and a sequence number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccessionNumber(String);
+
+
+impl AccessionNumber {
+
+    /// Parse "0000320193-24-000081" or "000032019324000081".
+    pub fn parse(s: &str) -> Option<Self> {
+        let digits: String = s.chars().filter(|c| *c != '-').collect();
+
+        if digits.len() != 18 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        if s.contains('-') && !dashed_pattern().is_match(s) {
+            return None;
+        }
+
+        return Some(Self(format!("{}-{}-{}", &digits[..10], &digits[10..12],
+                                 &digits[12..])));
+    }
+
+
+    /// Dashed form, "0000320193-24-000081".
+    pub fn as_str(&self) -> &str {
+        return &self.0;
+    }
+
+
+    /// Form used in Archives folder names, "000032019324000081".
+    pub fn without_dashes(&self) -> String {
+        return self.0.replace('-', "");
+    }
+}
+
+
+impl fmt::Display for AccessionNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+
+
+fn dashed_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    return PATTERN.get_or_init(|| Regex::new(r"^\d{10}-\d{2}-\d{6}$").expect("valid regex"));
+}
+
+
+
+/// Accession number of the filing an EDGAR URL points into, from any of
+/// its forms: the "-index.htm(l)" page, the folder's index.json, or any
+/// document under `/Archives/edgar/data/<cik>/<accession>/`.
+pub fn extract_accession_from_url(url: &str) -> Option<AccessionNumber> {
+    static DASHED: OnceLock<Regex> = OnceLock::new();
+    let dashed = DASHED.get_or_init(|| Regex::new(r"(\d{10}-\d{2}-\d{6})")
+                                    .expect("valid regex"));
+
+    // the index page names the filing with its dashed accession number
+    if let Some(m) = dashed.captures(url) {
+        return AccessionNumber::parse(&m[1]);
+    }
+
+    // otherwise the folder is named after it, without dashes
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    return path.split('/')
+               .find(|segment| segment.len() == 18
+                               && segment.chars().all(|c| c.is_ascii_digit()))
+               .and_then(AccessionNumber::parse);
+}
@@ -0,0 +1,257 @@
+//! Async counterpart of `SecClient` and the filing pipeline, for
+//! workloads that fetch many filings concurrently. Requires the `async`
+//! (or `tokio`) feature.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+use reqwest::header::USER_AGENT;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Serialize;
+use tokio::time::sleep;
+
+use crate::client::{backoff_delay, is_retriable, is_transient, lock_state, retry_after_delay,
+                    status_error_for, validate_user_agent, RateLimitState, SharedLimiter,
+                    USER_AGENT_ENV};
+use crate::classify::BALANCE_SHEET;
+use crate::edgar::{documents_params, find_report_url, parse_documents_feed,
+                   parse_filing_index, parse_filing_summary, DocumentsQuery, FilingRef,
+                   FilingType, ReportEntry, BROWSE_EDGAR_URL};
+use crate::error::SecFilingsError;
+use crate::parse::{parse_html_statement_data, StatementData};
+
+
+/// Async EDGAR client. Tasks sharing one client share its rate limit.
+///
+/// Paced, retried and failing like `SecClient`: the same sliding window
+/// limiter, retries of 429 and 5xx answers after `Retry-After` or a
+/// doubling delay, and the same `SecFilingsError`s.
+pub struct AsyncSecClient {
+    client: Client,
+    header: String,
+
+    // same window as SecClient's, the lock is never held across an await
+    rate_limit: Mutex<RateLimitState>,
+    request_threshold: u8,
+    // pacing shared with other clients, blocking ones too
+    shared_limiter: Option<Arc<SharedLimiter>>,
+
+    max_retries: u32,
+    retry_base_delay: Duration,
+}
+
+
+impl AsyncSecClient {
+
+    /// Client using the `User-Agent` from the `SEC_USER_AGENT` environment
+    /// variable, see `SecClient::new()`.
+    pub fn new() -> Result<Self, SecFilingsError> {
+        let header = std::env::var(USER_AGENT_ENV).map_err(|_| {
+            SecFilingsError::InvalidUserAgent(format!("{} is not set", USER_AGENT_ENV))
+        })?;
+
+        return Self::with_user_agent(&header);
+    }
+
+
+    /// Client sending `user_agent`, e.g. "Company Name admin@company.com".
+    pub fn with_user_agent(user_agent: &str) -> Result<Self, SecFilingsError> {
+        validate_user_agent(user_agent)?;
+
+        let instance = Self {
+            client: Client::builder().build()?,
+            header: user_agent.to_string(),
+            rate_limit: Mutex::new(RateLimitState::new()),
+            request_threshold: 10,
+            shared_limiter: None,
+            max_retries: 3,
+            retry_base_delay: Duration::from_millis(100),
+        };
+
+        return Ok(instance);
+    }
+
+
+    /// Maximum number of requests per second (SEC allows 10).
+    pub fn request_threshold(mut self, n: u8) -> Self {
+        self.request_threshold = n;
+        return self;
+    }
+
+
+    /// Pace requests with a limiter shared by other clients, see
+    /// `SecClientBuilder::shared_limiter`.
+    pub fn shared_limiter(mut self, limiter: Arc<SharedLimiter>) -> Self {
+        self.shared_limiter = Some(limiter);
+        return self;
+    }
+
+
+    /// See `SecClientBuilder::max_retries` (default 3).
+    pub fn max_retries(mut self, n: u32) -> Self {
+        self.max_retries = n;
+        return self;
+    }
+
+
+    /// See `SecClientBuilder::retry_base_delay` (default 100ms).
+    pub fn retry_base_delay(mut self, delay: Duration) -> Self {
+        self.retry_base_delay = delay;
+        return self;
+    }
+
+
+    /// Waits until a request may be sent, the task sleeping rather than
+    /// the thread.
+    async fn threshold_status(&self) {
+        loop {
+            let wait = match &self.shared_limiter {
+                Some(limiter) => limiter.try_acquire(Instant::now()),
+                None => lock_state(&self.rate_limit).try_acquire(Instant::now(),
+                                                                 self.request_threshold),
+            };
+            match wait {
+                Ok(()) => return,
+                Err(wait) => sleep(wait).await,
+            }
+        }
+    }
+
+
+
+    /// Async `SecClient::get_with_retry`, reading the body as text.
+    async fn get_with_retry<F>(&self, request: F) -> Result<String, SecFilingsError>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut attempt = 0;
+
+        loop {
+            self.threshold_status().await;
+            let retries_left = attempt < self.max_retries;
+
+            let sent = request().header(USER_AGENT, self.header.as_str()).send().await;
+
+            let (failure, retry_after) = match sent {
+                Ok(response) if is_retriable(response.status()) => {
+                    if !retries_left {
+                        return Err(status_error(response).await);
+                    }
+                    (format!("{} returned {}", response.url(), response.status()),
+                     retry_after_delay(response.headers()))
+                }
+                Ok(response) if !response.status().is_success() => {
+                    return Err(status_error(response).await);
+                }
+                Ok(response) => {
+                    let url = response.url().to_string();
+                    match response.text().await {
+                        Ok(body) => {
+                            if attempt > 0 {
+                                debug!("{} succeeded on attempt {}", url, attempt + 1);
+                            }
+                            return Ok(body);
+                        }
+                        Err(e) if retries_left && is_transient(&e) => (e.to_string(), None),
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                Err(e) if retries_left && is_transient(&e) => (e.to_string(), None),
+                Err(e) => return Err(e.into()),
+            };
+
+            let delay = retry_after.unwrap_or(backoff_delay(self.retry_base_delay, attempt));
+            attempt += 1;
+            warn!("{}, retry {} of {} in {:?}", failure, attempt, self.max_retries, delay);
+            sleep(delay).await;
+        }
+    }
+
+
+
+    // GET request from basic URL, non-2xx responses are errors
+    pub async fn get(&self, url: &str) -> Result<String, SecFilingsError> {
+        return self.get_with_retry(|| self.client.get(url)).await;
+    }
+
+    // GET request from URL with query parameters
+    pub async fn get_with_params<T: Serialize + ?Sized>(&self, url: &str, params: &T) ->
+                                 Result<String, SecFilingsError> {
+
+        return self.get_with_retry(|| self.client.get(url).query(params)).await;
+    }
+}
+
+
+/// Async `client::status_error`.
+async fn status_error(response: Response) -> SecFilingsError {
+    let status = response.status();
+    return status_error_for(status, &response.text().await.unwrap_or_default());
+}
+
+
+
+/// Async `edgar::documents`.
+pub async fn documents(sec_client: &AsyncSecClient, ticker: &str, query: &DocumentsQuery,
+                       filing_type: FilingType) -> Result<Vec<FilingRef>, SecFilingsError> {
+
+    let params = documents_params(("ticker", ticker), query, &filing_type);
+    let response = sec_client.get_with_params(BROWSE_EDGAR_URL, &params).await?;
+
+    return parse_documents_feed(&response);
+}
+
+
+/// Async `edgar::filing_summaries`, skips failing filings the same way.
+pub async fn filing_summaries(sec_client: &AsyncSecClient, documents_list: &[String])
+                              -> Result<Vec<String>, SecFilingsError> {
+
+    let mut summaries = vec![];
+
+    for document in documents_list {
+        let summary = sec_client.get(document).await
+                                .and_then(|response| parse_filing_index(&response))
+                                .map(|index| index.filing_summary_url());
+
+        match summary {
+            Ok(Some(url)) => summaries.push(url),
+            Ok(None) => continue,
+            Err(e) => warn!("skipping {}: {}", document, e),
+        }
+    }
+
+    Ok(summaries)
+}
+
+
+/// Async `edgar::master_reports`.
+pub async fn master_reports(sec_client: &AsyncSecClient, xml_summaries: &[String])
+                            -> Result<Vec<ReportEntry>, SecFilingsError> {
+
+    let mut all_reports = vec![];
+
+    for xml_url in xml_summaries {
+        let xml_content = sec_client.get(xml_url).await?;
+        all_reports.extend(parse_filing_summary(xml_url, &xml_content)?);
+    }
+
+    Ok(all_reports)
+}
+
+
+/// Async `edgar::balance_sheet_first`.
+pub async fn balance_sheet_first(sec_client: &AsyncSecClient, xml_summaries: &[ReportEntry])
+                            -> Result<StatementData, SecFilingsError> {
+
+    if let Some(url) = find_report_url(xml_summaries, &BALANCE_SHEET) {
+        let html = sec_client.get(url).await?;
+        let mut statement_data = parse_html_statement_data(&html)?;
+        statement_data.set_source(xml_summaries, url);
+        return Ok(statement_data);
+    }
+
+    return Err(SecFilingsError::NotFound(
+        "balance sheet report".to_string(),
+    ));
+}
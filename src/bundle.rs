@@ -0,0 +1,257 @@
+//! One JSON document per company for a web frontend: who the company is,
+//! every filing it made, and key metrics of its latest periodic reports,
+//! each traced back to the statement row it was read from.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::classify::{StatementQuery, BALANCE_SHEET, CASH_FLOW_STATEMENT, INCOME_STATEMENT};
+use crate::client::{HttpFetch, SecClient};
+use crate::edgar::{filing_summaries, master_reports};
+use crate::error::SecFilingsError;
+use crate::parse::StatementData;
+use crate::submissions::{submissions, with_older_filings, Filing};
+use crate::summary::{line_item_row, optional_statement, BALANCE_ITEMS, CASH_FLOW_ITEMS,
+                     INCOME_ITEMS};
+use crate::tickers::ticker_to_cik;
+
+
+/// Version of the `CompanyBundle` JSON layout, bumped whenever a field is
+/// renamed, removed or changes meaning.
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+
+/// Which filings of a company get their metrics extracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleOptions {
+    /// Form types to extract metrics from, e.g. "10-K".
+    pub metric_forms: Vec<String>,
+    /// Most filings to extract metrics from, the most recent ones. Each
+    /// costs a FilingSummary.xml and up to three R-file requests.
+    pub max_metric_filings: usize,
+}
+
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        return BundleOptions {
+            metric_forms: vec!["10-K".to_string(), "10-Q".to_string()],
+            max_metric_filings: 4,
+        };
+    }
+}
+
+
+/// Who the company is, from the submissions API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompanyIdentity {
+    pub cik: u64,
+    pub name: String,
+    /// Standard Industrial Classification code, e.g. "3571".
+    pub sic: Option<String>,
+    pub sic_description: String,
+    pub tickers: Vec<String>,
+}
+
+
+/// One filing of the timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub form: String,
+    /// e.g. "0000320193-24-000006".
+    pub accession: String,
+    pub filed_on: Option<NaiveDate>,
+    /// Period the filing reports on, if any.
+    pub report_date: Option<NaiveDate>,
+    pub has_xbrl: bool,
+}
+
+
+/// Where a metric was read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricSource {
+    /// URL of the R-file.
+    pub url: String,
+    /// Shortname of the report, e.g. "CONSOLIDATED BALANCE SHEETS".
+    pub report: String,
+    /// Label of the row as filed, e.g. "Total net sales".
+    pub label: String,
+    /// See `row_id()`.
+    pub row_id: String,
+}
+
+
+/// A value in units (dollars, not millions) with its source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub value: f64,
+    pub source: MetricSource,
+}
+
+
+/// Key metrics of one filing, `None` where the statement or the row was
+/// not found. Cash flows in a 10-Q are year-to-date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilingMetrics {
+    pub accession: String,
+    pub form: String,
+    pub filed_on: Option<NaiveDate>,
+    pub revenue: Option<Metric>,
+    pub net_income: Option<Metric>,
+    pub total_assets: Option<Metric>,
+    pub operating_cash_flow: Option<Metric>,
+}
+
+
+/// Everything a frontend shows for one company, see `company_bundle()`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompanyBundle {
+    /// `BUNDLE_SCHEMA_VERSION` of the code that wrote the bundle.
+    pub schema_version: u32,
+    pub company: CompanyIdentity,
+    /// Every filing, most recent first.
+    pub filings: Vec<TimelineEntry>,
+    /// Most recent first.
+    pub metrics: Vec<FilingMetrics>,
+}
+
+
+impl CompanyBundle {
+
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).expect("CompanyBundle serializes to JSON");
+    }
+
+
+    /// Fails on a bundle of another schema version.
+    pub fn from_json(s: &str) -> Result<Self, SecFilingsError> {
+        let bundle: CompanyBundle = serde_json::from_str(s)?;
+        if bundle.schema_version != BUNDLE_SCHEMA_VERSION {
+            return Err(SecFilingsError::UnexpectedStructure(format!(
+                "bundle schema version {}, expected {}", bundle.schema_version,
+                BUNDLE_SCHEMA_VERSION)));
+        }
+        return Ok(bundle);
+    }
+
+
+    /// Write the bundle as JSON to the file at `path`, replacing it if it
+    /// exists.
+    pub fn write_bundle<P: AsRef<Path>>(&self, path: P) -> Result<(), SecFilingsError> {
+        fs::write(path, self.to_json() + "\n")?;
+        return Ok(());
+    }
+}
+
+
+
+/// Date of a "YYYY-MM-DD" field of the submissions API, `None` if empty.
+fn date(raw: &str) -> Option<NaiveDate> {
+    return NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok();
+}
+
+
+/// Line item `name` of `items`, e.g. "Revenue" of `INCOME_ITEMS`, in
+/// `statement`.
+fn metric(statement: Option<&StatementData>, items: &[(&str, &[&str])], name: &str)
+          -> Option<Metric> {
+    let statement = statement?;
+    let prefixes = items.iter().find(|(item, _)| *item == name)?.1;
+    let row = line_item_row(statement, prefixes)?;
+
+    let columns = statement.data[row].len().saturating_sub(1);
+    let value = (0..columns).find_map(|col| statement.normalized_value(row, col))?;
+
+    return Some(Metric {
+        value,
+        source: MetricSource {
+            url: statement.source_url.clone().unwrap_or_default(),
+            report: statement.report_shortname.clone().unwrap_or_default(),
+            label: statement.data[row][0].clone(),
+            row_id: statement.row_ids.get(row).cloned().unwrap_or_default(),
+        },
+    });
+}
+
+
+/// Key metrics of `filing`, read from its statements.
+fn filing_metrics<C>(sec_client: &C, filing: &Filing) -> Result<FilingMetrics, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    let summaries = filing_summaries(sec_client, &[filing.index_url()])?;
+    let reports = master_reports(sec_client, &summaries)?;
+
+    let statement = |query: &StatementQuery| {
+        let statement = optional_statement(sec_client, &reports, query)?;
+        if statement.is_none() {
+            warn!("{}: no {}", filing.accession_number, query.what);
+        }
+        return Ok::<_, SecFilingsError>(statement);
+    };
+    let income = statement(&INCOME_STATEMENT)?;
+    let balance = statement(&BALANCE_SHEET)?;
+    let cash_flow = statement(&CASH_FLOW_STATEMENT)?;
+
+    return Ok(FilingMetrics {
+        accession: filing.accession_number.to_string(),
+        form: filing.form.clone(),
+        filed_on: filing.filed_on(),
+        revenue: metric(income.as_ref(), &INCOME_ITEMS, "Revenue"),
+        net_income: metric(income.as_ref(), &INCOME_ITEMS, "Net income"),
+        total_assets: metric(balance.as_ref(), &BALANCE_ITEMS, "Total assets"),
+        operating_cash_flow: metric(cash_flow.as_ref(), &CASH_FLOW_ITEMS, "Operating cash flow"),
+    });
+}
+
+
+
+/// Bundle of the company with `cik`: its identity and filing timeline
+/// from the submissions API, overflow files included, and the metrics of
+/// its `opts.max_metric_filings` latest filings of `opts.metric_forms`.
+pub fn company_bundle_by_cik<C>(sec_client: &C, cik: u64, opts: &BundleOptions)
+                                -> Result<CompanyBundle, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    let company = submissions(sec_client, cik)?;
+    let filings = with_older_filings(sec_client, &company)?;
+
+    let mut metrics = vec![];
+    for filing in filings.iter().filter(|filing| opts.metric_forms.contains(&filing.form))
+                                .take(opts.max_metric_filings) {
+        metrics.push(filing_metrics(sec_client, filing)?);
+    }
+
+    return Ok(CompanyBundle {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        company: CompanyIdentity {
+            cik,
+            name: company.name,
+            sic: company.sic,
+            sic_description: company.sic_description,
+            tickers: company.tickers,
+        },
+        filings: filings.iter().map(|filing| TimelineEntry {
+            form: filing.form.clone(),
+            accession: filing.accession_number.to_string(),
+            filed_on: filing.filed_on(),
+            report_date: date(&filing.report_date),
+            has_xbrl: filing.is_xbrl,
+        }).collect(),
+        metrics,
+    });
+}
+
+
+
+/// Bundle of the company listed as `ticker`, see `company_bundle_by_cik()`.
+pub fn company_bundle(sec_client: &SecClient, ticker: &str, opts: &BundleOptions)
+                      -> Result<CompanyBundle, SecFilingsError> {
+    let cik = ticker_to_cik(sec_client, ticker)?;
+    return company_bundle_by_cik(sec_client, cik, opts);
+}
@@ -0,0 +1,341 @@
+//! Opt-in on-disk cache of EDGAR documents, see
+//! `SecClientBuilder::cache_dir`.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use log::warn;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::accession::AccessionNumber;
+use crate::client::ARCHIVES_PREFIX;
+#[cfg(feature = "serde")]
+use crate::edgar::{ReportEntry, FILING_SUMMARY_PARSER_VERSION};
+
+
+/// Longest file name most filesystems accept is 255 bytes, longer keys
+/// are shortened and suffixed with a hash.
+const MAX_FILE_NAME_LEN: usize = 200;
+
+/// Subdirectory of parsed reports, one JSON file per filing.
+const REPORTS_DIR: &str = "reports";
+
+
+/// Directory of response bodies, one flat file per URL (query included)
+/// named after the base64url encoding of the URL.
+///
+/// Filed documents never change and are kept for good. Everything else,
+/// feeds, index.json listings and data.sec.gov responses, is only kept
+/// with a `metadata_ttl`, and for that long.
+///
+/// The reports parsed from each filing's FilingSummary.xml are kept too,
+/// under reports/, see `filing_reports()`.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    metadata_ttl: Option<Duration>,
+}
+
+
+/// Whether the body of `url` is a filed document, which never changes.
+fn is_immutable(url: &str) -> bool {
+    return url.starts_with(ARCHIVES_PREFIX) && !url.ends_with("index.json");
+}
+
+
+/// Write `body` to a temporary file renamed over `path`, so a killed
+/// process never leaves half a file behind.
+fn write_atomic(path: &Path, body: &str) -> io::Result<()> {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.tmp-{}", name, process::id()));
+
+    let written = fs::write(&temp, body).and_then(|_| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    return written;
+}
+
+
+/// Reports of one filing as `store_reports()` keeps them.
+#[cfg(feature = "serde")]
+#[derive(Debug, Serialize, Deserialize)]
+struct ReportSet {
+    /// `FILING_SUMMARY_PARSER_VERSION` of the parser the reports come from.
+    parser_version: u32,
+    accession: String,
+    reports: Vec<ReportEntry>,
+}
+
+
+impl ResponseCache {
+
+    /// Cache in `dir`, created if missing, holding filed documents only.
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        return Ok(Self {
+            dir: dir.to_path_buf(),
+            metadata_ttl: None,
+        });
+    }
+
+
+    /// Also keep responses that may change, for `ttl`.
+    pub fn with_metadata_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttl = Some(ttl);
+        return self;
+    }
+
+
+    pub fn dir(&self) -> &Path {
+        return &self.dir;
+    }
+
+
+    /// Whether responses of `url` are cached at all.
+    pub(crate) fn accepts(&self, url: &str) -> bool {
+        return is_immutable(url) || self.metadata_ttl.is_some();
+    }
+
+
+    fn path(&self, url: &str) -> PathBuf {
+        let mut name = URL_SAFE_NO_PAD.encode(url);
+
+        if name.len() > MAX_FILE_NAME_LEN {
+            // FNV-1a, stable across Rust releases unlike std's hasher
+            let mut hash: u64 = 0xcbf29ce484222325;
+            for byte in url.bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+            name.truncate(MAX_FILE_NAME_LEN - 17);
+            name = format!("{}-{:016x}", name, hash);
+        }
+
+        return self.dir.join(name);
+    }
+
+
+    /// Cached body of `url`, `None` on a miss or if it outlived the TTL.
+    pub(crate) fn load(&self, url: &str) -> Option<String> {
+        let path = self.path(url);
+
+        if !is_immutable(url) {
+            let ttl = self.metadata_ttl?;
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+            if age > ttl {
+                return None;
+            }
+        }
+
+        return fs::read_to_string(path).ok();
+    }
+
+
+    /// Store the body of `url`. It is written to a temporary file renamed
+    /// over the entry, so a killed process never leaves half an entry
+    /// behind. A failed write only costs a later download, so it is
+    /// logged rather than returned.
+    pub(crate) fn store(&self, url: &str, body: &str) {
+        if let Err(e) = write_atomic(&self.path(url), body) {
+            warn!("could not cache {}: {}", url, e);
+        }
+    }
+
+
+    #[cfg(feature = "serde")]
+    fn reports_path(&self, accession: &AccessionNumber) -> PathBuf {
+        return self.dir.join(REPORTS_DIR).join(format!("{}.json", accession));
+    }
+
+
+    /// Parsed reports of the filing `accession`, `None` on a miss or if
+    /// they come from another version of the FilingSummary.xml parser.
+    #[cfg(feature = "serde")]
+    pub(crate) fn load_reports(&self, accession: &AccessionNumber) -> Option<Vec<ReportEntry>> {
+        let json = fs::read_to_string(self.reports_path(accession)).ok()?;
+        let set: ReportSet = serde_json::from_str(&json).ok()?;
+
+        if set.parser_version != FILING_SUMMARY_PARSER_VERSION {
+            return None;
+        }
+        return Some(set.reports);
+    }
+
+
+    /// Store the parsed reports of the filing `accession`, like `store()`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn store_reports(&self, accession: &AccessionNumber, reports: &[ReportEntry]) {
+        let set = ReportSet {
+            parser_version: FILING_SUMMARY_PARSER_VERSION,
+            accession: accession.to_string(),
+            reports: reports.to_vec(),
+        };
+        let json = serde_json::to_string(&set).expect("reports serialize to JSON");
+
+        let path = self.reports_path(accession);
+        let written = fs::create_dir_all(self.dir.join(REPORTS_DIR))
+                          .and_then(|_| write_atomic(&path, &json));
+        if let Err(e) = written {
+            warn!("could not cache the reports of {}: {}", accession, e);
+        }
+    }
+
+
+    /// Drop the cached body of `url`, if any.
+    pub fn invalidate(&self, url: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(url)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+
+    /// Drop every cached body, and temporary files left by interrupted
+    /// writes. Parsed reports are kept, see `invalidate_reports()`.
+    pub fn invalidate_all(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        return Ok(());
+    }
+
+
+    /// Drop the parsed reports of every filing.
+    pub fn invalidate_reports(&self) -> io::Result<()> {
+        match fs::remove_dir_all(self.dir.join(REPORTS_DIR)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+
+    const DOCUMENT: &str = "https://www.sec.gov/Archives/edgar/data/320193/\
+                            000032019324000006/R2.htm";
+    const INDEX: &str = "https://www.sec.gov/Archives/edgar/data/320193/\
+                         000032019324000006/index.json";
+    const FEED: &str = "https://www.sec.gov/cgi-bin/browse-edgar?action=getcompany&CIK=320193";
+
+
+    fn temp_cache(test: &str) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("sec_filings_cache_{}_{}", test,
+                                                    process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        return ResponseCache::new(&dir).unwrap();
+    }
+
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        return fs::read_dir(dir).unwrap()
+                                .map(|entry| entry.unwrap().file_name().to_string_lossy()
+                                                  .into_owned())
+                                .collect();
+    }
+
+
+    #[test]
+    fn filed_documents_are_immutable() {
+        assert!(is_immutable(DOCUMENT));
+        assert!(is_immutable("https://www.sec.gov/Archives/edgar/data/320193/\
+                              000032019324000006/FilingSummary.xml"));
+
+        // listings and anything outside the archives change
+        assert!(!is_immutable(INDEX));
+        assert!(!is_immutable(FEED));
+        assert!(!is_immutable("https://data.sec.gov/submissions/CIK0000320193.json"));
+    }
+
+
+    #[test]
+    fn metadata_is_only_served_with_a_ttl() {
+        let cache = temp_cache("split");
+        cache.store(DOCUMENT, "document");
+        cache.store(INDEX, "listing");
+
+        assert_eq!(cache.load(DOCUMENT).as_deref(), Some("document"));
+        assert_eq!(cache.load(INDEX), None);
+        assert!(cache.accepts(DOCUMENT) && !cache.accepts(INDEX));
+
+        let cache = cache.with_metadata_ttl(Duration::from_secs(60));
+        assert_eq!(cache.load(INDEX).as_deref(), Some("listing"));
+
+        // past the TTL, documents are kept regardless
+        let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        for url in [DOCUMENT, INDEX] {
+            File::options().write(true).open(cache.path(url)).unwrap()
+                           .set_modified(hour_ago).unwrap();
+        }
+        assert_eq!(cache.load(INDEX), None);
+        assert_eq!(cache.load(DOCUMENT).as_deref(), Some("document"));
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+
+    #[test]
+    fn store_replaces_entries_whole() {
+        let cache = temp_cache("atomic");
+        cache.store(DOCUMENT, "first");
+        cache.store(DOCUMENT, "second");
+
+        assert_eq!(cache.load(DOCUMENT).as_deref(), Some("second"));
+        // no temporary file left next to the entry
+        assert_eq!(file_names(cache.dir()).len(), 1);
+
+        // a failed write leaves nothing behind either
+        fs::remove_dir_all(cache.dir()).unwrap();
+        cache.store(DOCUMENT, "lost");
+        assert!(!cache.dir().exists());
+        assert_eq!(cache.load(DOCUMENT), None);
+    }
+
+
+    #[test]
+    fn write_atomic_cleans_up_after_a_failed_rename() {
+        let cache = temp_cache("rename");
+        // a directory in the way of the entry
+        let path = cache.dir().join("entry");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("inside"), "").unwrap();
+
+        assert!(write_atomic(&path, "body").is_err());
+        assert_eq!(file_names(cache.dir()), ["entry"]);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+
+    #[test]
+    fn long_urls_get_short_file_names() {
+        let cache = temp_cache("long");
+        let url = format!("{}&{}", FEED, "x".repeat(400));
+        cache.store(&url, "long");
+
+        let name = cache.path(&url).file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(name.len(), MAX_FILE_NAME_LEN);
+        let cache = cache.with_metadata_ttl(Duration::from_secs(60));
+        assert_eq!(cache.load(&url).as_deref(), Some("long"));
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+//! Recognizing financial statements from their report shortnames.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+
+/// How to recognize a statement from its report shortname.
+pub(crate) struct StatementQuery {
+    /// what the statement is called in errors
+    pub what: &'static str,
+    /// shortname must contain one of these
+    pub keywords: &'static [&'static str],
+    /// and none of these
+    pub excluded: &'static [&'static str],
+    /// matches containing these are only used when nothing else matches
+    pub fallback: &'static [&'static str],
+}
+
+
+impl StatementQuery {
+
+    /// Whether a lowercased shortname is one of the statements queried.
+    pub fn matches(&self, name: &str) -> bool {
+        return self.keywords.iter().any(|kw| name.contains(kw))
+               && !self.excluded.iter().any(|ex| name.contains(ex));
+    }
+}
+
+
+pub(crate) const BALANCE_SHEET: StatementQuery = StatementQuery {
+    what: "balance sheet",
+    keywords: &["balance sheet", "financial condition"],
+    excluded: &["parenthetical"],
+    fallback: &[],
+};
+
+
+// filers name these inconsistently, and many combine the income statement
+// with comprehensive income, so the pure income statement is preferred
+pub(crate) const INCOME_STATEMENT: StatementQuery = StatementQuery {
+    what: "income statement",
+    keywords: &["statements of operations", "statement of operations",
+                "statements of income", "statement of income",
+                "income statements", "income statement",
+                "statements of earnings", "statement of earnings",
+                "profit and loss", "comprehensive income", "comprehensive loss"],
+    excluded: &["parenthetical"],
+    fallback: &["comprehensive"],
+};
+
+
+pub(crate) const CASH_FLOW_STATEMENT: StatementQuery = StatementQuery {
+    what: "cash flow statement",
+    keywords: &["cash flows", "cash flow statement"],
+    excluded: &["parenthetical"],
+    fallback: &[],
+};
+
+
+pub(crate) const STOCKHOLDERS_EQUITY: StatementQuery = StatementQuery {
+    what: "statement of stockholders' equity",
+    keywords: &["stockholders' equity", "shareholders' equity", "stockholders equity",
+                "shareholders equity", "changes in equity", "statement of equity",
+                "statements of equity"],
+    excluded: &["parenthetical"],
+    fallback: &[],
+};
+
+
+// disclosures about a statement rather than the statement itself
+const NOT_A_STATEMENT: [&str; 5] = ["(details", "(tables)", "(policies)", "narrative",
+                                    "parenthetical"];
+
+
+
+/// Kind of primary financial statement a report holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StatementKind {
+    BalanceSheet,
+    IncomeStatement,
+    ComprehensiveIncome,
+    CashFlow,
+    StockholdersEquity,
+}
+
+
+/// Classifies reports by their FilingSummary.xml shortname.
+pub struct StatementClassifier;
+
+
+impl StatementClassifier {
+
+    /// Statement held by the report called `shortname`, `None` for notes,
+    /// details, parenthetical and other non-statement reports.
+    pub fn classify(shortname: &str) -> Option<StatementKind> {
+        let name = shortname.to_lowercase().replace('\u{2019}', "'");
+
+        if NOT_A_STATEMENT.iter().any(|ex| name.contains(ex)) {
+            return None;
+        }
+
+        if BALANCE_SHEET.matches(&name) {
+            return Some(StatementKind::BalanceSheet);
+        }
+        if CASH_FLOW_STATEMENT.matches(&name) {
+            return Some(StatementKind::CashFlow);
+        }
+        if STOCKHOLDERS_EQUITY.matches(&name) {
+            return Some(StatementKind::StockholdersEquity);
+        }
+
+        if INCOME_STATEMENT.matches(&name) {
+            // "operations and comprehensive income" is still the income statement
+            let combined = ["operations", "earnings", "statements of income",
+                            "statement of income"].iter().any(|kw| name.contains(kw));
+
+            if name.contains("comprehensive") && !combined {
+                return Some(StatementKind::ComprehensiveIncome);
+            }
+            return Some(StatementKind::IncomeStatement);
+        }
+
+        return None;
+    }
+}
@@ -0,0 +1,1125 @@
+//! Rate limited HTTP client for EDGAR.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use std::thread::sleep;
+
+use bytes::Bytes;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use log::{debug, warn};
+use reqwest::header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER, USER_AGENT};
+use reqwest::StatusCode;
+
+use serde::Serialize;
+use static_assertions::assert_impl_all;
+use tower::util::MapRequestLayer;
+
+use crate::cache::ResponseCache;
+use crate::error::SecFilingsError;
+use crate::fundamentals::FundamentalsCache;
+use crate::labels::{fetch_label_linkbase, US_GAAP_TAXONOMY_YEAR};
+use crate::tickers::TickerMap;
+
+
+/// Immutable filing documents live under this prefix, and are the only
+/// requests routed to an archive mirror.
+pub(crate) const ARCHIVES_PREFIX: &str = "https://www.sec.gov/Archives/";
+
+
+/// Environment variable read by `SecClient::new()`.
+pub const USER_AGENT_ENV: &str = "SEC_USER_AGENT";
+
+
+/// Sliding one second window of the rate limiter: send times of the
+/// requests made within the last second.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimitState {
+    sent: VecDeque<Instant>,
+}
+
+
+impl RateLimitState {
+
+    pub(crate) fn new() -> Self {
+        return Self {
+            sent: VecDeque::new(),
+        };
+    }
+
+
+    /// Counts a request sent at `now` if it fits under `request_threshold`
+    /// within any one second window, otherwise returns how long until it
+    /// would. Never blocks, the caller sleeps with the lock released.
+    pub(crate) fn try_acquire(&mut self, now: Instant, request_threshold: u8)
+                              -> Result<(), Duration> {
+        let window = Duration::from_secs(1);
+        let request_threshold = usize::from(request_threshold.max(1));
+
+        // forget requests that left the window
+        while self.sent.front().is_some_and(|t| now.saturating_duration_since(*t) >= window) {
+            self.sent.pop_front();
+        }
+
+        // at the threshold, the oldest of the last `request_threshold`
+        // has to leave the window first
+        if self.sent.len() >= request_threshold {
+            let oldest = self.sent[self.sent.len() - request_threshold];
+            return Err(window - now.saturating_duration_since(oldest));
+        }
+
+        self.sent.push_back(now);
+        return Ok(());
+    }
+}
+
+
+/// Lock the state, a panic in another thread does not invalidate it.
+pub(crate) fn lock_state(state: &Mutex<RateLimitState>)
+                         -> std::sync::MutexGuard<'_, RateLimitState> {
+    return state.lock().unwrap_or_else(|e| e.into_inner());
+}
+
+
+/// Blocks until `state` lets a request through. The lock is only taken
+/// to check, never held while sleeping, so other threads can still
+/// check, or find the window has room for them, in the meantime.
+fn wait_for_slot(state: &Mutex<RateLimitState>, request_threshold: u8) {
+    loop {
+        let wait = lock_state(state).try_acquire(Instant::now(), request_threshold);
+        match wait {
+            Ok(()) => return,
+            Err(wait) => sleep(wait),
+        }
+    }
+}
+
+
+
+/// Request budget shared by several `SecClient`s, so a process that builds
+/// more than one client still stays under SEC's cap as a whole.
+///
+/// Clients do not share anything by default, each one paces itself
+/// independently unless built with `SecClient::with_shared_limiter` or
+/// `SecClientBuilder::shared_limiter`.
+pub struct SharedLimiter {
+    state: Mutex<RateLimitState>,
+    request_threshold: u8,
+}
+
+
+static GLOBAL_LIMITER: OnceLock<Arc<SharedLimiter>> = OnceLock::new();
+
+
+impl SharedLimiter {
+
+    pub fn new(request_threshold: u8) -> Self {
+        return Self {
+            state: Mutex::new(RateLimitState::new()),
+            request_threshold,
+        };
+    }
+
+
+    /// Process-wide limiter allowing 10 requests per second.
+    pub fn global() -> Arc<SharedLimiter> {
+        return GLOBAL_LIMITER.get_or_init(|| Arc::new(SharedLimiter::new(10)))
+                             .clone();
+    }
+
+
+    /// Blocks until a request may be sent.
+    fn acquire(&self) {
+        wait_for_slot(&self.state, self.request_threshold);
+    }
+
+
+    /// Counts a request sent at `now`, or how long until one may be, see
+    /// `RateLimitState::try_acquire()`.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        return lock_state(&self.state).try_acquire(now, self.request_threshold);
+    }
+}
+
+
+
+/// One request sent by a `SecClient` built with
+/// `SecClientBuilder::enable_logging`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestRecord {
+    pub url: String,
+    /// When the request was sent.
+    pub timestamp: Instant,
+    /// Response status, 0 if no response came back.
+    pub status: u16,
+    /// Time until the response headers arrived.
+    pub duration: Duration,
+}
+
+
+
+/// A response body as sent, see `SecClient::get_response()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecResponse {
+    pub bytes: Bytes,
+    /// Content-Type header, e.g. "text/html" or "application/pdf".
+    pub content_type: Option<String>,
+    pub status: u16,
+}
+
+
+impl SecResponse {
+
+    /// `bytes` as text, invalid UTF-8 replaced.
+    pub fn text(&self) -> String {
+        return String::from_utf8_lossy(&self.bytes).into_owned();
+    }
+}
+
+
+
+pub struct SecClient {
+    client: Client,
+    header: String,
+
+    // required to resepct threshold of 10 requests per second, the
+    // mutex keeps threads sharing a client from racing past it
+    rate_limit: Mutex<RateLimitState>,
+    request_threshold: u8,
+
+    // pacing shared with other clients, replaces the local threshold
+    shared_limiter: Option<Arc<SharedLimiter>>,
+    // requests sent by this client, whichever limiter paced them
+    requests_sent: AtomicU64,
+    // requests that reached the network, mirror included, and the
+    // connections opened for them, the rest went over pooled ones
+    network_requests: AtomicU64,
+    connections_opened: Arc<AtomicU64>,
+    // every request sent, if logging is enabled
+    request_log: Option<Arc<Mutex<Vec<RequestRecord>>>>,
+
+    // retries of a request that failed transiently, and the delay
+    // before the first one, doubled for each further retry
+    max_retries: u32,
+    retry_base_delay: Duration,
+
+    // base URL standing in for ARCHIVES_PREFIX, e.g. an internal mirror
+    archive_mirror: Option<String>,
+    // where Archives documents come from when the mirror lacks them,
+    // ARCHIVES_PREFIX unless set
+    archives_base: Option<String>,
+
+    // response bodies, see ResponseCache for what is kept how long
+    cache: Option<ResponseCache>,
+    // refetch cached responses, refreshing the cache
+    cache_bust: bool,
+    // company_facts() results, see FundamentalsCache
+    fundamentals_cache: Option<Mutex<FundamentalsCache>>,
+
+    // company_tickers.json, downloaded on first use
+    ticker_map: OnceLock<TickerMap>,
+    // US GAAP concept labels, downloaded on first use
+    us_gaap_labels: OnceLock<HashMap<String, String>>,
+}
+
+
+assert_impl_all!(SecClient: Send, Sync);
+
+
+/// Builder for `SecClient`, used to supply the contact info SEC expects
+/// in the `User-Agent` header along with rate limit and timeout settings.
+pub struct SecClientBuilder {
+    user_agent: Option<String>,
+    request_threshold: u8,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    pool_max_idle_per_host: usize,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    shared_limiter: Option<Arc<SharedLimiter>>,
+    archive_mirror: Option<String>,
+    archives_base: Option<String>,
+    cache_dir: Option<PathBuf>,
+    cache_ttl: Option<Duration>,
+    cache_bust: bool,
+    fundamentals_cache: Option<(usize, Duration)>,
+    logging: bool,
+    max_retries: u32,
+    retry_base_delay: Duration,
+}
+
+
+impl Default for SecClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl SecClientBuilder {
+
+    pub fn new() -> Self {
+        return Self {
+            user_agent: None,
+            request_threshold: 10,
+            timeout: None,
+            connect_timeout: None,
+            // a connection per request in flight at 10 requests/s, kept
+            // open across the pauses of the rate limiter
+            pool_max_idle_per_host: 10,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            shared_limiter: None,
+            archive_mirror: None,
+            archives_base: None,
+            cache_dir: None,
+            cache_ttl: None,
+            cache_bust: false,
+            fundamentals_cache: None,
+            logging: false,
+            max_retries: 3,
+            retry_base_delay: Duration::from_millis(100),
+        };
+    }
+
+
+    /// Contact info sent as `User-Agent`, e.g. "Company Name admin@company.com".
+    pub fn user_agent(mut self, email: &str) -> Self {
+        self.user_agent = Some(email.to_string());
+        return self;
+    }
+
+
+    /// Compose the `User-Agent` in the "Company Name email@domain" format
+    /// SEC asks for.
+    pub fn contact(self, company: &str, email: &str) -> Self {
+        let user_agent = format!("{} {}", company.trim(), email.trim());
+        return self.user_agent(user_agent.trim());
+    }
+
+
+    /// Maximum number of requests per second (SEC allows 10).
+    pub fn request_threshold(mut self, n: u8) -> Self {
+        self.request_threshold = n;
+        return self;
+    }
+
+
+    /// Timeout applied to every request, from connecting until the body
+    /// is read. None by default. EDGAR usually answers within a few
+    /// hundred milliseconds, but large documents (company facts, full
+    /// submission .txt files) take seconds to download, so 30s or more
+    /// is a sensible choice.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        return self;
+    }
+
+
+    /// Timeout for establishing a connection only, None by default.
+    /// Connecting to sec.gov takes well under a second, 5-10s catches a
+    /// dead network without cutting off slow downloads.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        return self;
+    }
+
+
+    /// Idle connections kept open per host for reuse (default 10). More
+    /// only helps with many threads sharing the client.
+    pub fn pool_max_idle_per_host(mut self, n: usize) -> Self {
+        self.pool_max_idle_per_host = n;
+        return self;
+    }
+
+
+    /// How long an idle connection is kept for reuse (default 90s),
+    /// `None` to keep it until the server closes it.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        return self;
+    }
+
+
+    /// Interval of TCP keep-alive probes on open connections (default
+    /// 60s), `None` to send none.
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        return self;
+    }
+
+
+    /// How often a request answered with 429 or 5xx, or cut off by a
+    /// connection error, is retried before giving up (default 3).
+    pub fn max_retries(mut self, n: u32) -> Self {
+        self.max_retries = n;
+        return self;
+    }
+
+
+    /// Delay before the first retry, doubled for each further one
+    /// (default 100ms). A `Retry-After` header takes precedence.
+    pub fn retry_base_delay(mut self, delay: Duration) -> Self {
+        self.retry_base_delay = delay;
+        return self;
+    }
+
+
+    /// Pace requests with a limiter shared by other clients, instead of
+    /// this client's own `request_threshold`.
+    pub fn shared_limiter(mut self, limiter: Arc<SharedLimiter>) -> Self {
+        self.shared_limiter = Some(limiter);
+        return self;
+    }
+
+
+    /// Fetch immutable `https://www.sec.gov/Archives/...` documents from a
+    /// mirror instead, e.g. "https://edgar-mirror.internal/Archives/".
+    /// Metadata queries (browse-edgar, index feeds) keep going to sec.gov.
+    /// Documents the mirror answers with 404, or that it cannot be reached
+    /// for, are fetched from sec.gov; other error statuses fail. Cached
+    /// documents are keyed by their sec.gov URL, whichever host sent them.
+    pub fn archive_mirror(mut self, base_url: &str) -> Self {
+        self.archive_mirror = Some(with_trailing_slash(base_url));
+        return self;
+    }
+
+
+    /// Fetch `https://www.sec.gov/Archives/...` documents the mirror lacks
+    /// from `base_url` instead of sec.gov, e.g. a local server standing in
+    /// for EDGAR in tests. Like the mirror, it only changes where they come
+    /// from, not how they are cached.
+    pub fn archives_base(mut self, base_url: &str) -> Self {
+        self.archives_base = Some(with_trailing_slash(base_url));
+        return self;
+    }
+
+
+    /// Keep filing documents (`https://www.sec.gov/Archives/...`) in
+    /// `dir` and serve them from there on later requests, without going
+    /// through the rate limiter. Feeds and other metadata are only cached
+    /// with a `cache_ttl`. Off by default.
+    pub fn cache_dir(mut self, dir: &Path) -> Self {
+        self.cache_dir = Some(dir.to_path_buf());
+        return self;
+    }
+
+
+    /// Also cache responses that change over time, the Atom feed,
+    /// index.json listings and data.sec.gov, for `ttl`. Needs a
+    /// `cache_dir`.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        return self;
+    }
+
+
+    /// Ignore cached responses and fetch everything again, storing the
+    /// fresh bodies in the cache.
+    pub fn cache_bust(mut self, bust: bool) -> Self {
+        self.cache_bust = bust;
+        return self;
+    }
+
+
+    /// Keep what `company_facts()` returns for up to `capacity` companies
+    /// in memory, each for `ttl`, see `FundamentalsCache`. Off by default.
+    pub fn with_fundamentals_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.fundamentals_cache = Some((capacity, ttl));
+        return self;
+    }
+
+
+    /// Record every request sent, see `SecClient::request_log`.
+    pub fn enable_logging(mut self) -> Self {
+        self.logging = true;
+        return self;
+    }
+
+
+    /// Fails if no `User-Agent` was given, or it does not look like it
+    /// contains contact info.
+    pub fn build(self) -> Result<SecClient, SecFilingsError> {
+        let header = self.user_agent.ok_or_else(|| SecFilingsError::InvalidUserAgent(
+                     format!("no user agent set, use .user_agent() or set {}",
+                             USER_AGENT_ENV)))?;
+        validate_user_agent(&header)?;
+
+        // the connector is only called for a connection the pool lacks
+        let connections_opened = Arc::new(AtomicU64::new(0));
+        let counter = count_calls(connections_opened.clone());
+
+        let mut client_builder = Client::builder()
+                                 .pool_max_idle_per_host(self.pool_max_idle_per_host)
+                                 .pool_idle_timeout(self.pool_idle_timeout)
+                                 .tcp_keepalive(self.tcp_keepalive)
+                                 .connector_layer(MapRequestLayer::new(counter));
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            client_builder = client_builder.connect_timeout(timeout);
+        }
+        let client = client_builder.build()?;
+
+        let cache = match &self.cache_dir {
+            Some(dir) => Some(ResponseCache::new(dir).map_err(|e| SecFilingsError::Cache(
+                              format!("{}: {}", dir.display(), e)))?),
+            None => None,
+        };
+        let cache = match self.cache_ttl {
+            Some(ttl) => cache.map(|cache| cache.with_metadata_ttl(ttl)),
+            None => cache,
+        };
+
+        let instance = SecClient {
+            client,
+            header,
+            rate_limit: Mutex::new(RateLimitState::new()),
+            request_threshold: self.request_threshold,
+            shared_limiter: self.shared_limiter,
+            requests_sent: AtomicU64::new(0),
+            network_requests: AtomicU64::new(0),
+            connections_opened,
+            request_log: self.logging.then(|| Arc::new(Mutex::new(vec![]))),
+            max_retries: self.max_retries,
+            retry_base_delay: self.retry_base_delay,
+            archive_mirror: self.archive_mirror,
+            archives_base: self.archives_base,
+            cache,
+            cache_bust: self.cache_bust,
+            fundamentals_cache: self.fundamentals_cache.map(|(capacity, ttl)|
+                                    Mutex::new(FundamentalsCache::new(capacity, ttl))),
+            ticker_map: OnceLock::new(),
+            us_gaap_labels: OnceLock::new(),
+        };
+
+        return Ok(instance);
+    }
+}
+
+
+/// `base_url` ending in '/', to append document paths to.
+fn with_trailing_slash(base_url: &str) -> String {
+    let mut base_url = base_url.to_string();
+    if !base_url.ends_with('/') {
+        base_url.push('/');
+    }
+    return base_url;
+}
+
+
+/// `User-Agent` held by `USER_AGENT_ENV`.
+fn env_user_agent() -> Result<String, SecFilingsError> {
+    return std::env::var(USER_AGENT_ENV).map_err(|_| SecFilingsError::InvalidUserAgent(format!(
+        "{} is not set, it should hold e.g. \"Company Name admin@company.com\"",
+        USER_AGENT_ENV)));
+}
+
+
+/// Passes its argument through, counting the calls in `counter`.
+fn count_calls<T>(counter: Arc<AtomicU64>) -> impl Fn(T) -> T + Clone {
+    return move |value| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    };
+}
+
+
+/// EDGAR answers with these for brief windows even at legal request rates.
+/// Other statuses, 404 included, fail right away.
+pub(crate) fn is_retriable(status: StatusCode) -> bool {
+    return status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+}
+
+
+/// Connection failures, timeouts and transfers cut short.
+pub(crate) fn is_transient(e: &reqwest::Error) -> bool {
+    return e.is_connect() || e.is_timeout() || e.is_body() || e.is_request();
+}
+
+
+/// Delay asked for by a `Retry-After` header in seconds. The HTTP date
+/// form is not used by SEC and is ignored.
+pub(crate) fn retry_after_delay(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    return value.trim().parse().ok().map(Duration::from_secs);
+}
+
+
+/// Delay before retry `attempt + 1`, unless the server asked for one.
+pub(crate) fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    return base.saturating_mul(2u32.saturating_pow(attempt));
+}
+
+
+/// Marker of the page SEC serves to clients without a proper User-Agent.
+const UNDECLARED_TOOL_MARKER: &str = "Undeclared Automated Tool";
+
+/// How much of an error page to keep in `SecFilingsError::HttpStatus`,
+/// in characters.
+const ERROR_BODY_LEN: usize = 300;
+
+
+/// `response` read in full.
+fn read_response(response: Response) -> reqwest::Result<SecResponse> {
+    let status = response.status().as_u16();
+    let content_type = response.headers().get(CONTENT_TYPE)
+                               .and_then(|value| value.to_str().ok()).map(str::to_string);
+
+    return Ok(SecResponse { bytes: response.bytes()?, content_type, status });
+}
+
+
+
+/// Error for a response that was not a success, with the start of its
+/// body to tell what the server complained about.
+fn status_error(response: Response) -> SecFilingsError {
+    let status = response.status();
+    return status_error_for(status, &response.text().unwrap_or_default());
+}
+
+
+/// `status_error()` for a response whose body was already read, e.g. by
+/// the async client.
+pub(crate) fn status_error_for(status: StatusCode, body: &str) -> SecFilingsError {
+    if status == StatusCode::FORBIDDEN && body.contains(UNDECLARED_TOOL_MARKER) {
+        return SecFilingsError::UndeclaredAutomatedTool;
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return SecFilingsError::RateLimited;
+    }
+
+    return SecFilingsError::HttpStatus {
+        status: status.as_u16(),
+        body: body.chars().take(ERROR_BODY_LEN).collect(),
+    };
+}
+
+
+/// SEC wants a company name and a contact email, we can only check the
+/// latter is present.
+pub(crate) fn validate_user_agent(header: &str) -> Result<(), SecFilingsError> {
+    if header.trim().is_empty() {
+        return Err(SecFilingsError::InvalidUserAgent(
+                   "user agent is empty".to_string()));
+    }
+
+    if !header.contains('@') {
+        return Err(SecFilingsError::InvalidUserAgent(format!(
+                   "'{}' has no contact email", header)));
+    }
+
+    return Ok(());
+}
+
+
+impl SecClient {
+
+    /// Client with default settings, using the `User-Agent` from the
+    /// `SEC_USER_AGENT` environment variable.
+    pub fn new() -> Result<Self, SecFilingsError> {
+        return SecClientBuilder::new().user_agent(&env_user_agent()?).build();
+    }
+
+
+    /// Like `new()`, `User-Agent` from the environment included, but
+    /// paced by `limiter`, e.g. `SharedLimiter::global()`. With the
+    /// `User-Agent` at hand, use `SecClientBuilder::shared_limiter`.
+    pub fn with_shared_limiter(limiter: Arc<SharedLimiter>) -> Result<Self, SecFilingsError> {
+        return SecClientBuilder::new().user_agent(&env_user_agent()?)
+                                      .shared_limiter(limiter)
+                                      .build();
+    }
+
+
+    /// Client sending "`company` `email`" as `User-Agent`.
+    pub fn with_contact(company: &str, email: &str) -> Result<Self, SecFilingsError> {
+        return SecClientBuilder::new().contact(company, email).build();
+    }
+
+
+    /// The `User-Agent` sent with every request.
+    pub fn user_agent(&self) -> &str {
+        return &self.header;
+    }
+
+
+    /// Number of requests this client has sent.
+    pub fn requests_sent(&self) -> u64 {
+        return self.requests_sent.load(Ordering::Relaxed);
+    }
+
+
+    /// Connections opened to send requests, to sec.gov or the mirror.
+    pub fn connections_opened(&self) -> u64 {
+        return self.connections_opened.load(Ordering::Relaxed);
+    }
+
+
+    /// Requests sent over a connection kept open from an earlier one.
+    /// High relative to `connections_opened()` when the pool settings
+    /// suit the request rate.
+    pub fn connections_reused(&self) -> u64 {
+        let requests = self.network_requests.load(Ordering::Relaxed);
+        return requests.saturating_sub(self.connections_opened());
+    }
+
+
+    /// Requests sent so far, oldest first, `None` unless built with
+    /// `SecClientBuilder::enable_logging`. Retries and mirror requests
+    /// are listed too, cache hits are not.
+    pub fn request_log(&self) -> Option<Vec<RequestRecord>> {
+        return self.request_log.as_ref()
+                   .map(|log| log.lock().unwrap_or_else(|e| e.into_inner()).clone());
+    }
+
+
+    /// Send `request` with the `User-Agent`, logging it.
+    fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        self.network_requests.fetch_add(1, Ordering::Relaxed);
+
+        let started = Instant::now();
+        let sent = request.header(USER_AGENT, self.header.as_str()).send();
+        self.log_request(&sent, started);
+
+        return sent;
+    }
+
+
+    /// Add the outcome of a request sent at `started` to the log.
+    fn log_request(&self, sent: &reqwest::Result<Response>, started: Instant) {
+        let log = match &self.request_log {
+            Some(log) => log,
+            None => return,
+        };
+
+        let (url, status) = match sent {
+            Ok(response) => (response.url().to_string(), response.status().as_u16()),
+            Err(e) => (e.url().map(|url| url.to_string()).unwrap_or_default(),
+                       e.status().map_or(0, |status| status.as_u16())),
+        };
+
+        log.lock().unwrap_or_else(|e| e.into_inner()).push(RequestRecord {
+            url,
+            timestamp: started,
+            status,
+            duration: started.elapsed(),
+        });
+    }
+
+
+    /// Ticker to CIK map, downloaded on the first call and kept for the
+    /// lifetime of the client.
+    pub fn ticker_map(&self) -> Result<&TickerMap, SecFilingsError> {
+        if let Some(map) = self.ticker_map.get() {
+            return Ok(map);
+        }
+
+        let map = TickerMap::fetch(self)?;
+        return Ok(self.ticker_map.get_or_init(|| map));
+    }
+
+
+    /// Standard labels of the US GAAP taxonomy concepts, downloaded on
+    /// the first call and kept for the lifetime of the client.
+    pub fn us_gaap_labels(&self) -> Result<&HashMap<String, String>, SecFilingsError> {
+        if let Some(labels) = self.us_gaap_labels.get() {
+            return Ok(labels);
+        }
+
+        let labels = fetch_label_linkbase(self, US_GAAP_TAXONOMY_YEAR)?;
+        return Ok(self.us_gaap_labels.get_or_init(|| labels));
+    }
+
+
+    /// Cache of `company_facts()` results, if built with
+    /// `SecClientBuilder::with_fundamentals_cache`.
+    pub fn fundamentals_cache(&self) -> Option<&Mutex<FundamentalsCache>> {
+        return self.fundamentals_cache.as_ref();
+    }
+
+
+    /// The document cache, if built with `SecClientBuilder::cache_dir`.
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        return self.cache.as_ref();
+    }
+
+
+    /// The cache to serve from, none when busting it.
+    #[cfg(feature = "serde")]
+    pub(crate) fn cache_to_read(&self) -> Option<&ResponseCache> {
+        return self.cache.as_ref().filter(|_| !self.cache_bust);
+    }
+
+
+    pub fn builder() -> SecClientBuilder {
+        return SecClientBuilder::new();
+    }
+
+
+    fn threshold_status(&self) {
+        self.requests_sent.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(limiter) = &self.shared_limiter {
+            limiter.acquire();
+            return;
+        }
+
+        wait_for_slot(&self.rate_limit, self.request_threshold);
+    }
+
+
+
+    /// `url` rewritten to the archive mirror, if it is routed there.
+    fn mirror_url(&self, url: &str) -> Option<String> {
+        let mirror = self.archive_mirror.as_ref()?;
+        let path = url.strip_prefix(ARCHIVES_PREFIX)?;
+        return Some(format!("{}{}", mirror, path));
+    }
+
+
+    /// `url` rewritten to the `archives_base`, if set and under it.
+    fn archives_url(&self, url: &str) -> String {
+        let rewritten = self.archives_base.as_ref().and_then(|base| {
+            url.strip_prefix(ARCHIVES_PREFIX).map(|path| format!("{}{}", base, path))
+        });
+        return rewritten.unwrap_or_else(|| url.to_string());
+    }
+
+
+
+    /// Send the request built by `request` and `read` the response,
+    /// retrying transient failures after `Retry-After`, or `2^attempt *
+    /// retry_base_delay` if SEC did not send one. Every attempt counts
+    /// against the limit.
+    fn get_with_retry<F, R, T>(&self, request: F, read: R) -> Result<T, SecFilingsError>
+    where
+        F: Fn() -> RequestBuilder,
+        R: Fn(Response) -> reqwest::Result<T>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            self.threshold_status();
+            let retries_left = attempt < self.max_retries;
+
+            let sent = self.send(request());
+
+            let (failure, retry_after) = match sent {
+                Ok(response) if is_retriable(response.status()) => {
+                    if !retries_left {
+                        return Err(status_error(response));
+                    }
+                    (format!("{} returned {}", response.url(), response.status()),
+                     retry_after_delay(response.headers()))
+                }
+                Ok(response) if !response.status().is_success() => {
+                    return Err(status_error(response));
+                }
+                Ok(response) => {
+                    let url = response.url().to_string();
+                    match read(response) {
+                        Ok(body) => {
+                            if attempt > 0 {
+                                debug!("{} succeeded on attempt {}", url, attempt + 1);
+                            }
+                            return Ok(body);
+                        }
+                        Err(e) if retries_left && is_transient(&e) => (e.to_string(), None),
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                Err(e) if retries_left && is_transient(&e) => (e.to_string(), None),
+                Err(e) => return Err(e.into()),
+            };
+
+            let delay = retry_after.unwrap_or(backoff_delay(self.retry_base_delay, attempt));
+            attempt += 1;
+            warn!("{}, retry {} of {} in {:?}", failure, attempt, self.max_retries, delay);
+            sleep(delay);
+        }
+    }
+
+
+
+    // GET request from basic URL, non-2xx responses are errors
+    pub fn get(&self, url: &str) -> Result<String, SecFilingsError> {
+        return self.cached(url, || self.fetch(url));
+    }
+
+
+    /// Body of `key` from the cache if it holds it, otherwise from
+    /// `fetch`, stored for next time.
+    fn cached<F>(&self, key: &str, fetch: F) -> Result<String, SecFilingsError>
+    where
+        F: FnOnce() -> Result<String, SecFilingsError>,
+    {
+        let cache = match &self.cache {
+            Some(cache) if cache.accepts(key) => cache,
+            _ => return fetch(),
+        };
+
+        if !self.cache_bust {
+            if let Some(body) = cache.load(key) {
+                debug!("{} served from cache", key);
+                return Ok(body);
+            }
+        }
+
+        let body = fetch()?;
+        cache.store(key, &body);
+        return Ok(body);
+    }
+
+
+    /// Body of `url` handed to `parse` as a reader, see `get_reader()`.
+    pub(crate) fn get_with_reader<P, T>(&self, url: &str, parse: P) -> Result<T, SecFilingsError>
+    where
+        P: FnOnce(&mut dyn Read) -> Result<T, SecFilingsError>,
+    {
+        return parse(&mut HttpFetch::get_reader(self, url)?);
+    }
+
+
+    /// Body of `url` as bytes, for binary documents such as images and
+    /// PDFs. Archives documents come from the mirror like with `get()`,
+    /// but nothing is cached.
+    pub fn get_bytes(&self, url: &str) -> Result<Bytes, SecFilingsError> {
+        return Ok(self.get_response(url)?.bytes);
+    }
+
+
+    /// Like `get_bytes()`, with `params` as the query.
+    pub fn get_bytes_with_params<T: Serialize + ?Sized>(&self, url: &str, params: &T)
+                                                        -> Result<Bytes, SecFilingsError> {
+        return Ok(self.get_response_with_params(url, params)?.bytes);
+    }
+
+
+    /// Body of `url` with its content type, to tell e.g. HTML from PDF.
+    /// Fetched like `get_bytes()`.
+    pub fn get_response(&self, url: &str) -> Result<SecResponse, SecFilingsError> {
+        return self.fetch_with(url, read_response);
+    }
+
+
+    /// Like `get_response()`, with `params` as the query.
+    pub fn get_response_with_params<T: Serialize + ?Sized>(&self, url: &str, params: &T)
+                                                           -> Result<SecResponse, SecFilingsError> {
+        return self.get_with_retry(|| self.client.get(url).query(params), read_response);
+    }
+
+
+    fn fetch(&self, url: &str) -> Result<String, SecFilingsError> {
+        return self.fetch_with(url, Response::text);
+    }
+
+
+    /// `read` the response for `url`, from the archive mirror if it is
+    /// routed there and the mirror has it, otherwise from sec.gov.
+    fn fetch_with<R, T>(&self, url: &str, read: R) -> Result<T, SecFilingsError>
+    where
+        R: Fn(Response) -> reqwest::Result<T>,
+    {
+        // the mirror is not sec.gov, so it does not count against the limit
+        if let Some(mirror_url) = self.mirror_url(url) {
+            match self.send(self.client.get(&mirror_url)) {
+                Ok(response) if response.status().is_success() => return Ok(read(response)?),
+                Ok(response) if response.status() != StatusCode::NOT_FOUND => {
+                    return Err(status_error(response));
+                }
+                Ok(_) => debug!("{} not on mirror, falling back to sec.gov", url),
+                // a mirror that is down should not take the documents with it
+                Err(e) if e.is_connect() => {
+                    warn!("mirror unreachable for {}, falling back to sec.gov: {}", url, e);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let url = self.archives_url(url);
+        return self.get_with_retry(|| self.client.get(&url), &read);
+    }
+
+    // GET request from URL with query parameters
+    pub fn get_with_params<T: Serialize + ?Sized>(&self, url: &str, params: &T) ->
+                           Result<String, SecFilingsError> {
+
+        if self.cache.is_none() {
+            return self.get_with_retry(|| self.client.get(url).query(params), Response::text);
+        }
+
+        // the cache is keyed by the URL with its query
+        let key = self.client.get(url).query(params).build()?.url().to_string();
+        return self.cached(&key, || self.get_with_retry(|| self.client.get(url).query(params),
+                                                        Response::text));
+    }
+}
+
+
+
+/// Where the pipeline functions (`documents()`, `filing_summaries()`,
+/// `master_reports()`, `balance_sheet_first()`, ...) get response bodies
+/// from: `SecClient` for EDGAR, `FixtureClient` for canned responses.
+pub trait HttpFetch {
+
+    /// Body of `url`.
+    fn get(&self, url: &str) -> Result<String, SecFilingsError>;
+
+    /// Body of `url` with `params` as its query.
+    fn get_with_params(&self, url: &str, params: &[(&str, String)])
+                       -> Result<String, SecFilingsError>;
+
+    /// Body of `url` as a reader, for JSON documents too large to hold
+    /// whole. Read in full by `get()` unless the fetcher can stream it.
+    fn get_reader(&self, url: &str) -> Result<Box<dyn Read + '_>, SecFilingsError> {
+        return Ok(Box::new(io::Cursor::new(self.get(url)?)));
+    }
+}
+
+
+impl HttpFetch for SecClient {
+
+    fn get(&self, url: &str) -> Result<String, SecFilingsError> {
+        return SecClient::get(self, url);
+    }
+
+
+    fn get_with_params(&self, url: &str, params: &[(&str, String)])
+                       -> Result<String, SecFilingsError> {
+        return SecClient::get_with_params(self, url, params);
+    }
+
+
+    /// Streamed from the network so a large JSON document is never held
+    /// in memory whole. Responses the cache keeps are read in full and
+    /// cached as usual. Not for Archives documents, the mirror is not
+    /// consulted.
+    fn get_reader(&self, url: &str) -> Result<Box<dyn Read + '_>, SecFilingsError> {
+        if self.cache.as_ref().is_some_and(|cache| cache.accepts(url)) {
+            return Ok(Box::new(io::Cursor::new(self.get(url)?)));
+        }
+
+        let response = self.get_with_retry(|| self.client.get(url), Ok)?;
+        return Ok(Box::new(BufReader::new(response)));
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+
+    #[test]
+    fn undeclared_automated_tool() {
+        let body = "<html><title>SEC.gov | Request Rate Threshold Exceeded</title>\
+                    Your Request Originates from an Undeclared Automated Tool</html>";
+        assert!(matches!(status_error_for(StatusCode::FORBIDDEN, body),
+                         SecFilingsError::UndeclaredAutomatedTool));
+
+        // any other 403 is an ordinary status error
+        assert!(matches!(status_error_for(StatusCode::FORBIDDEN, "Forbidden"),
+                         SecFilingsError::HttpStatus { status: 403, .. }));
+    }
+
+
+    #[test]
+    fn too_many_requests() {
+        assert!(matches!(status_error_for(StatusCode::TOO_MANY_REQUESTS, "slow down"),
+                         SecFilingsError::RateLimited));
+    }
+
+
+    #[test]
+    fn other_statuses_keep_the_start_of_the_body() {
+        match status_error_for(StatusCode::NOT_FOUND, "Not Found") {
+            SecFilingsError::HttpStatus { status, body } => {
+                assert_eq!(status, 404);
+                assert_eq!(body, "Not Found");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // cut after 300 characters, not bytes
+        let page = "\u{e9}".repeat(400);
+        match status_error_for(StatusCode::BAD_GATEWAY, &page) {
+            SecFilingsError::HttpStatus { status, body } => {
+                assert_eq!(status, 502);
+                assert_eq!(body, "\u{e9}".repeat(300));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+
+    #[test]
+    fn try_acquire_slides_the_window() {
+        let mut state = RateLimitState::new();
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            assert_eq!(state.try_acquire(start, 10), Ok(()));
+        }
+        assert_eq!(state.try_acquire(start, 10), Err(Duration::from_secs(1)));
+
+        let later = start + Duration::from_millis(999);
+        assert_eq!(state.try_acquire(later, 10), Err(Duration::from_millis(1)));
+        assert_eq!(state.try_acquire(start + Duration::from_secs(1), 10), Ok(()));
+    }
+
+
+    #[test]
+    fn injected_clock_never_exceeds_threshold() {
+        let mut state = RateLimitState::new();
+        let mut now = Instant::now();
+        let mut sent = vec![];
+
+        // a caller that sleeps exactly as long as it is told
+        while sent.len() < 55 {
+            match state.try_acquire(now, 10) {
+                Ok(()) => sent.push(now),
+                Err(wait) => now += wait,
+            }
+            now += Duration::from_millis(7);
+        }
+
+        let window = Duration::from_secs(1);
+        for (i, first) in sent.iter().enumerate() {
+            let in_window = sent[i..].iter().take_while(|t| t.duration_since(*first) < window)
+                                     .count();
+            assert!(in_window <= 10, "{} requests within a second", in_window);
+        }
+        assert!(sent[54].duration_since(sent[0]) >= Duration::from_secs(5));
+    }
+
+
+    #[test]
+    fn lock_is_free_while_waiting() {
+        let state = Arc::new(Mutex::new(RateLimitState::new()));
+        for _ in 0..2 {
+            assert_eq!(lock_state(&state).try_acquire(Instant::now(), 2), Ok(()));
+        }
+
+        let waiter = {
+            let state = state.clone();
+            thread::spawn(move || wait_for_slot(&state, 2))
+        };
+        thread::sleep(Duration::from_millis(100));
+
+        // the waiter sleeps for most of a second, without the lock
+        assert!(!waiter.is_finished());
+        assert!(state.try_lock().is_ok());
+
+        waiter.join().unwrap();
+        assert_eq!(lock_state(&state).sent.len(), 1);
+    }
+}
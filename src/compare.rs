@@ -0,0 +1,190 @@
+//! Period over period comparison of two statements.
+
+use std::collections::HashMap;
+
+use crate::parse::{normalize_label, row_id, StatementData};
+use crate::scale::format_cell;
+
+
+/// Written where a row is missing from either statement, or either value
+/// is not a number.
+const NOT_AVAILABLE: &str = "N/A";
+
+
+impl StatementData {
+
+    /// Change from `base` to `comparison` per row, e.g. last year's
+    /// statement to this year's. Each row holds the label, the value in
+    /// `base`, the value in `comparison` and the difference.
+    ///
+    /// The first value column of each is compared, the most recent
+    /// period in an R-file. Rows are matched by label, ignoring case and
+    /// punctuation, a repeated label with its occurrence of the same
+    /// label. Values are scaled to units first, see `normalized_value()`.
+    pub fn diff(base: &StatementData, comparison: &StatementData) -> StatementData {
+        return compare(base, comparison, "Change", |from, to| Some(format_cell(to - from)));
+    }
+
+
+    /// Like `diff()`, with the change as a percentage of the base value,
+    /// "N/A" where that is zero.
+    pub fn pct_change(base: &StatementData, comparison: &StatementData) -> StatementData {
+        return compare(base, comparison, "% Change", |from, to| {
+            if from == 0.0 {
+                return None;
+            }
+            return Some(format!("{:.2}%", (to - from) / from.abs() * 100.0));
+        });
+    }
+}
+
+
+
+/// Heading of the first value column, e.g. "Sep. 28, 2024". Multi-row
+/// headers put the title and period lengths ("3 Months Ended") above the
+/// dates, so the last row holds no title cell.
+fn first_heading(data: &StatementData) -> String {
+    let heading = match data.headers.as_slice() {
+        [only] => only.get(1),
+        [.., last] => last.first(),
+        [] => None,
+    };
+    return heading.cloned().unwrap_or_default();
+}
+
+
+/// Label of row `row` of `data`.
+fn label(data: &StatementData, row: usize) -> &str {
+    return data.data[row].first().map_or("", String::as_str);
+}
+
+
+/// Normalized label and occurrence of each row, to match rows by.
+fn row_keys(data: &StatementData) -> Vec<(String, usize)> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+
+    return (0..data.data.len()).map(|row| {
+        let label = normalize_label(label(data, row));
+        let occurrence = occurrences.entry(label.clone()).or_default();
+        *occurrence += 1;
+        (label, *occurrence - 1)
+    }).collect();
+}
+
+
+/// Rows of `base` then those only in `comparison`, with `change` of the
+/// first value column of each.
+fn compare<F>(base: &StatementData, comparison: &StatementData, heading: &str, change: F)
+              -> StatementData
+where
+    F: Fn(f64, f64) -> Option<String>,
+{
+    let base_keys = row_keys(base);
+    let comparison_keys = row_keys(comparison);
+    let comparison_rows: HashMap<&(String, usize), usize> = comparison_keys.iter().enumerate()
+                                                                .map(|(i, key)| (key, i)).collect();
+
+    // (label, row in base, row in comparison)
+    let mut rows: Vec<(&str, Option<usize>, Option<usize>)> = vec![];
+    for (i, key) in base_keys.iter().enumerate() {
+        rows.push((label(base, i), Some(i), comparison_rows.get(key).copied()));
+    }
+    for (i, key) in comparison_keys.iter().enumerate() {
+        if !base_keys.contains(key) {
+            rows.push((label(comparison, i), None, Some(i)));
+        }
+    }
+
+    let mut result = StatementData::default();
+    result.headers.push(vec![String::new(), first_heading(base), first_heading(comparison),
+                             heading.to_string()]);
+
+    let cell = |value: Option<f64>| value.map_or(NOT_AVAILABLE.to_string(), format_cell);
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+
+    for (label, base_row, comparison_row) in rows {
+        let from = base_row.and_then(|row| base.normalized_value(row, 0));
+        let to = comparison_row.and_then(|row| comparison.normalized_value(row, 0));
+        let difference = match (from, to) {
+            (Some(from), Some(to)) => change(from, to),
+            _ => None,
+        };
+
+        result.data.push(vec![label.to_string(), cell(from), cell(to),
+                              difference.unwrap_or_else(|| NOT_AVAILABLE.to_string())]);
+
+        let occurrence = occurrences.entry(label).or_default();
+        result.row_ids.push(row_id(label, "", *occurrence));
+        *occurrence += 1;
+        result.row_sections.push(None);
+    }
+
+    return result;
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scale::Scale;
+
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        return cells.iter().map(|cell| cell.to_string()).collect();
+    }
+
+
+    /// An income statement as the R-files lay it out, the title and
+    /// period length spanning the dates below them.
+    fn operations(period: &str, net_sales: &str, other: &str) -> StatementData {
+        return StatementData {
+            headers: vec![row(&["CONDENSED CONSOLIDATED STATEMENTS OF OPERATIONS - USD ($) \
+                                 $ in Millions", "3 Months Ended"]),
+                          row(&[period, "Dec. 31, 2022"])],
+            data: vec![row(&["Net sales", net_sales, "$ 117,154"]),
+                       row(&["Other income/(expense), net", other, "(393)"])],
+            monetary_scale: Scale::Millions,
+            ..StatementData::default()
+        };
+    }
+
+
+    #[test]
+    fn diff_with_two_header_rows() {
+        let base = operations("Dec. 31, 2022", "$ 117,154", "(393)");
+        let mut comparison = operations("Dec. 30, 2023", "$ 119,575", "(50)");
+        comparison.data.push(row(&["Goodwill", "10", "0"]));
+
+        let diff = StatementData::diff(&base, &comparison);
+        assert_eq!(diff.headers, [row(&["", "Dec. 31, 2022", "Dec. 30, 2023", "Change"])]);
+        assert_eq!(diff.data, [row(&["Net sales", "117154000000", "119575000000", "2421000000"]),
+                               row(&["Other income/(expense), net", "-393000000", "-50000000",
+                                     "343000000"]),
+                               row(&["Goodwill", "N/A", "10000000", "N/A"])]);
+    }
+
+
+    #[test]
+    fn pct_change_with_two_header_rows() {
+        let base = operations("Dec. 31, 2022", "$ 100", "0");
+        let comparison = operations("Dec. 30, 2023", "$ 125", "(50)");
+
+        let change = StatementData::pct_change(&base, &comparison);
+        assert_eq!(change.headers, [row(&["", "Dec. 31, 2022", "Dec. 30, 2023", "% Change"])]);
+        assert_eq!(change.data, [row(&["Net sales", "100000000", "125000000", "25.00%"]),
+                                 row(&["Other income/(expense), net", "0", "-50000000",
+                                       "N/A"])]);
+    }
+
+
+    #[test]
+    fn first_heading_with_one_header_row() {
+        let data = StatementData {
+            headers: vec![row(&["Balance Sheets - USD ($)", "Dec. 30, 2023", "Sep. 30, 2023"])],
+            ..StatementData::default()
+        };
+        assert_eq!(first_heading(&data), "Dec. 30, 2023");
+        assert_eq!(first_heading(&StatementData::default()), "");
+    }
+}
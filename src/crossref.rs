@@ -0,0 +1,104 @@
+//! Cross references between parts of a filing and to other filings,
+//! e.g. "See Note 7" or "incorporated by reference to Exhibit 10.1".
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::disclosure::document_text;
+
+
+/// What a cross reference points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossReferenceType {
+    /// Note to the financial statements, e.g. "Note 7".
+    Note(u8),
+    /// Exhibit number, e.g. "10.1".
+    Exhibit(String),
+    /// Another filing incorporated by reference, e.g. "Form 8-K".
+    ExternalFiling(String),
+    /// Item of the filing itself, e.g. "Item 7A".
+    Section(String),
+}
+
+
+/// One reference found in the filing text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossReference {
+    /// Sentence the reference appears in.
+    pub source_text: String,
+    pub reference_type: CrossReferenceType,
+}
+
+
+struct Patterns {
+    note: Regex,
+    exhibit: Regex,
+    item: Regex,
+    form: Regex,
+}
+
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+
+    return PATTERNS.get_or_init(|| Patterns {
+        note: Regex::new(r"\bNotes? (\d{1,3})\b").expect("valid regex"),
+        exhibit: Regex::new(r"\bExhibits? (\d+\.\d+)\b").expect("valid regex"),
+        item: Regex::new(r"\bItem (\d{1,2}[A-C]?)\b").expect("valid regex"),
+        form: Regex::new(r"\bForm (10-K|10-Q|8-K|S-1|S-3|S-4|S-8|DEF 14A|20-F|40-F|6-K)(/A)?\b")
+              .expect("valid regex"),
+    });
+}
+
+
+
+/// References to notes, exhibits, items and other filings in `html`, in
+/// document order. Other filings are only reported from sentences that
+/// incorporate something by reference, so mentions of the filing's own
+/// form type are not picked up.
+pub fn extract_cross_references(html: &str) -> Vec<CrossReference> {
+    let text = document_text(html);
+    let patterns = patterns();
+
+    let mut references = vec![];
+
+    for sentence in text.split_inclusive(". ").map(str::trim) {
+        // (position in sentence, reference), sorted to keep document order
+        let mut found: Vec<(usize, CrossReferenceType)> = vec![];
+
+        for m in patterns.note.captures_iter(sentence) {
+            if let Ok(number) = m[1].parse::<u8>() {
+                found.push((m.get(0).map_or(0, |g| g.start()), CrossReferenceType::Note(number)));
+            }
+        }
+
+        for m in patterns.exhibit.captures_iter(sentence) {
+            found.push((m.get(0).map_or(0, |g| g.start()),
+                        CrossReferenceType::Exhibit(m[1].to_string())));
+        }
+
+        for m in patterns.item.captures_iter(sentence) {
+            found.push((m.get(0).map_or(0, |g| g.start()),
+                        CrossReferenceType::Section(format!("Item {}", &m[1]))));
+        }
+
+        if sentence.to_lowercase().contains("by reference") {
+            for m in patterns.form.captures_iter(sentence) {
+                found.push((m.get(0).map_or(0, |g| g.start()),
+                            CrossReferenceType::ExternalFiling(m[0].to_string())));
+            }
+        }
+
+        found.sort_by_key(|(position, _)| *position);
+
+        for (_, reference_type) in found {
+            references.push(CrossReference {
+                source_text: sentence.to_string(),
+                reference_type,
+            });
+        }
+    }
+
+    return references;
+}
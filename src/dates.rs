@@ -0,0 +1,190 @@
+//! Filing dates from EDGAR's different sources, which do not always
+//! agree: the Atom feed dates a filing by its acceptance in Eastern time,
+//! while a filing accepted after the 5:30pm cutoff is dated the next
+//! business day in the submissions API.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use log::warn;
+
+use crate::edgar::FilingRef;
+use crate::submissions::Filing;
+
+
+/// `n`-th (1-based) Sunday of `month`.
+fn nth_sunday(year: i32, month: u32, n: u8) -> Option<NaiveDate> {
+    return NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n);
+}
+
+
+
+/// US Eastern date of a UTC time. Daylight saving time runs from 2am on
+/// the second Sunday of March to 2am on the first Sunday of November,
+/// the rules in force since 2007.
+pub fn eastern_date(utc: NaiveDateTime) -> NaiveDate {
+    let year = utc.year();
+    let dst = match (nth_sunday(year, 3, 2), nth_sunday(year, 11, 1)) {
+        // 2am EST is 7am UTC, 2am EDT is 6am UTC
+        (Some(start), Some(end)) => utc >= start.and_hms_opt(7, 0, 0).unwrap_or_default()
+                                    && utc < end.and_hms_opt(6, 0, 0).unwrap_or_default(),
+        _ => false,
+    };
+
+    let offset = if dst { 4 } else { 5 };
+    return (utc - Duration::hours(offset)).date();
+}
+
+
+
+/// UTC time of an acceptance time such as "2024-08-01T18:04:43.000Z".
+pub fn parse_acceptance_time(raw: &str) -> Option<NaiveDateTime> {
+    let raw = raw.trim().trim_end_matches('Z');
+    return NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f").ok();
+}
+
+
+
+/// Settle `filed_on` of every filing in `filings` also listed in
+/// `submissions`: the submissions filing date wins, then the Eastern date
+/// of its acceptance time, then the feed's own date.
+///
+/// Returns a warning for every filing whose dates disagree, each also
+/// logged.
+pub fn reconcile_filing_dates(filings: &mut [FilingRef], submissions: &[Filing]) -> Vec<String> {
+    let mut warnings = vec![];
+
+    for filing in filings.iter_mut() {
+        let submission = match submissions.iter()
+                                          .find(|s| s.accession_number == filing.accession) {
+            Some(submission) => submission,
+            None => continue,
+        };
+
+        let accepted_on = parse_acceptance_time(&submission.acceptance_date_time).map(eastern_date);
+        let settled = submission.filed_on().or(accepted_on).or(filing.filed_on);
+
+        let sources = [("feed", filing.filed_on), ("acceptance time", accepted_on)];
+        for (source, date) in sources {
+            if let (Some(date), Some(settled)) = (date, settled) {
+                if date != settled {
+                    let warning = format!("{}: {} date {} differs from filing date {}",
+                                          filing.accession, source, date, settled);
+                    warn!("{}", warning);
+                    warnings.push(warning);
+                }
+            }
+        }
+
+        filing.filed_on = settled;
+    }
+
+    return warnings;
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accession::AccessionNumber;
+    use crate::edgar::parse_documents_feed;
+
+
+    fn date(raw: &str) -> NaiveDate {
+        return NaiveDate::parse_from_str(raw, "%Y-%m-%d").unwrap();
+    }
+
+
+    /// The feed's two filings, 0000320193-24-000006 dated 2024-02-02 and
+    /// 0000320193-23-000106 dated 2023-11-02.
+    fn feed() -> Vec<FilingRef> {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/feeds/prefixed_atom.xml");
+        return parse_documents_feed(&std::fs::read_to_string(path).unwrap()).unwrap();
+    }
+
+
+    fn submission(accession: &str, filing_date: &str, accepted: &str) -> Filing {
+        return Filing {
+            cik: 320193,
+            accession_number: AccessionNumber::parse(accession).unwrap(),
+            form: "10-Q".to_string(),
+            filing_date: filing_date.to_string(),
+            acceptance_date_time: accepted.to_string(),
+            report_date: String::new(),
+            primary_document: String::new(),
+            primary_doc_description: String::new(),
+            is_xbrl: true,
+        };
+    }
+
+
+    #[test]
+    fn eastern_dates() {
+        let utc = |raw: &str| parse_acceptance_time(raw).unwrap();
+
+        // EST, UTC-5
+        assert_eq!(eastern_date(utc("2024-02-02T04:59:59.000Z")), date("2024-02-01"));
+        assert_eq!(eastern_date(utc("2024-02-02T05:00:00.000Z")), date("2024-02-02"));
+        // EDT, UTC-4
+        assert_eq!(eastern_date(utc("2023-08-04T03:59:59.000Z")), date("2023-08-03"));
+        assert_eq!(eastern_date(utc("2023-08-04T04:00:00.000Z")), date("2023-08-04"));
+        // either side of the switch to EDT on 2024-03-10 at 7am UTC
+        assert_eq!(eastern_date(utc("2024-03-10T06:59:00.000Z")), date("2024-03-10"));
+        assert_eq!(eastern_date(utc("2024-03-11T03:30:00.000Z")), date("2024-03-10"));
+
+        assert_eq!(parse_acceptance_time("2024-02-02"), None);
+    }
+
+
+    #[test]
+    fn agreeing_dates_give_no_warning() {
+        let mut filings = feed();
+        let submissions = [submission("0000320193-24-000006", "2024-02-02",
+                                      "2024-02-02T11:01:40.000Z")];
+
+        let warnings = reconcile_filing_dates(&mut filings, &submissions);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(filings[0].filed_on, Some(date("2024-02-02")));
+    }
+
+
+    #[test]
+    fn submissions_filing_date_wins() {
+        // accepted 2024-02-01 at 6:04pm Eastern, after the cutoff, so
+        // filed the next day; the feed is a day early
+        let mut filings = feed();
+        filings[0].filed_on = Some(date("2024-02-01"));
+        let submissions = [submission("0000320193-24-000006", "2024-02-02",
+                                      "2024-02-01T23:04:43.000Z")];
+
+        let warnings = reconcile_filing_dates(&mut filings, &submissions);
+        assert_eq!(filings[0].filed_on, Some(date("2024-02-02")));
+        assert_eq!(warnings, [
+            "0000320193-24-000006: feed date 2024-02-01 differs from filing date 2024-02-02",
+            "0000320193-24-000006: acceptance time date 2024-02-01 differs from filing date \
+             2024-02-02",
+        ]);
+
+        // the filing missing from the submissions keeps the feed's date
+        assert_eq!(filings[1].filed_on, Some(date("2023-11-02")));
+    }
+
+
+    #[test]
+    fn acceptance_time_in_eastern_time_without_filing_date() {
+        // 9:08pm EDT on 2023-11-02 is already 2023-11-03 in UTC
+        let mut filings = feed();
+        let submissions = [submission("0000320193-23-000106", "", "2023-11-03T01:08:27.000Z")];
+
+        let warnings = reconcile_filing_dates(&mut filings, &submissions);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(filings[1].filed_on, Some(date("2023-11-02")));
+
+        // with a feed a day late, the acceptance time still wins
+        filings[1].filed_on = Some(date("2023-11-03"));
+        let warnings = reconcile_filing_dates(&mut filings, &submissions);
+        assert_eq!(filings[1].filed_on, Some(date("2023-11-02")));
+        assert_eq!(warnings, ["0000320193-23-000106: feed date 2023-11-03 differs from filing \
+                               date 2023-11-02"]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Keyword based detection of risk disclosures in filing text.
+
+use scraper::Html;
+
+
+/// Visible text of `html` with whitespace collapsed.
+pub(crate) fn document_text(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let text = document.root_element().text().collect::<Vec<_>>().join(" ");
+
+    return text.split_whitespace().collect::<Vec<_>>().join(" ")
+               .replace('\u{2019}', "'");
+}
+
+
+/// `document_text`, lowercased.
+fn filing_text(html: &str) -> String {
+    return document_text(html).to_lowercase();
+}
+
+
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    return text.split(['.', ';']);
+}
+
+
+
+fn going_concern(text: &str) -> bool {
+    return sentences(text).any(|s| s.contains("substantial doubt")
+                                   && s.contains("going concern"));
+}
+
+
+fn material_weakness(text: &str) -> bool {
+    // "no material weakness" or "did not identify any material weakness"
+    // are the usual clean opinions, only count affirmative statements
+    let negations = ["no material weakness", "not identify any material weakness",
+                     "not identified any material weakness",
+                     "no material weaknesses"];
+
+    return sentences(text).any(|s| s.contains("material weakness")
+                                   && !negations.iter().any(|n| s.contains(n)));
+}
+
+
+fn whistleblower(text: &str) -> bool {
+    let phrases = ["sec whistleblower", "securities whistleblower",
+                   "whistleblower program", "rule 21f", "section 21f"];
+
+    return phrases.iter().any(|p| text.contains(p));
+}
+
+
+
+/// Whether the filing discloses substantial doubt about the company's
+/// ability to continue as a going concern.
+pub fn detect_going_concern(html: &str) -> bool {
+    return going_concern(&filing_text(html));
+}
+
+
+/// Whether the filing reports a material weakness in internal control
+/// over financial reporting (statements that none was found are ignored).
+pub fn detect_material_weakness(html: &str) -> bool {
+    return material_weakness(&filing_text(html));
+}
+
+
+/// Whether the filing mentions SEC whistleblower complaints, e.g. in risk
+/// factors or legal proceedings, including Rule 21F references.
+pub fn detect_whistleblower_mentions(html: &str) -> bool {
+    return whistleblower(&filing_text(html));
+}
+
+
+
+/// Disclosure risk flags of one filing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AuditFlags {
+    pub going_concern: bool,
+    pub material_weakness: bool,
+    pub whistleblower: bool,
+}
+
+
+impl AuditFlags {
+
+    /// True if any flag is raised.
+    pub fn any(&self) -> bool {
+        return self.going_concern || self.material_weakness || self.whistleblower;
+    }
+}
+
+
+/// Runs all disclosure checks on `html`, parsing it only once.
+pub fn audit_flags(html: &str) -> AuditFlags {
+    let text = filing_text(html);
+
+    return AuditFlags {
+        going_concern: going_concern(&text),
+        material_weakness: material_weakness(&text),
+        whistleblower: whistleblower(&text),
+    };
+}
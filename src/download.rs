@@ -0,0 +1,136 @@
+//! Save the documents of a filing to disk as they are.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use regex::Regex;
+
+use crate::client::SecClient;
+use crate::edgar::{filing_index, DirItem};
+use crate::error::SecFilingsError;
+
+
+/// Which documents `download_filing()` saves, and whether it overwrites
+/// files already in the destination.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    extensions: Vec<String>,
+    names: Vec<Regex>,
+    force: bool,
+}
+
+
+impl DownloadOptions {
+
+    /// Every document, keeping files that already exist.
+    pub fn new() -> Self {
+        return DownloadOptions::default();
+    }
+
+
+    /// Also save documents ending in `.extension`, e.g. "htm" or "pdf".
+    pub fn extension(mut self, extension: &str) -> Self {
+        self.extensions.push(extension.trim_start_matches('.').to_lowercase());
+        return self;
+    }
+
+
+    /// Also save documents whose name matches `glob`, where `*` stands for
+    /// any run of characters and `?` for one, e.g. "*ex31*.htm".
+    pub fn name_glob(mut self, glob: &str) -> Self {
+        self.names.push(glob_regex(glob));
+        return self;
+    }
+
+
+    /// Download and overwrite files already in the destination, rather
+    /// than skipping them.
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        return self;
+    }
+
+
+    /// Whether the document called `name` is to be saved. Without any
+    /// extension or glob, every document is.
+    fn selects(&self, name: &str) -> bool {
+        if self.extensions.is_empty() && self.names.is_empty() {
+            return true;
+        }
+
+        let lower = name.to_lowercase();
+        let extension_matches = self.extensions.iter()
+            .any(|extension| lower.rsplit_once('.').is_some_and(|(_, ext)| ext == extension));
+
+        return extension_matches || self.names.iter().any(|glob| glob.is_match(name));
+    }
+}
+
+
+/// `glob` as an anchored, case insensitive regex.
+fn glob_regex(glob: &str) -> Regex {
+    let pattern: String = glob.chars().map(|c| match c {
+        '*' => ".*".to_string(),
+        '?' => ".".to_string(),
+        _ => regex::escape(&c.to_string()),
+    }).collect();
+
+    return Regex::new(&format!("(?i)^{}$", pattern)).expect("escaped glob is a valid regex");
+}
+
+
+/// Whether `item` of a filing index is a document rather than a folder.
+fn is_file(item: &DirItem) -> bool {
+    return item.item_type != "folder.gif";
+}
+
+
+
+/// Save the documents of the filing whose index.json is at `index_url`
+/// (e.g. a `FilingRef::url`) that `options` selects into `dest_dir`,
+/// under their names in the filing. `dest_dir` is created if missing.
+///
+/// Files already in `dest_dir` are skipped unless `options` forces
+/// downloads. Each download goes through the client's rate limiter and
+/// is written to a temporary file first, so an interrupted run never
+/// leaves a partial file to be skipped next time.
+///
+/// Returns the paths of the selected documents, downloaded or skipped.
+pub fn download_filing(sec_client: &SecClient, index_url: &str, dest_dir: &Path,
+                       options: &DownloadOptions) -> Result<Vec<PathBuf>, SecFilingsError> {
+
+    let index = filing_index(sec_client, index_url)?;
+    fs::create_dir_all(dest_dir)?;
+
+    let mut paths = vec![];
+
+    for item in index.directory.items.iter().filter(|item| is_file(item)) {
+        if !options.selects(&item.name) {
+            continue;
+        }
+
+        // names come from the listing, keep them inside dest_dir
+        let name = match Path::new(&item.name).file_name() {
+            Some(name) => name,
+            None => continue,
+        };
+        let path = dest_dir.join(name);
+
+        if path.exists() && !options.force {
+            debug!("{} exists, skipping", path.display());
+            paths.push(path);
+            continue;
+        }
+
+        let bytes = sec_client.get_bytes(&index.url_of(&item.name))?;
+
+        let partial = dest_dir.join(format!(".{}.partial", name.to_string_lossy()));
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, &path)?;
+
+        paths.push(path);
+    }
+
+    return Ok(paths);
+}
@@ -0,0 +1,1346 @@
+//! EDGAR lookups: filing indexes, FilingSummary.xml and statement reports.
+
+use chrono::NaiveDate;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use serde_xml_rs::from_str;
+
+use crate::accession::{extract_accession_from_url, AccessionNumber};
+use crate::classify::{StatementClassifier, StatementKind, StatementQuery, BALANCE_SHEET,
+                      CASH_FLOW_STATEMENT, INCOME_STATEMENT, STOCKHOLDERS_EQUITY};
+use crate::client::{HttpFetch, SecClient};
+use crate::error::SecFilingsError;
+use crate::parse::{parse_html_statement_data, StatementData};
+
+
+/// Root element of the SEC Atom XML response.
+#[derive(Debug, Deserialize)]
+#[serde(rename = "feed")]
+struct Feed {
+    #[serde(rename = "entry", default)]
+    entries: Vec<Entry>,
+}
+
+/// Individual filing entry.
+#[derive(Debug, Deserialize)]
+struct Entry {
+    #[serde(rename = "link")]
+    link: Link,
+    /// e.g. "10-Q  - Quarterly report [Sections 13 or 15(d)]".
+    title: Option<String>,
+    /// ISO 8601 timestamp, e.g. "2024-08-02T06:01:36-04:00".
+    updated: Option<String>,
+    category: Option<Category>,
+    /// "urn:tag:sec.gov,2008:accession-number=0000320193-24-000081".
+    id: Option<String>,
+}
+
+/// Form type of a filing entry.
+#[derive(Debug, Deserialize)]
+struct Category {
+    #[serde(rename = "term", alias = "@term")]
+    term: String,
+}
+
+/// Represents link to a specific filing.
+#[derive(Debug, Deserialize)]
+struct Link {
+    #[serde(rename = "href", alias = "@href")]
+    href: String,
+}
+
+
+/// XML documents read with `from_xml()`.
+trait TrimText {
+    /// Trim text content the way serde-xml-rs does, attributes are kept.
+    fn trim_text(&mut self);
+}
+
+
+fn trim(text: &mut Option<String>) {
+    if let Some(text) = text {
+        *text = text.trim().to_string();
+    }
+}
+
+
+impl TrimText for Feed {
+    fn trim_text(&mut self) {
+        for entry in self.entries.iter_mut() {
+            trim(&mut entry.title);
+            trim(&mut entry.updated);
+            trim(&mut entry.id);
+        }
+    }
+}
+
+
+impl TrimText for FilingSummary {
+    fn trim_text(&mut self) {
+        for report in self.filing.reports.iter_mut() {
+            trim(&mut report.shortname);
+            trim(&mut report.htmlfilename);
+            trim(&mut report.xmlfilename);
+            trim(&mut report.menu_category);
+        }
+    }
+}
+
+
+/// `xml` deserialized with quick-xml, several times faster than
+/// serde-xml-rs, which is still taken when quick-xml fails so errors keep
+/// their kind.
+fn from_xml<T: DeserializeOwned + TrimText>(xml: &str) -> Result<T, SecFilingsError> {
+    if let Ok(mut value) = quick_xml::de::from_str::<T>(xml) {
+        value.trim_text();
+        return Ok(value);
+    }
+    return Ok(from_str(xml)?);
+}
+
+
+
+/// EDGAR form types accepted by `documents()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilingType {
+    TenQ,
+    TenK,
+    EightK,
+    /// Definitive proxy statement, DEF 14A.
+    DefProxy,
+    /// Registration statement, filed ahead of an IPO.
+    S1,
+    Other(String),
+}
+
+
+impl FilingType {
+
+    /// Form type string as expected by the EDGAR `type` query param.
+    pub fn as_str(&self) -> &str {
+        match self {
+            FilingType::TenQ => "10-Q",
+            FilingType::TenK => "10-K",
+            FilingType::EightK => "8-K",
+            FilingType::DefProxy => "DEF 14A",
+            FilingType::S1 => "S-1",
+            FilingType::Other(form) => form.as_str(),
+        }
+    }
+}
+
+
+impl AsRef<str> for FilingType {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+
+/// The form type named `form`, `Other` unless it has a variant.
+impl From<&str> for FilingType {
+    fn from(form: &str) -> Self {
+        return match form {
+            "10-Q" => FilingType::TenQ,
+            "10-K" => FilingType::TenK,
+            "8-K" => FilingType::EightK,
+            "DEF 14A" => FilingType::DefProxy,
+            "S-1" => FilingType::S1,
+            other => FilingType::Other(other.to_string()),
+        };
+    }
+}
+
+
+
+/// Which filings of a company's insiders `documents()` lists along with
+/// the company's own, the browse-edgar `owner` param.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OwnerFilter {
+    Include,
+    /// The company's own filings only.
+    #[default]
+    Exclude,
+    /// Ownership filings (Forms 3, 4 and 5) only.
+    Only,
+}
+
+
+impl OwnerFilter {
+
+    fn as_str(self) -> &'static str {
+        return match self {
+            OwnerFilter::Include => "include",
+            OwnerFilter::Exclude => "exclude",
+            OwnerFilter::Only => "only",
+        };
+    }
+}
+
+
+
+/// Filing dates to list filings for, both ends inclusive and optional.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DateRange {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+
+impl DateRange {
+
+    /// Fails if `to` comes before `from`.
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<Self, SecFilingsError> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if to < from {
+                return Err(SecFilingsError::InvalidDateRange { from, to });
+            }
+        }
+        return Ok(DateRange { from, to });
+    }
+
+
+    pub fn from(&self) -> Option<NaiveDate> {
+        return self.from;
+    }
+
+
+    pub fn to(&self) -> Option<NaiveDate> {
+        return self.to;
+    }
+}
+
+
+/// `date` as EDGAR's "YYYYMMDD", empty for none.
+fn edgar_date(date: Option<NaiveDate>) -> String {
+    return date.map(|date| date.format("%Y%m%d").to_string()).unwrap_or_default();
+}
+
+
+
+/// Most filings EDGAR lists per page of the Atom feed, a larger count is
+/// served as this many.
+pub const MAX_DOCUMENTS_COUNT: u32 = 100;
+
+
+/// Paging and filters of a `documents()` lookup, e.g.
+/// `DocumentsQuery::new().date("20230101").count(40)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentsQuery {
+    count: u32,
+    start: u32,
+    owner: OwnerFilter,
+    // "YYYYMMDD" bounds of the filing date, empty for none
+    datea: String,
+    dateb: String,
+}
+
+
+impl Default for DocumentsQuery {
+    fn default() -> Self {
+        return DocumentsQuery { count: MAX_DOCUMENTS_COUNT, start: 0, owner: OwnerFilter::default(),
+                                datea: String::new(), dateb: String::new() };
+    }
+}
+
+
+impl DocumentsQuery {
+
+    /// The first 100 filings, of any date, without insider filings.
+    pub fn new() -> Self {
+        return DocumentsQuery::default();
+    }
+
+
+    /// Filings per page, clamped to 1 through `MAX_DOCUMENTS_COUNT` so
+    /// `documents_paged()` can tell a short page from a full one.
+    pub fn count(mut self, n: u32) -> Self {
+        self.count = n.clamp(1, MAX_DOCUMENTS_COUNT);
+        return self;
+    }
+
+
+    /// Number of filings to skip, most recent first.
+    pub fn start(mut self, offset: u32) -> Self {
+        self.start = offset;
+        return self;
+    }
+
+
+    pub fn owner(mut self, owner: OwnerFilter) -> Self {
+        self.owner = owner;
+        return self;
+    }
+
+
+    /// Only filings made on or before `date`, "YYYYMMDD".
+    pub fn date(mut self, date: &str) -> Self {
+        self.dateb = date.to_string();
+        return self;
+    }
+
+
+    /// Only filings made within `range`, replacing `date()`.
+    pub fn date_range(mut self, range: DateRange) -> Self {
+        self.datea = edgar_date(range.from);
+        self.dateb = edgar_date(range.to);
+        return self;
+    }
+}
+
+
+
+/// Query params for the browse-edgar Atom feed, `company` is either
+/// ("ticker", ticker) or ("CIK", cik).
+pub(crate) fn documents_params<'a>(company: (&'a str, &'a str), query: &'a DocumentsQuery,
+                                   filing_type: &'a FilingType)
+                                   -> [(&'a str, String); 9] {
+    return [
+        ("action", "getcompany".to_string()),
+        (company.0, company.1.to_string()),
+        ("type", filing_type.as_str().to_string()),
+        ("datea", query.datea.clone()),
+        ("dateb", query.dateb.clone()),
+        ("owner", query.owner.as_str().to_string()),
+        ("start", query.start.to_string()),
+        ("output", "atom".to_string()),
+        ("count", query.count.to_string()),
+    ];
+}
+
+
+
+/// Base URL of the browse-edgar CGI serving the Atom feed.
+pub(crate) const BROWSE_EDGAR_URL: &str = "https://www.sec.gov/cgi-bin/browse-edgar";
+
+/// Filing listed by `documents()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilingRef {
+    /// index.json URL of the filing's folder.
+    pub url: String,
+    pub accession: AccessionNumber,
+    /// Form type as listed in the feed, e.g. "10-Q" or "10-Q/A".
+    pub form_type: String,
+    /// Filing date, from the feed unless settled otherwise by
+    /// `reconcile_filing_dates()`, `None` if the feed gave no date.
+    pub filed_on: Option<NaiveDate>,
+    /// Entry title, e.g. "10-Q  - Quarterly report [Sections 13 or
+    /// 15(d)]", empty if the feed gave none.
+    pub title: String,
+}
+
+
+
+/// Turn a filing href from the Atom feed into its index.json URL.
+pub(crate) fn index_json_url(href: &str) -> String {
+    // filings from the 1990s link the submission text file, not an index
+    let href = match href.strip_suffix(".txt") {
+        Some(stem) => format!("{}/index.json", stem),
+        None => href.to_string(),
+    };
+
+    let mut documents_url = href
+        .replace("-index.html", "/index.json")
+        .replace("-index.htm", "/index.json")
+        .replace("-", "");
+
+    // adjust the URL if it has exactly 10 parts
+    let split_items: Vec<&str> = documents_url.split("/").collect();
+    if split_items.len() == 10 {
+        let mut items = split_items;
+        items.remove(7);
+        documents_url = items.join("/");
+    }
+
+    return documents_url;
+}
+
+
+
+/// Filings listed in a browse-edgar Atom feed, e.g. one saved from the
+/// URL `documents()` requests.
+pub fn parse_documents_feed(xml: &str) -> Result<Vec<FilingRef>, SecFilingsError> {
+    // deserialize the Atom feed XML into 'Feed' struct
+    let feed: Feed = from_xml(xml)?;
+
+    let mut documents_list = Vec::new();
+
+    for entry in feed.entries {
+        let href = entry.link.href;
+
+        // the entry id carries the accession number too
+        let accession = extract_accession_from_url(&href).or_else(|| entry.id.as_deref()
+                        .and_then(|id| id.rsplit('=').next())
+                        .and_then(AccessionNumber::parse));
+
+        let accession = match accession {
+            Some(accession) => accession,
+            None => {
+                warn!("skipping {}: no accession number in URL", href);
+                continue;
+            }
+        };
+
+        // "updated" is the acceptance time in Eastern time, so its date
+        // is the day EDGAR took the filing in
+        let filed_on = entry.updated.and_then(|updated| {
+            return NaiveDate::parse_from_str(updated.get(..10)?, "%Y-%m-%d").ok();
+        });
+
+        documents_list.push(FilingRef {
+            url: index_json_url(&href),
+            accession,
+            form_type: entry.category.map(|c| c.term).unwrap_or_default(),
+            filed_on,
+            title: entry.title.map(|t| t.trim().to_string()).unwrap_or_default(),
+        });
+    }
+
+    Ok(documents_list)
+}
+
+
+
+/// Get one page of the filings of a given ticker and filing type, see
+/// `documents_all()` for every page.
+pub fn documents<C>(sec_client: &C, ticker: &str, query: &DocumentsQuery,
+                    filing_type: FilingType) -> Result<Vec<FilingRef>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    let params = documents_params(("ticker", ticker), query, &filing_type);
+    let response = sec_client.get_with_params(BROWSE_EDGAR_URL, &params)?;
+
+    return parse_documents_feed(&response);
+}
+
+
+
+/// index.json URLs of the filings `documents()` lists.
+pub fn document_urls<C>(sec_client: &C, ticker: &str, query: &DocumentsQuery,
+                        filing_type: FilingType) -> Result<Vec<String>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    let filings = documents(sec_client, ticker, query, filing_type)?;
+    return Ok(filings.into_iter().map(|filing| filing.url).collect());
+}
+
+
+
+/// Like `documents()`, but looks the company up by its Central Index Key,
+/// for issuers without a (US) ticker.
+pub fn documents_by_cik<C>(sec_client: &C, cik: u64, query: &DocumentsQuery,
+                           filing_type: FilingType) -> Result<Vec<FilingRef>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    let cik = cik.to_string();
+    let params = documents_params(("CIK", &cik), query, &filing_type);
+    let response = sec_client.get_with_params(BROWSE_EDGAR_URL, &params)?;
+
+    return parse_documents_feed(&response);
+}
+
+
+
+/// Filings of a given ticker and filing type from `query`'s start on,
+/// requesting pages of `query`'s count until one comes back short, or
+/// `max_results` filings are listed.
+pub fn documents_paged<C>(sec_client: &C, ticker: &str, query: &DocumentsQuery,
+                          filing_type: FilingType, max_results: Option<usize>)
+                          -> Result<Vec<FilingRef>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    let mut query = query.clone();
+    let mut all_documents = vec![];
+
+    loop {
+        let page = documents(sec_client, ticker, &query, filing_type.clone())?;
+        let page_len = page.len();
+        all_documents.extend(page);
+
+        if let Some(max_results) = max_results {
+            if all_documents.len() >= max_results {
+                all_documents.truncate(max_results);
+                return Ok(all_documents);
+            }
+        }
+        if page_len == 0 || page_len < query.count as usize {
+            return Ok(all_documents);
+        }
+        query.start += page_len as u32;
+    }
+}
+
+
+
+/// Every filing of a given ticker and filing type, most recent first.
+pub fn documents_all<C>(sec_client: &C, ticker: &str, filing_type: FilingType)
+                        -> Result<Vec<FilingRef>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return documents_paged(sec_client, ticker, &DocumentsQuery::new(), filing_type, None);
+}
+
+
+
+/// A filing folder's index.json, listing the documents filed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FilingIndex {
+    pub directory: Directory,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Directory {
+    /// Path of the folder, e.g. "/Archives/edgar/data/320193/000032019324000081".
+    pub name: String,
+    #[serde(rename = "item")]
+    pub items: Vec<DirItem>,
+}
+
+/// A document or subfolder of a filing folder.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DirItem {
+    /// File name, e.g. "R2.htm" or "0000320193-24-000081.txt".
+    pub name: String,
+    /// "YYYY-MM-DD HH:MM:SS".
+    #[serde(rename = "last-modified", default)]
+    pub last_modified: String,
+    /// Icon name the listing uses as type, e.g. "text.gif" or "folder.gif".
+    #[serde(rename = "type", default)]
+    pub item_type: String,
+    /// Size in bytes, `None` where the listing leaves it blank.
+    #[serde(default, deserialize_with = "deserialize_size")]
+    pub size: Option<u64>,
+}
+
+
+/// `size` comes as a string, and an empty one for some items.
+fn deserialize_size<'de, D: serde::Deserializer<'de>>(deserializer: D)
+                                                      -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Number(u64),
+        Text(String),
+    }
+
+    return Ok(match Size::deserialize(deserializer)? {
+        Size::Number(size) => Some(size),
+        Size::Text(text) => text.trim().parse().ok(),
+    });
+}
+
+
+impl FilingIndex {
+
+    /// URL of the document called `name` in the folder.
+    pub fn url_of(&self, name: &str) -> String {
+        return format!("https://www.sec.gov{}/{}", self.directory.name.trim_end_matches('/'),
+                       name);
+    }
+
+
+    /// URL of the filing's FilingSummary.xml, if it has one.
+    pub fn filing_summary_url(&self) -> Option<String> {
+        return self.directory.items.iter().find(|item| item.name == "FilingSummary.xml")
+                   .map(|item| self.url_of(&item.name));
+    }
+}
+
+
+
+/// Parse a filing folder's index.json.
+pub(crate) fn parse_filing_index(json: &str) -> Result<FilingIndex, SecFilingsError> {
+    return Ok(serde_json::from_str(json)?);
+}
+
+
+
+/// Documents of the filing whose index.json is at `url`, e.g. a
+/// `FilingRef::url`.
+pub fn filing_index<C>(sec_client: &C, url: &str) -> Result<FilingIndex, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    let response = sec_client.get(url)?;
+    return parse_filing_index(&response);
+}
+
+
+
+/// FilingSummary.xml URLs for every filing in `documents_list`.
+///
+/// Filings without a FilingSummary.xml are skipped. So are filings whose
+/// index.json cannot be fetched or parsed, with a warning logged, so one
+/// bad URL does not fail the whole batch.
+pub fn filing_summaries<C>(sec_client: &C, documents_list: &[String])
+                        -> Result<Vec<String>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    // store all summeries from documents_list in here
+    let mut summaries = vec![];
+
+    // iterate over each JSON index URL
+    for document in documents_list {
+
+        // GET request
+        let summary = filing_index(sec_client, document)
+                      .map(|index| index.filing_summary_url());
+
+        match summary {
+            Ok(Some(url)) => summaries.push(url),
+            // if not found, simply jump to next doc
+            Ok(None) => continue,
+            Err(e) => warn!("skipping {}: {}", document, e),
+        }
+    }
+
+    Ok(summaries)
+}
+
+
+
+/// Root element of the SEC XML response.
+#[derive(Debug, Deserialize)]
+struct FilingSummary {
+    #[serde(rename = "MyReports")]
+    filing: Reports,
+}
+
+/// Represents the `<MyReports>` section in the XML.
+#[derive(Debug, Deserialize)]
+#[serde(rename = "MyReports")]
+struct Reports {
+    #[serde(rename = "Report", default)]
+    reports: Vec<Report>,
+}
+
+/// Individual report entries inside `<MyReports>`.
+#[derive(Debug, Deserialize)]
+struct Report {
+    #[serde(rename = "ShortName")]
+    shortname: Option<String>,
+    #[serde(rename = "HtmlFileName")]
+    htmlfilename: Option<String>,
+    #[serde(rename = "XmlFileName")]
+    xmlfilename: Option<String>,
+    #[serde(rename = "MenuCategory")]
+    menu_category: Option<String>,
+    #[serde(rename = "Position")]
+    position: Option<u32>,
+}
+
+
+/// Format of a report's file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReportFileType {
+    Html,
+    Xml,
+    Other,
+}
+
+
+impl ReportFileType {
+
+    fn from_file_name(file: &str) -> Self {
+        let file = file.to_lowercase();
+
+        if file.ends_with(".htm") || file.ends_with(".html") {
+            return ReportFileType::Html;
+        }
+        if file.ends_with(".xml") {
+            return ReportFileType::Xml;
+        }
+        return ReportFileType::Other;
+    }
+}
+
+
+/// Report listed in a FilingSummary.xml.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReportEntry {
+    pub shortname: String,
+    pub url: String,
+    pub file_type: ReportFileType,
+    /// Statement the report holds, see `StatementClassifier::classify`.
+    pub statement_kind: Option<StatementKind>,
+    /// Position of the report in its FilingSummary.xml, starting at 0.
+    pub sequence: usize,
+    /// Section of the EDGAR viewer menu, e.g. "Statements" or "Notes".
+    pub menu_category: Option<String>,
+    /// Order of the report within the filing as given by the filer.
+    pub position: Option<u32>,
+}
+
+
+/// Version of what `parse_filing_summary()` makes of a FilingSummary.xml.
+/// Bump it with any change to that, reports `filing_reports()` cached
+/// under another version are parsed again.
+pub const FILING_SUMMARY_PARSER_VERSION: u32 = 1;
+
+
+/// Reports listed in a FilingSummary.xml.
+pub(crate) fn parse_filing_summary(xml_url: &str, xml_content: &str)
+                                   -> Result<Vec<ReportEntry>, SecFilingsError> {
+
+    let mut all_reports = vec![];
+
+    // get base URL
+    let base_url = xml_url.replace("FilingSummary.xml", "");
+
+    // deserialize filing elements
+    let xml_summary: FilingSummary = from_xml(xml_content)?;
+
+    // extract reports from the XML summary
+    let mut reports = xml_summary.filing.reports;
+
+    // exclude the last report which should aways be the 'base_url'
+    if reports.len() > 1 {
+        reports.pop();
+    }
+
+    // process each report
+    for (sequence, report) in reports.into_iter().enumerate() {
+        // prefer htmlfilename over xmlfilename
+        let file = report.htmlfilename.or(report.xmlfilename).unwrap_or_default();
+
+        // grab url and its short description
+        let url = format!("{}{}", base_url, file);
+        let shortname = report.shortname.unwrap_or_default();
+
+        all_reports.push(ReportEntry {
+            statement_kind: StatementClassifier::classify(&shortname),
+            file_type: ReportFileType::from_file_name(&file),
+            shortname,
+            url,
+            sequence,
+            menu_category: report.menu_category,
+            position: report.position,
+        });
+    }
+
+    Ok(all_reports)
+}
+
+
+
+/// Reports of every FilingSummary.xml in `xml_summaries`, classified by
+/// statement kind.
+pub fn master_reports<C>(sec_client: &C, xml_summaries: &[String])
+                     -> Result<Vec<ReportEntry>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    let mut all_reports = vec![];
+
+    for xml_url in xml_summaries {
+        // GET request
+        let xml_content = sec_client.get(xml_url)?;
+
+        all_reports.extend(parse_filing_summary(xml_url, &xml_content)?);
+    }
+    Ok(all_reports)
+}
+
+
+
+/// Reports of `filing`, read from its index.json and FilingSummary.xml,
+/// empty if it has none.
+///
+/// With a cache, see `SecClientBuilder::cache_dir`, and the serde feature,
+/// the parsed reports are kept per accession number and served from there
+/// on later calls, even once the documents they were read from are gone
+/// from the cache.
+pub fn filing_reports(sec_client: &SecClient, filing: &FilingRef)
+                      -> Result<Vec<ReportEntry>, SecFilingsError> {
+
+    #[cfg(feature = "serde")]
+    if let Some(reports) = sec_client.cache_to_read()
+                                     .and_then(|cache| cache.load_reports(&filing.accession)) {
+        debug!("reports of {} served from cache", filing.accession);
+        return Ok(reports);
+    }
+
+    let reports = match filing_index(sec_client, &filing.url)?.filing_summary_url() {
+        Some(url) => master_reports(sec_client, &[url])?,
+        None => vec![],
+    };
+
+    #[cfg(feature = "serde")]
+    if let Some(cache) = sec_client.cache() {
+        cache.store_reports(&filing.accession, &reports);
+    }
+
+    return Ok(reports);
+}
+
+
+
+/// URL of the first statement report matching `query`, the first of
+/// `find_report_urls()`, so notes and details mentioning the statement
+/// are passed over here too.
+pub(crate) fn find_report_url<'a>(reports: &'a [ReportEntry], query: &StatementQuery)
+                                  -> Option<&'a str> {
+
+    return find_report_urls(reports, query).into_iter().next();
+}
+
+
+
+/// URLs of every statement report matching `query`, those not in its
+/// fallback list first. Notes and details mentioning the statement are
+/// left out.
+pub(crate) fn find_report_urls<'a>(reports: &'a [ReportEntry], query: &StatementQuery)
+                                   -> Vec<&'a str> {
+
+    let (fallback, preferred): (Vec<_>, Vec<_>) = reports.iter()
+        .filter(|report| StatementClassifier::classify(&report.shortname).is_some())
+        .map(|report| (report.shortname.to_lowercase(), report.url.as_str()))
+        .filter(|(name, _)| query.matches(name))
+        .partition(|(name, _)| query.fallback.iter().any(|fb| name.contains(fb)));
+
+    return preferred.into_iter().chain(fallback).map(|(_, url)| url).collect();
+}
+
+
+
+/// Report `balance_sheet_first()`, `income_statement()` and friends would pick
+/// for `kind`, if any. Handy to see why a statement came out wrong.
+pub fn selected_report(reports: &[ReportEntry], kind: StatementKind) -> Option<&ReportEntry> {
+    let query = match kind {
+        StatementKind::BalanceSheet => &BALANCE_SHEET,
+        StatementKind::IncomeStatement => &INCOME_STATEMENT,
+        StatementKind::CashFlow => &CASH_FLOW_STATEMENT,
+        StatementKind::StockholdersEquity => &STOCKHOLDERS_EQUITY,
+        // no accessor reads it on its own
+        StatementKind::ComprehensiveIncome => return None,
+    };
+
+    let url = find_report_url(reports, query)?;
+    return reports.iter().find(|report| report.url == url);
+}
+
+
+
+/// Fetch and parse the first report matching `query`.
+pub(crate) fn find_statement<C>(sec_client: &C, reports: &[ReportEntry],
+                                query: &StatementQuery)
+                                -> Result<StatementData, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    // find the shortname == keywords, and parse its url
+    if let Some(url) = find_report_url(reports, query) {
+
+        debug!("{}: {}", query.what, url);
+
+        // GET html
+        let html = sec_client.get(url)?;
+
+        // parse html
+        let mut statement_data = parse_html_statement_data(&html)?;
+        statement_data.set_source(reports, url);
+
+        return Ok(statement_data);
+    }
+
+    return Err(SecFilingsError::NotFound(format!("{} report", query.what)));
+}
+
+
+
+/// Fetch and parse every statement report matching `query`.
+pub(crate) fn find_statements<C>(sec_client: &C, reports: &[ReportEntry],
+                                 query: &StatementQuery)
+                                 -> Result<Vec<StatementData>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    let mut statements = vec![];
+
+    for url in find_report_urls(reports, query) {
+        debug!("{}: {}", query.what, url);
+
+        let html = sec_client.get(url)?;
+        let mut statement_data = parse_html_statement_data(&html)?;
+        statement_data.set_source(reports, url);
+
+        statements.push(statement_data);
+    }
+
+    return Ok(statements);
+}
+
+
+
+/// Balance sheet of the filing, the first one listed when there are
+/// several, see `balance_sheets_all()`.
+pub fn balance_sheet_first<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                              -> Result<StatementData, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return find_statement(sec_client, xml_summaries, &BALANCE_SHEET);
+}
+
+
+
+#[deprecated(note = "renamed to `balance_sheet_first`")]
+pub fn balance_sheets<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                         -> Result<StatementData, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return balance_sheet_first(sec_client, xml_summaries);
+}
+
+
+
+/// Every balance sheet of the filing, e.g. the consolidated one and the
+/// parent company only one some 10-Ks carry. Empty if there is none.
+pub fn balance_sheets_all<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                             -> Result<Vec<StatementData>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return find_statements(sec_client, xml_summaries, &BALANCE_SHEET);
+}
+
+
+
+/// Income statement (statement of operations) of the filing. When both a
+/// plain and a comprehensive income statement exist, the plain one wins.
+pub fn income_statement<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                           -> Result<StatementData, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return find_statement(sec_client, xml_summaries, &INCOME_STATEMENT);
+}
+
+
+
+/// Every income statement of the filing, plain ones before
+/// comprehensive ones. Empty if there is none.
+pub fn income_statement_all<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                               -> Result<Vec<StatementData>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return find_statements(sec_client, xml_summaries, &INCOME_STATEMENT);
+}
+
+
+
+/// Cash flow statement of the filing.
+pub fn cash_flow_statement<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                              -> Result<StatementData, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return find_statement(sec_client, xml_summaries, &CASH_FLOW_STATEMENT);
+}
+
+
+
+/// Every cash flow statement of the filing. Empty if there is none.
+pub fn cash_flow_statement_all<C>(sec_client: &C, xml_summaries: &[ReportEntry])
+                                  -> Result<Vec<StatementData>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    return find_statements(sec_client, xml_summaries, &CASH_FLOW_STATEMENT);
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn report(shortname: &str, url: &str) -> ReportEntry {
+        return ReportEntry {
+            shortname: shortname.to_string(),
+            url: url.to_string(),
+            file_type: ReportFileType::Html,
+            statement_kind: StatementClassifier::classify(shortname),
+            sequence: 0,
+            menu_category: None,
+            position: None,
+        };
+    }
+
+
+    #[test]
+    fn index_json_url_shapes() {
+        let cases = [
+            // "-index.htm", folder and accession: ten parts, the folder is kept once
+            ("https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/\
+              0000320193-24-000006-index.htm",
+             "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/index.json"),
+            // "-index.html"
+            ("https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/\
+              0000320193-24-000006-index.html",
+             "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/index.json"),
+            // accession right under the CIK
+            ("https://www.sec.gov/Archives/edgar/data/320193/0000320193-24-000006-index.htm",
+             "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/index.json"),
+            // already an index.json URL
+            ("https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/index.json",
+             "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/index.json"),
+            // one digit CIK
+            ("https://www.sec.gov/Archives/edgar/data/2/000000000224000001/\
+              0000000002-24-000001-index.htm",
+             "https://www.sec.gov/Archives/edgar/data/2/000000000224000001/index.json"),
+            // 1994 filing, linking the submission text file
+            ("https://www.sec.gov/Archives/edgar/data/1750/0000912057-94-000263.txt",
+             "https://www.sec.gov/Archives/edgar/data/1750/000091205794000263/index.json"),
+        ];
+
+        for (href, expected) in cases {
+            assert_eq!(index_json_url(href), expected, "{}", href);
+        }
+    }
+
+
+    #[test]
+    fn find_report_url_skips_notes_and_details() {
+        let reports = [
+            report("Balance Sheet Components (Details)", "R40.htm"),
+            report("Condensed Consolidated Balance Sheets (Tables)", "R30.htm"),
+            report("CONDENSED CONSOLIDATED BALANCE SHEETS (Unaudited) (Parenthetical)", "R5.htm"),
+            report("CONDENSED CONSOLIDATED BALANCE SHEETS (Unaudited)", "R4.htm"),
+            report("Parent Company Balance Sheet", "R90.htm"),
+        ];
+
+        assert_eq!(find_report_url(&reports, &BALANCE_SHEET), Some("R4.htm"));
+        assert_eq!(find_report_urls(&reports, &BALANCE_SHEET), ["R4.htm", "R90.htm"]);
+        assert_eq!(selected_report(&reports, StatementKind::BalanceSheet).map(|r| &*r.url),
+                   Some("R4.htm"));
+
+        let notes = [report("Balance Sheet Components (Details)", "R40.htm")];
+        assert_eq!(find_report_url(&notes, &BALANCE_SHEET), None);
+    }
+
+
+    #[test]
+    fn income_statement_skips_notes_and_details() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/aapl_10q/R2.htm");
+        let client = crate::fixture::FixtureClient::new().with_response("R2.htm", fixture);
+
+        // only R2.htm is served, picking any other report fails
+        let reports = [
+            report("Condensed Consolidated Statements of Operations (Tables)", "R30.htm"),
+            report("Income Statement Disclosure (Details)", "R41.htm"),
+            report("Comprehensive Income (Details Narrative)", "R42.htm"),
+            report("CONDENSED CONSOLIDATED STATEMENTS OF COMPREHENSIVE INCOME (Unaudited)",
+                   "R3.htm"),
+            report("CONDENSED CONSOLIDATED STATEMENTS OF OPERATIONS (Unaudited)", "R2.htm"),
+        ];
+
+        let statement = income_statement(&client, &reports).unwrap();
+        assert_eq!(statement.source_url.as_deref(), Some("R2.htm"));
+        assert_eq!(statement.get_row_exact("Net income").unwrap()[1..], ["$ 33,916", "$ 29,998"]);
+
+        let notes = &reports[..3];
+        assert!(matches!(income_statement(&client, notes), Err(SecFilingsError::NotFound(_))));
+    }
+
+
+    #[test]
+    fn prefixed_atom_feed_parses_like_unprefixed() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        let read = |name: &str| std::fs::read_to_string(format!("{}/{}", fixtures, name)).unwrap();
+
+        // atom:feed, atom:entry, ... matched by local name
+        let prefixed = parse_documents_feed(&read("feeds/prefixed_atom.xml")).unwrap();
+        let plain = parse_documents_feed(&read("aapl_10q/browse_edgar.xml")).unwrap();
+        assert_eq!(prefixed.len(), 2);
+        assert_eq!(prefixed[0], plain[0]);
+
+        // accession from atom:id when the link has none
+        assert_eq!(prefixed[1].accession.to_string(), "0000320193-23-000106");
+        assert_eq!(prefixed[1].form_type, "10-K");
+        assert_eq!(prefixed[1].filed_on, NaiveDate::from_ymd_opt(2023, 11, 2));
+        assert_eq!(prefixed[1].title,
+                   "10-K  - Annual report [Section 13 and 15(d), not S-K Item 405]");
+    }
+
+
+    #[test]
+    fn quick_xml_reads_what_serde_xml_rs_reads() {
+        // serde-xml-rs is only taken when quick-xml fails, so both must
+        // agree wherever quick-xml succeeds
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        let read = |name: &str| std::fs::read_to_string(format!("{}/{}", fixtures, name)).unwrap();
+
+        for name in ["aapl_10q/browse_edgar.xml", "feeds/prefixed_atom.xml"] {
+            let fast: Feed = from_xml(&read(name)).unwrap();
+            let slow: Feed = from_str(&read(name)).unwrap();
+            assert_eq!(format!("{:?}", fast), format!("{:?}", slow), "{}", name);
+        }
+
+        let padded = "<FilingSummary><MyReports><Report><ShortName> Balance Sheets \n\
+                      </ShortName><HtmlFileName>R2.htm </HtmlFileName><Position>2</Position>\
+                      </Report></MyReports></FilingSummary>";
+        for xml in [read("aapl_10q/FilingSummary.xml"), padded.to_string()] {
+            let fast: FilingSummary = from_xml(&xml).unwrap();
+            let slow: FilingSummary = from_str(&xml).unwrap();
+            assert_eq!(format!("{:?}", fast), format!("{:?}", slow));
+        }
+    }
+
+
+    #[test]
+    fn documents_query_count_is_clamped() {
+        let count = |query: DocumentsQuery| {
+            let params = documents_params(("ticker", "aapl"), &query, &FilingType::TenQ);
+            return params.iter().find(|(name, _)| *name == "count").unwrap().1.clone();
+        };
+
+        assert_eq!(count(DocumentsQuery::new()), "100");
+        assert_eq!(count(DocumentsQuery::new().count(40)), "40");
+        assert_eq!(count(DocumentsQuery::new().count(500)), "100");
+        assert_eq!(count(DocumentsQuery::new().count(0)), "1");
+    }
+
+
+    #[test]
+    fn documents_params_per_filing_type() {
+        let query = DocumentsQuery::new();
+        let cases = [(FilingType::TenQ, "10-Q"), (FilingType::TenK, "10-K"),
+                     (FilingType::EightK, "8-K"), (FilingType::DefProxy, "DEF 14A"),
+                     (FilingType::S1, "S-1"), (FilingType::Other("20-F".to_string()), "20-F"),
+                     (FilingType::Other(String::new()), "")];
+
+        for (filing_type, form) in cases {
+            let params = documents_params(("CIK", "320193"), &query, &filing_type);
+            assert_eq!(params, [("action", "getcompany".to_string()),
+                                ("CIK", "320193".to_string()),
+                                ("type", form.to_string()),
+                                ("datea", String::new()),
+                                ("dateb", String::new()),
+                                ("owner", "exclude".to_string()),
+                                ("start", "0".to_string()),
+                                ("output", "atom".to_string()),
+                                ("count", "100".to_string())]);
+
+            // and back from the form, the empty one aside
+            if !form.is_empty() {
+                assert_eq!(FilingType::from(form), filing_type);
+            }
+        }
+
+        // the form goes into the URL escaped
+        let params = documents_params(("ticker", "aapl"), &query, &FilingType::DefProxy);
+        let url = reqwest::Url::parse_with_params(BROWSE_EDGAR_URL, &params).unwrap();
+        assert!(url.as_str().contains("&type=DEF+14A&"), "{}", url);
+    }
+
+
+    #[test]
+    fn date_range_rejects_an_end_before_its_start() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        let (from, to) = (date(2024, 3, 31), date(2024, 1, 1));
+
+        match DateRange::new(from, to) {
+            Err(SecFilingsError::InvalidDateRange { from: f, to: t }) => {
+                assert_eq!((Some(f), Some(t)), (from, to));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(DateRange::new(from, to).unwrap_err().to_string(),
+                   "invalid date range: 2024-03-31 is after 2024-01-01");
+
+        // a single day, and ranges open at either end
+        assert!(DateRange::new(from, from).is_ok());
+        assert!(DateRange::new(from, None).is_ok());
+        assert!(DateRange::new(None, to).is_ok());
+
+        let range = DateRange::new(to, from).unwrap();
+        let query = DocumentsQuery::new().date_range(range);
+        let params = documents_params(("ticker", "aapl"), &query, &FilingType::TenQ);
+        assert_eq!(params[3], ("datea", "20240101".to_string()));
+        assert_eq!(params[4], ("dateb", "20240331".to_string()));
+    }
+
+
+    #[test]
+    fn documents_paged_stitches_pages() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        let query = DocumentsQuery::new().count(2);
+        let page_url = |start: u32| {
+            let query = query.clone().start(start);
+            let params = documents_params(("ticker", "aapl"), &query, &FilingType::TenQ);
+            return reqwest::Url::parse_with_params(BROWSE_EDGAR_URL, &params).unwrap().to_string();
+        };
+
+        // a full page of two, then a short one; a third request would find
+        // no fixture and fail
+        let client = crate::fixture::FixtureClient::new()
+            .with_response(&page_url(0), format!("{}/feeds/prefixed_atom.xml", fixtures))
+            .with_response(&page_url(2), format!("{}/aapl_10q/browse_edgar.xml", fixtures));
+
+        let accessions = |filings: Vec<FilingRef>| {
+            return filings.iter().map(|filing| filing.accession.to_string()).collect::<Vec<_>>();
+        };
+
+        let all = documents_paged(&client, "aapl", &query, FilingType::TenQ, None).unwrap();
+        assert_eq!(accessions(all), ["0000320193-24-000006", "0000320193-23-000106",
+                                     "0000320193-24-000006"]);
+
+        let capped = documents_paged(&client, "aapl", &query, FilingType::TenQ, Some(2)).unwrap();
+        assert_eq!(accessions(capped), ["0000320193-24-000006", "0000320193-23-000106"]);
+
+        // starting past the first page skips it
+        let rest = documents_paged(&client, "aapl", &query.clone().start(2), FilingType::TenQ,
+                                   None).unwrap();
+        assert_eq!(accessions(rest), ["0000320193-24-000006"]);
+    }
+
+
+    const AAPL_10Q: &str = "https://www.sec.gov/Archives/edgar/data/320193/\
+                            000032019324000006/index.json";
+    const AAPL_FORM_4: &str = "https://www.sec.gov/Archives/edgar/data/320193/\
+                               000032019324000002/index.json";
+    const AAPL_10K: &str = "https://www.sec.gov/Archives/edgar/data/320193/\
+                            000032019323000106/index.json";
+
+
+    /// Apple's 10-Q and 10-K folders, and a Form 4 folder without
+    /// FilingSummary.xml.
+    fn index_client() -> crate::fixture::FixtureClient {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        return crate::fixture::FixtureClient::new()
+               .with_response(AAPL_10Q, format!("{}/aapl_10q/index.json", fixtures))
+               .with_response(AAPL_FORM_4, format!("{}/indexes/form4_index.json", fixtures))
+               .with_response(AAPL_10K, format!("{}/indexes/10k_index.json", fixtures));
+    }
+
+
+    #[test]
+    fn filing_index_lists_the_folder() {
+        let index = filing_index(&index_client(), AAPL_10Q).unwrap();
+
+        assert_eq!(index.directory.name, "/Archives/edgar/data/320193/000032019324000006");
+        assert_eq!(index.directory.items.len(), 10);
+        let summary = &index.directory.items[3];
+        assert_eq!((summary.name.as_str(), summary.size), ("FilingSummary.xml", Some(12254)));
+        assert_eq!(summary.last_modified, "2024-02-01 18:02:05");
+        // blank sizes are none
+        assert_eq!(index.directory.items[0].size, None);
+
+        assert_eq!(index.filing_summary_url().unwrap(),
+                   "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/\
+                    FilingSummary.xml");
+        assert_eq!(index.url_of("R2.htm"),
+                   "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/R2.htm");
+    }
+
+
+    #[test]
+    fn filing_summaries_of_every_filing_in_order() {
+        let client = index_client();
+        let summary = |folder: &str| {
+            format!("https://www.sec.gov/Archives/edgar/data/320193/{}/FilingSummary.xml", folder)
+        };
+        let (ten_q, ten_k) = (summary("000032019324000006"), summary("000032019323000106"));
+
+        let urls = [AAPL_10Q.to_string(), AAPL_10K.to_string()];
+        assert_eq!(filing_summaries(&client, &urls).unwrap(), [ten_q.clone(), ten_k.clone()]);
+
+        let reversed = [AAPL_10K.to_string(), AAPL_10Q.to_string()];
+        assert_eq!(filing_summaries(&client, &reversed).unwrap(), [ten_k, ten_q]);
+    }
+
+
+    #[test]
+    fn filings_without_a_filing_summary() {
+        let client = index_client();
+        let index = filing_index(&client, AAPL_FORM_4).unwrap();
+
+        assert_eq!(index.directory.items.len(), 5);
+        assert_eq!(index.directory.items[4].item_type, "folder.gif");
+        assert_eq!(index.filing_summary_url(), None);
+
+        // skipped by filing_summaries(), like a folder that cannot be fetched
+        let missing = AAPL_FORM_4.replace("000032019324000002", "000032019324000099");
+        let urls = [AAPL_FORM_4.to_string(), AAPL_10Q.to_string(), missing];
+        assert_eq!(filing_summaries(&client, &urls).unwrap(),
+                   [filing_index(&client, AAPL_10Q).unwrap().filing_summary_url().unwrap()]);
+    }
+
+
+    fn unexpected<T>(result: Result<T, SecFilingsError>) -> bool {
+        return matches!(result, Err(SecFilingsError::UnexpectedStructure(_)));
+    }
+
+
+    #[test]
+    fn index_json_failures() {
+        // not JSON, or cut short
+        assert!(matches!(parse_filing_index("<html>"), Err(SecFilingsError::JsonParse(_))));
+        assert!(matches!(parse_filing_index(r#"{"directory": {"name": "/x","#),
+                         Err(SecFilingsError::JsonParse(_))));
+
+        // JSON, but no listing
+        assert!(unexpected(parse_filing_index("{}")));
+        assert!(unexpected(parse_filing_index(r#"{"directory": {"name": "/x"}}"#)));
+        assert!(unexpected(parse_filing_index(r#"{"directory": {"name": "/x", "item": 1}}"#)));
+        assert!(parse_filing_index(r#"{"directory": {"name": "/x", "item": []}}"#).is_ok());
+    }
+
+
+    #[test]
+    fn feed_and_filing_summary_failures() {
+        assert!(matches!(parse_documents_feed("<feed><entry>"), Err(SecFilingsError::XmlParse(_))));
+        // an entry without its link
+        let entry = "<feed><entry><title>10-K</title></entry></feed>";
+        assert!(unexpected(parse_documents_feed(entry)));
+
+        let summary = |xml| parse_filing_summary("FilingSummary.xml", xml);
+        assert!(matches!(summary("<FilingSummary><MyReports>"), Err(SecFilingsError::XmlParse(_))));
+        assert!(unexpected(summary("<FilingSummary></FilingSummary>")));
+        assert!(unexpected(summary("<FilingSummary><MyReports><Report><Position>first</Position>\
+                                    </Report></MyReports></FilingSummary>")));
+    }
+
+
+    #[test]
+    fn statement_failures() {
+        let html = "<html><p>no table</p></html>";
+        assert!(unexpected(crate::parse::parse_html_statement_data(html)));
+
+        let notes = [report("Balance Sheet Components (Details)", "R40.htm")];
+        let client = crate::fixture::FixtureClient::new();
+        assert!(matches!(balance_sheet_first(&client, &notes), Err(SecFilingsError::NotFound(_))));
+    }
+}
@@ -0,0 +1,146 @@
+//! Error type shared by the public API.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use chrono::NaiveDate;
+use serde_json::error::Category;
+
+
+/// Errors returned by the public API.
+#[derive(Debug)]
+pub enum SecFilingsError {
+    /// Request failed, or the server could not be reached.
+    Http(reqwest::Error),
+    /// Server answered with a non-success status, `body` holds the start
+    /// of the response.
+    HttpStatus { status: u16, body: String },
+    /// SEC rejected the request as coming from an undeclared automated
+    /// tool, i.e. the `User-Agent` lacks proper contact info.
+    UndeclaredAutomatedTool,
+    /// Atom feed or FilingSummary.xml is not well-formed XML.
+    XmlParse(serde_xml_rs::Error),
+    /// index.json or a data.sec.gov response is not valid JSON.
+    JsonParse(serde_json::Error),
+    /// Something we looked for is missing, e.g. the balance sheet report.
+    NotFound(String),
+    /// Statement HTML does not have the expected structure.
+    HtmlParse(String),
+    /// A document parsed, but is not shaped the way EDGAR documents are,
+    /// e.g. an index.json without a directory listing, or a statement
+    /// without a table.
+    UnexpectedStructure(String),
+    /// SEC refused the request for exceeding its request rate.
+    RateLimited,
+    /// The response cache directory could not be set up.
+    Cache(String),
+    /// `User-Agent` is missing or lacks the contact info SEC requires.
+    InvalidUserAgent(String),
+    /// Figures that should reconcile do not, holds (computed, expected).
+    ReconciliationError(f64, f64),
+    /// An argument the API cannot take, e.g. an unknown taxonomy.
+    InvalidInput(String),
+    /// A date range ending before it starts.
+    InvalidDateRange { from: NaiveDate, to: NaiveDate },
+    /// Reading or writing a local file failed.
+    Io(io::Error),
+}
+
+
+/// Shorter name for `SecFilingsError`.
+pub type SecError = SecFilingsError;
+
+
+impl fmt::Display for SecFilingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecFilingsError::Http(e) => write!(f, "HTTP error: {}", e),
+            SecFilingsError::HttpStatus { status, body } => write!(f, "HTTP status {}: {}",
+                                                                  status, body),
+            SecFilingsError::UndeclaredAutomatedTool => write!(f,
+                "SEC rejected the request as an undeclared automated tool, check the user agent"),
+            SecFilingsError::XmlParse(e) => write!(f, "XML parse error: {}", e),
+            SecFilingsError::JsonParse(e) => write!(f, "JSON parse error: {}", e),
+            SecFilingsError::NotFound(what) => write!(f, "not found: {}", what),
+            SecFilingsError::HtmlParse(msg) => write!(f, "HTML parse error: {}", msg),
+            SecFilingsError::UnexpectedStructure(msg) => write!(f, "unexpected structure: {}",
+                                                                msg),
+            SecFilingsError::RateLimited => write!(f, "rate limited by SEC"),
+            SecFilingsError::Cache(msg) => write!(f, "cache error: {}", msg),
+            SecFilingsError::InvalidUserAgent(msg) => write!(f, "invalid user agent: {}", msg),
+            SecFilingsError::ReconciliationError(sum, expected) => write!(f,
+                "reconciliation failed: {} does not match {}", sum, expected),
+            SecFilingsError::InvalidInput(msg) => write!(f, "invalid input: {}", msg),
+            SecFilingsError::InvalidDateRange { from, to } => write!(f,
+                "invalid date range: {} is after {}", from, to),
+            SecFilingsError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+
+impl Error for SecFilingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SecFilingsError::Http(e) => Some(e),
+            SecFilingsError::HttpStatus { .. } => None,
+            SecFilingsError::UndeclaredAutomatedTool => None,
+            SecFilingsError::XmlParse(e) => Some(e),
+            SecFilingsError::JsonParse(e) => Some(e),
+            SecFilingsError::NotFound(_) => None,
+            SecFilingsError::HtmlParse(_) => None,
+            SecFilingsError::UnexpectedStructure(_) => None,
+            SecFilingsError::RateLimited => None,
+            SecFilingsError::Cache(_) => None,
+            SecFilingsError::InvalidUserAgent(_) => None,
+            SecFilingsError::ReconciliationError(..) => None,
+            SecFilingsError::InvalidInput(_) => None,
+            SecFilingsError::InvalidDateRange { .. } => None,
+            SecFilingsError::Io(e) => Some(e),
+        }
+    }
+}
+
+
+impl From<reqwest::Error> for SecFilingsError {
+    fn from(e: reqwest::Error) -> Self {
+        SecFilingsError::Http(e)
+    }
+}
+
+
+/// Well-formed XML missing elements or holding values of the wrong type
+/// is `UnexpectedStructure`, the rest `XmlParse`.
+impl From<serde_xml_rs::Error> for SecFilingsError {
+    fn from(e: serde_xml_rs::Error) -> Self {
+        match e {
+            serde_xml_rs::Error::Custom { .. }
+            | serde_xml_rs::Error::ParseIntError { .. }
+            | serde_xml_rs::Error::ParseFloatError { .. }
+            | serde_xml_rs::Error::ParseBoolError { .. } => {
+                SecFilingsError::UnexpectedStructure(e.to_string())
+            }
+            _ => SecFilingsError::XmlParse(e),
+        }
+    }
+}
+
+
+/// Valid JSON of the wrong shape is `UnexpectedStructure`, the rest
+/// `JsonParse`.
+impl From<serde_json::Error> for SecFilingsError {
+    fn from(e: serde_json::Error) -> Self {
+        match e.classify() {
+            Category::Data => SecFilingsError::UnexpectedStructure(e.to_string()),
+            _ => SecFilingsError::JsonParse(e),
+        }
+    }
+}
+
+
+impl From<io::Error> for SecFilingsError {
+    fn from(e: io::Error) -> Self {
+        SecFilingsError::Io(e)
+    }
+}
@@ -0,0 +1,224 @@
+//! Plain text exports of `StatementData`.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::parse::{CellValue, CollapsePolicy, StatementData};
+
+
+/// Quote a CSV field if it contains a delimiter, quote or line break.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        return Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")));
+    }
+    return Cow::Borrowed(field);
+}
+
+
+/// `row` with every cell after the label replaced by its value under
+/// `policy`, empty if it has none.
+fn numeric_row(row: &[String], policy: CollapsePolicy) -> Vec<String> {
+    let values = row.iter().skip(1).map(|cell| CellValue::parse(cell).to_option(policy)
+                                                .map_or(String::new(), |value| value.to_string()));
+    return row.iter().take(1).cloned().chain(values).collect();
+}
+
+
+/// Write `row` padded with empty fields to `width` columns, then `row_id`.
+fn write_csv_row<W: Write>(writer: &mut W, row: &[String], width: usize, row_id: &str)
+                           -> io::Result<()> {
+    for i in 0..width.max(row.len()) {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if let Some(field) = row.get(i) {
+            writer.write_all(csv_field(field).as_bytes())?;
+        }
+    }
+    return writeln!(writer, ",{}", csv_field(row_id));
+}
+
+
+
+impl StatementData {
+
+    /// The statement as CSV, see `write_csv()`.
+    pub fn to_csv(&self) -> String {
+        let mut out = Vec::new();
+        self.write_csv(&mut out).expect("writing to a Vec does not fail");
+
+        return String::from_utf8(out).expect("CSV is built from strings");
+    }
+
+
+    /// Write the statement as CSV: the header rows, a blank line, then the
+    /// data rows with each section as a `[section] <name>` row before its
+    /// rows. Ragged rows are padded so every row has as many columns as
+    /// the widest one, followed by a last `row_id` column: "row_id" in the
+    /// first header row, the `row_ids` of the data rows, empty elsewhere.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), io::Error> {
+        return self.write_csv_values(writer, None);
+    }
+
+
+    /// The statement as numeric CSV, see `write_numeric_csv()`.
+    pub fn to_numeric_csv(&self, policy: CollapsePolicy) -> String {
+        let mut out = Vec::new();
+        self.write_numeric_csv(&mut out, policy).expect("writing to a Vec does not fail");
+
+        return String::from_utf8(out).expect("CSV is built from strings");
+    }
+
+
+    /// Like `write_csv()`, with the cells after each label written as the
+    /// number they hold under `policy`: "0" for a zero, empty for a cell
+    /// without a value, so a blank or a dash never reads as a real zero.
+    pub fn write_numeric_csv<W: Write>(&self, writer: W, policy: CollapsePolicy)
+                                       -> Result<(), io::Error> {
+        return self.write_csv_values(writer, Some(policy));
+    }
+
+
+    /// `write_csv()`, with data cells as numbers under `policy` if given.
+    fn write_csv_values<W: Write>(&self, mut writer: W, policy: Option<CollapsePolicy>)
+                                  -> Result<(), io::Error> {
+        let width = self.headers.iter().chain(&self.data).map(Vec::len).max()
+                        .unwrap_or(0).max(2);
+
+        for (i, header) in self.headers.iter().enumerate() {
+            write_csv_row(&mut writer, header, width, if i == 0 { "row_id" } else { "" })?;
+        }
+        writeln!(writer)?;
+
+        let section_row = |name: &str| vec![format!("[section] {}", name)];
+
+        // sections before this index have been written
+        let mut next_section = 0;
+
+        for (i, row) in self.data.iter().enumerate() {
+            if let Some(Some(section)) = self.row_sections.get(i) {
+                while next_section <= *section {
+                    write_csv_row(&mut writer, &section_row(&self.sections[next_section]),
+                                  width, "")?;
+                    next_section += 1;
+                }
+            }
+            let row_id = self.row_ids.get(i).map_or("", String::as_str);
+            match policy {
+                Some(policy) => write_csv_row(&mut writer, &numeric_row(row, policy), width,
+                                              row_id)?,
+                None => write_csv_row(&mut writer, row, width, row_id)?,
+            }
+        }
+
+        // trailing sections without rows
+        for section in self.sections.iter().skip(next_section) {
+            write_csv_row(&mut writer, &section_row(section), width, "")?;
+        }
+
+        return Ok(());
+    }
+
+
+    /// Write the statement as CSV to the file at `path`, replacing it if
+    /// it exists.
+    pub fn to_csv_file<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        return writer.flush();
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parse_html_statement_data;
+
+    use super::*;
+
+
+    fn statement_fixture(name: &str) -> StatementData {
+        let path = format!("{}/tests/fixtures/statements/{}", env!("CARGO_MANIFEST_DIR"), name);
+        return parse_html_statement_data(&std::fs::read_to_string(path).unwrap()).unwrap();
+    }
+
+
+    #[test]
+    fn csv_ends_rows_in_their_row_id() {
+        let statement = statement_fixture("other_rows_q1.htm");
+        let csv = statement.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "CONSOLIDATED BALANCE SHEETS - USD ($)  $ in Millions,\
+                              \"Mar. 31, 2024\",row_id");
+        assert_eq!(lines[1], "");
+        assert_eq!(lines[2], "[section] Current assets:,,");
+        assert_eq!(lines[3], format!("Cash,\"$ 1,200\",{}", statement.row_ids[0]));
+        assert_eq!(lines[4], format!("Other,310,{}", statement.row_ids[1]));
+
+        // one line per header, blank, section and data row
+        assert_eq!(lines.len(), 1 + 1 + 3 + statement.data.len());
+    }
+
+
+    #[test]
+    fn numeric_csv_keeps_zeros_apart_from_missing_values() {
+        let row = |cells: &[&str]| cells.iter().map(|cell| cell.to_string()).collect();
+        let statement = StatementData {
+            headers: vec![row(&["USD ($) $ in Millions", "Q1", "Q2", "Q3"])],
+            data: vec![row(&["Revenue", "$ 1,200", "0", "(15)"]),
+                       row(&["Goodwill", "\u{2014}", "", "See Note 5"])],
+            row_ids: vec!["r1".to_string(), "r2".to_string()],
+            row_sections: vec![None, None],
+            ..StatementData::default()
+        };
+
+        let csv = statement.to_numeric_csv(CollapsePolicy::default());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "USD ($) $ in Millions,Q1,Q2,Q3,row_id");
+        assert_eq!(lines[2], "Revenue,1200,0,-15,r1");
+        assert_eq!(lines[3], "Goodwill,,,,r2");
+
+        let dash_as_zero = CollapsePolicy { dash_as_zero: true, empty_as_zero: false };
+        assert_eq!(statement.to_numeric_csv(dash_as_zero).lines().nth(3),
+                   Some("Goodwill,0,,,r2"));
+
+        // the text CSV is left as filed
+        assert_eq!(statement.to_csv().lines().nth(3), Some("Goodwill,\u{2014},,See Note 5,r2"));
+    }
+
+
+    #[test]
+    fn csv_fields_round_trip_through_a_csv_reader() {
+        let row = |cells: &[&str]| cells.iter().map(|cell| cell.to_string()).collect();
+        let statement = StatementData {
+            headers: vec![row(&["BALANCE SHEETS - USD ($)", "Dec. 30, 2023"])],
+            sections: vec!["Revenue, net:".to_string()],
+            data: vec![row(&["Revenue, net", "$ 1,200"]),
+                       row(&["Shares \"as converted\"", "(15)"]),
+                       row(&["Other\nincome, net", "\u{2014}"])],
+            row_ids: vec!["r1".to_string(), "r2".to_string(), "r3".to_string()],
+            row_sections: vec![Some(0), Some(0), Some(0)],
+            ..StatementData::default()
+        };
+
+        let csv = statement.to_csv();
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).from_reader(csv.as_bytes());
+        let records: Vec<Vec<String>> = reader.records().map(|record| {
+            record.unwrap().iter().map(str::to_string).collect()
+        }).collect();
+
+        // the blank line after the headers is skipped by the reader
+        let expected: Vec<Vec<String>> = vec![
+            row(&["BALANCE SHEETS - USD ($)", "Dec. 30, 2023", "row_id"]),
+            row(&["[section] Revenue, net:", "", ""]),
+            row(&["Revenue, net", "$ 1,200", "r1"]),
+            row(&["Shares \"as converted\"", "(15)", "r2"]),
+            row(&["Other\nincome, net", "\u{2014}", "r3"]),
+        ];
+        assert_eq!(records, expected);
+    }
+}
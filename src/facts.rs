@@ -0,0 +1,873 @@
+//! Every XBRL fact a company reported, from the data.sec.gov company
+//! facts API.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::NaiveDate;
+
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::Deserialize;
+
+use crate::client::SecClient;
+use crate::error::SecFilingsError;
+use crate::tickers::padded_cik;
+
+
+/// Base URL of the company facts API, followed by "CIK##########.json".
+pub const COMPANY_FACTS_URL: &str = "https://data.sec.gov/api/xbrl/companyfacts/";
+
+
+/// Base URL of the company concept API, followed by
+/// "CIK##########/{taxonomy}/{tag}.json".
+pub const COMPANY_CONCEPT_URL: &str = "https://data.sec.gov/api/xbrl/companyconcept/";
+
+
+/// Taxonomies `company_concept()` accepts.
+pub const CONCEPT_TAXONOMIES: [&str; 4] = ["us-gaap", "dei", "ifrs-full", "srt"];
+
+
+/// Base URL of the frames API, followed by "{taxonomy}/{tag}/{unit}/{period}.json".
+pub const FRAMES_URL: &str = "https://data.sec.gov/api/xbrl/frames/";
+
+
+/// Facts of one company, by taxonomy ("us-gaap", "dei", "ifrs-full", ...)
+/// and concept name, e.g. `facts["us-gaap"]["Assets"]`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyFacts {
+    pub cik: u64,
+    #[serde(default)]
+    pub entity_name: String,
+    #[serde(default)]
+    pub facts: HashMap<String, HashMap<String, Concept>>,
+}
+
+
+impl CompanyFacts {
+
+    /// Concept called `concept` ("Assets"), or "us-gaap:Assets" to pick
+    /// the taxonomy. Unqualified names are looked up in us-gaap first,
+    /// then the other taxonomies in name order.
+    pub fn concept(&self, concept: &str) -> Option<&Concept> {
+        if let Some((taxonomy, name)) = concept.split_once(':') {
+            return self.facts.get(taxonomy)?.get(name);
+        }
+
+        let mut taxonomies: Vec<&String> = self.facts.keys().collect();
+        taxonomies.sort_by_key(|taxonomy| (taxonomy.as_str() != "us-gaap", taxonomy.as_str()));
+
+        return taxonomies.into_iter().find_map(|taxonomy| self.facts[taxonomy].get(concept));
+    }
+
+
+    /// Values of `concept` (see `concept()`) in `unit`, e.g.
+    /// `facts.get("Assets", "USD")`, as reported, duplicates included.
+    pub fn get(&self, concept: &str, unit: &str) -> Option<&[FactValue]> {
+        return self.concept(concept)?.units.get(unit).map(Vec::as_slice);
+    }
+
+
+    /// Values of `concept` in `unit` reported in `form` filings ("10-Q",
+    /// amendments included), of fiscal period `fp` ("Q1".."Q3", "FY") if
+    /// given, one per period, ordered by period end.
+    ///
+    /// A period is reported again as comparative by later filings, and
+    /// sometimes restated, so the most recently filed value is kept.
+    ///
+    /// Durations are kept only if as long as the form's period, about 90
+    /// days for a 10-Q and a year for a 10-K, 20-F or 40-F, which leaves
+    /// out the year to date values of second and third quarter 10-Qs and
+    /// the quarters some 10-Ks tag. Instants are always kept.
+    pub fn series(&self, concept: &str, unit: &str, form: &str, fp: Option<&str>)
+                  -> Vec<&FactValue> {
+        let amendment = format!("{}/A", form);
+        let days = period_days(form);
+        let mut latest: HashMap<(Option<&str>, &str), &FactValue> = HashMap::new();
+
+        for value in self.get(concept, unit).unwrap_or_default() {
+            if value.form != form && value.form != amendment {
+                continue;
+            }
+            if fp.is_some() && value.fp.as_deref() != fp {
+                continue;
+            }
+            if let Some(days) = &days {
+                if value.duration_days().is_some_and(|duration| !days.contains(&duration)) {
+                    continue;
+                }
+            }
+
+            let period = (value.start.as_deref(), value.end.as_str());
+            match latest.get(&period) {
+                Some(kept) if kept.filed >= value.filed => {}
+                _ => {
+                    latest.insert(period, value);
+                }
+            }
+        }
+
+        let mut series: Vec<&FactValue> = latest.into_values().collect();
+        series.sort_by(|a, b| a.end.cmp(&b.end).then_with(|| a.start.cmp(&b.start)));
+        return series;
+    }
+
+
+    /// Latest quarterly value of each concept in `taxonomy` ("us-gaap"),
+    /// by concept name: of the values reported in 10-Qs, that of the
+    /// most recently filed one, for the latest quarter it covers.
+    ///
+    /// Year to date values are left out as in `series()`. A concept is
+    /// taken in "USD" if reported in it, otherwise in the first of its
+    /// units by name, e.g. "shares".
+    pub fn latest_quarterly_values(&self, taxonomy: &str) -> HashMap<String, f64> {
+        return self.latest_values(taxonomy, "10-Q");
+    }
+
+
+    /// Like `latest_quarterly_values()`, for the fiscal years of 10-Ks.
+    pub fn latest_annual_values(&self, taxonomy: &str) -> HashMap<String, f64> {
+        return self.latest_values(taxonomy, "10-K");
+    }
+
+
+    fn latest_values(&self, taxonomy: &str, form: &str) -> HashMap<String, f64> {
+        let amendment = format!("{}/A", form);
+        let days = period_days(form);
+        let mut latest = HashMap::new();
+
+        for (name, concept) in self.facts.get(taxonomy).into_iter().flatten() {
+            let mut units: Vec<&String> = concept.units.keys().collect();
+            units.sort_by_key(|unit| (unit.as_str() != "USD", unit.as_str()));
+
+            let value = units.into_iter().find_map(|unit| {
+                concept.units[unit].iter()
+                    .filter(|value| value.form == form || value.form == amendment)
+                    .filter(|value| match (&days, value.duration_days()) {
+                        (Some(days), Some(duration)) => days.contains(&duration),
+                        _ => true,
+                    })
+                    .max_by(|a, b| a.filed.cmp(&b.filed).then_with(|| a.end.cmp(&b.end)))
+            });
+
+            if let Some(value) = value {
+                latest.insert(name.clone(), value.val);
+            }
+        }
+
+        return latest;
+    }
+}
+
+
+
+/// Length in days of the periods `form` reports, `None` for forms not
+/// tied to one.
+fn period_days(form: &str) -> Option<RangeInclusive<i64>> {
+    return match form.trim_end_matches("/A") {
+        "10-Q" => Some(80..=100),
+        "10-K" | "20-F" | "40-F" => Some(350..=380),
+        _ => None,
+    };
+}
+
+
+
+/// A taxonomy concept and the values reported for it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Concept {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    /// Values by unit, e.g. "USD", "shares" or "USD/shares".
+    #[serde(default)]
+    pub units: HashMap<String, Vec<FactValue>>,
+}
+
+
+/// One reported value. The same value shows up once per filing that
+/// reported it, e.g. in a 10-Q and again as comparative in the next 10-K.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FactValue {
+    pub val: f64,
+    /// Start of the period, "YYYY-MM-DD", `None` for instants such as
+    /// balance sheet values.
+    pub start: Option<String>,
+    /// End of the period or the instant, "YYYY-MM-DD".
+    pub end: String,
+    /// Accession number of the reporting filing.
+    #[serde(default)]
+    pub accn: String,
+    /// Fiscal year and period ("Q1".."Q3", "FY") of the reporting filing.
+    pub fy: Option<i32>,
+    pub fp: Option<String>,
+    /// Form type of the reporting filing, e.g. "10-Q".
+    #[serde(default)]
+    pub form: String,
+    /// "YYYY-MM-DD".
+    #[serde(default)]
+    pub filed: String,
+    /// Calendar period the value was picked for in the frames API, e.g.
+    /// "CY2023Q4I", if any.
+    pub frame: Option<String>,
+}
+
+
+impl FactValue {
+
+    /// Days from `start` to `end`, `None` for an instant, or dates that
+    /// do not parse.
+    pub fn duration_days(&self) -> Option<i64> {
+        let start = NaiveDate::parse_from_str(self.start.as_deref()?, "%Y-%m-%d").ok()?;
+        let end = NaiveDate::parse_from_str(&self.end, "%Y-%m-%d").ok()?;
+        return Some((end - start).num_days());
+    }
+}
+
+
+
+/// Every value one company reported for one concept, from the company
+/// concept API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConceptHistory {
+    pub cik: u64,
+    /// e.g. "us-gaap".
+    #[serde(default)]
+    pub taxonomy: String,
+    /// Concept name, e.g. "Assets".
+    #[serde(default)]
+    pub tag: String,
+    #[serde(default)]
+    pub entity_name: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    /// Values by unit, e.g. "USD", "shares" or "USD/shares".
+    #[serde(default)]
+    pub units: HashMap<String, Vec<FactValue>>,
+}
+
+
+/// Name `company_concept()` was asked to return under.
+pub type ConceptSeries = ConceptHistory;
+
+
+impl ConceptHistory {
+
+    /// Values in `unit` ordered by period end, each reported value once.
+    /// The API can list a value twice for the same filing, those are
+    /// merged by period, form and accession number.
+    pub fn series(&self, unit: &str) -> Vec<&FactValue> {
+        let mut series: Vec<&FactValue> = self.units.get(unit).into_iter().flatten().collect();
+
+        series.sort_by(|a, b| a.end.cmp(&b.end).then_with(|| a.start.cmp(&b.start))
+                               .then_with(|| a.form.cmp(&b.form))
+                               .then_with(|| a.accn.cmp(&b.accn)));
+        series.dedup_by(|a, b| a.end == b.end && a.start == b.start && a.form == b.form
+                               && a.accn == b.accn);
+        return series;
+    }
+}
+
+
+
+/// One concept across every company reporting it for one calendar
+/// period, from the frames API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FrameData {
+    #[serde(default)]
+    pub taxonomy: String,
+    #[serde(default)]
+    pub tag: String,
+    /// Calendar period, e.g. "CY2023Q4I".
+    #[serde(rename = "ccp", default)]
+    pub period: String,
+    /// Unit of the values, e.g. "USD".
+    #[serde(rename = "uom", default)]
+    pub unit: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    /// One value per company, the latest filed for the period.
+    #[serde(default)]
+    pub data: Vec<FrameEntry>,
+}
+
+
+/// One company's value in a `FrameData`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameEntry {
+    pub cik: u64,
+    #[serde(default)]
+    pub entity_name: String,
+    pub val: f64,
+    /// Accession number of the reporting filing.
+    #[serde(default)]
+    pub accn: String,
+    /// End of the period or the instant, "YYYY-MM-DD".
+    #[serde(default)]
+    pub end: String,
+    /// Location of the company, e.g. "US-CA".
+    pub loc: Option<String>,
+}
+
+
+
+/// Concepts `company_facts()` keeps, by name ("Assets") or qualified
+/// name ("us-gaap:Assets"), `None` for all.
+#[derive(Clone, Copy)]
+struct ConceptFilter<'a>(Option<&'a [&'a str]>);
+
+
+impl ConceptFilter<'_> {
+
+    fn keeps_all(&self) -> bool {
+        return self.0.is_none();
+    }
+
+
+    fn keeps(&self, taxonomy: &str, concept: &str) -> bool {
+        return match self.0 {
+            None => true,
+            Some(concepts) => concepts.iter().any(|wanted| match wanted.split_once(':') {
+                Some((prefix, name)) => prefix == taxonomy && name == concept,
+                None => *wanted == concept,
+            }),
+        };
+    }
+
+
+    /// Copy of `facts` with only the concepts kept.
+    fn apply(&self, facts: &CompanyFacts) -> CompanyFacts {
+        let kept = facts.facts.iter().filter_map(|(taxonomy, concepts)| {
+            let concepts: HashMap<String, Concept> = concepts.iter()
+                .filter(|(concept, _)| self.keeps(taxonomy, concept))
+                .map(|(concept, values)| (concept.clone(), values.clone()))
+                .collect();
+            (!concepts.is_empty()).then(|| (taxonomy.clone(), concepts))
+        });
+
+        return CompanyFacts {
+            cik: facts.cik,
+            entity_name: facts.entity_name.clone(),
+            facts: kept.collect(),
+        };
+    }
+}
+
+
+/// Top level object, deserialized field by field so `facts` can be
+/// filtered while it is read.
+struct CompanyFactsSeed<'a>(ConceptFilter<'a>);
+
+/// "facts": taxonomy -> concepts.
+struct TaxonomiesSeed<'a>(ConceptFilter<'a>);
+
+/// One taxonomy: concept name -> concept, skipping filtered out ones
+/// without building them.
+struct ConceptsSeed<'a> {
+    filter: ConceptFilter<'a>,
+    taxonomy: &'a str,
+}
+
+
+impl<'de> DeserializeSeed<'de> for CompanyFactsSeed<'_> {
+    type Value = CompanyFacts;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        return deserializer.deserialize_map(self);
+    }
+}
+
+
+impl<'de> Visitor<'de> for CompanyFactsSeed<'_> {
+    type Value = CompanyFacts;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "a company facts object");
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut cik = None;
+        let mut entity_name = String::new();
+        let mut facts = HashMap::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "cik" => cik = Some(map.next_value()?),
+                "entityName" => entity_name = map.next_value()?,
+                "facts" => facts = map.next_value_seed(TaxonomiesSeed(self.0))?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let cik = cik.ok_or_else(|| serde::de::Error::missing_field("cik"))?;
+        return Ok(CompanyFacts { cik, entity_name, facts });
+    }
+}
+
+
+impl<'de> DeserializeSeed<'de> for TaxonomiesSeed<'_> {
+    type Value = HashMap<String, HashMap<String, Concept>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        return deserializer.deserialize_map(self);
+    }
+}
+
+
+impl<'de> Visitor<'de> for TaxonomiesSeed<'_> {
+    type Value = HashMap<String, HashMap<String, Concept>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "a map of taxonomies");
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut taxonomies = HashMap::new();
+
+        while let Some(taxonomy) = map.next_key::<String>()? {
+            let concepts = map.next_value_seed(ConceptsSeed { filter: self.0,
+                                                              taxonomy: &taxonomy })?;
+            // leave out taxonomies the filter emptied
+            if self.0.keeps_all() || !concepts.is_empty() {
+                taxonomies.insert(taxonomy, concepts);
+            }
+        }
+
+        return Ok(taxonomies);
+    }
+}
+
+
+impl<'de> DeserializeSeed<'de> for ConceptsSeed<'_> {
+    type Value = HashMap<String, Concept>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        return deserializer.deserialize_map(self);
+    }
+}
+
+
+impl<'de> Visitor<'de> for ConceptsSeed<'_> {
+    type Value = HashMap<String, Concept>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "a map of concepts");
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut concepts = HashMap::new();
+
+        while let Some(name) = map.next_key::<String>()? {
+            if self.filter.keeps(self.taxonomy, &name) {
+                concepts.insert(name, map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        return Ok(concepts);
+    }
+}
+
+
+
+/// Parse a company facts JSON document from `reader`, keeping only
+/// `concepts` if given. Filtered out concepts are skipped as they are
+/// read, so memory use follows what is kept, not the document size.
+pub(crate) fn read_company_facts(reader: &mut dyn Read, concepts: Option<&[&str]>)
+                                 -> Result<CompanyFacts, SecFilingsError> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let facts = CompanyFactsSeed(ConceptFilter(concepts)).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+
+    return Ok(facts);
+}
+
+
+
+/// XBRL facts the company with `cik` reported, in any filing, limited to
+/// `concepts` ("Assets", or "us-gaap:Assets" to pick the taxonomy) if
+/// given.
+///
+/// The response runs to tens of megabytes for large filers. It is parsed
+/// as it downloads, keeping only the requested concepts, unless a
+/// `SecClientBuilder::cache_ttl` has it cached, which needs it whole.
+///
+/// With a `SecClientBuilder::with_fundamentals_cache`, every company's
+/// facts fetched without `concepts` are kept in memory, and later calls
+/// for the company are answered from there, with or without `concepts`.
+pub fn company_facts(sec_client: &SecClient, cik: u64, concepts: Option<&[&str]>)
+                     -> Result<CompanyFacts, SecFilingsError> {
+    let filter = ConceptFilter(concepts);
+
+    if let Some(cache) = sec_client.fundamentals_cache() {
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(facts) = cache.get(cik) {
+            return Ok(filter.apply(facts));
+        }
+    }
+
+    let url = format!("{}CIK{}.json", COMPANY_FACTS_URL, padded_cik(cik));
+    let facts = sec_client.get_with_reader(&url, |reader| read_company_facts(reader, concepts))?;
+
+    // a filtered copy would answer later calls for other concepts wrongly
+    if let (Some(cache), true) = (sec_client.fundamentals_cache(), filter.keeps_all()) {
+        cache.lock().unwrap_or_else(|e| e.into_inner()).insert(cik, facts.clone());
+    }
+    return Ok(facts);
+}
+
+
+
+/// History of concept `tag` in `taxonomy` (one of `CONCEPT_TAXONOMIES`)
+/// as reported by the company with `cik`, e.g. every "us-gaap"/"Assets"
+/// value. Far smaller a download than `company_facts()` for a single
+/// concept.
+///
+/// `InvalidInput` if the taxonomy is unknown or `tag` is not a concept
+/// name, letters, digits and underscores only, and `NotFound` if the
+/// company never reported the concept, which the API answers with a 404.
+pub fn company_concept(sec_client: &SecClient, cik: u64, taxonomy: &str, tag: &str)
+                       -> Result<ConceptHistory, SecFilingsError> {
+    check_concept(taxonomy, tag)?;
+
+    let url = format!("{}CIK{}/{}/{}.json", COMPANY_CONCEPT_URL, padded_cik(cik), taxonomy, tag);
+    let history = sec_client.get_with_reader(&url, |reader| Ok(serde_json::from_reader(reader)?));
+
+    // the API's answer to a concept the company never reported
+    if let Err(SecFilingsError::HttpStatus { status: 404, .. }) = history {
+        return Err(SecFilingsError::NotFound(format!("{}:{} for CIK {}", taxonomy, tag, cik)));
+    }
+    return history;
+}
+
+
+
+/// Most threads `fetch_concepts_parallel()` runs, the requests SEC allows
+/// per second.
+const CONCEPT_FETCHERS: usize = 10;
+
+
+/// `company_concept()` of each `(taxonomy, concept)` in `concepts`, by
+/// "{taxonomy}:{concept}", e.g. "us-gaap:Assets", fetched on up to 10
+/// threads at a time.
+///
+/// The requests go through the client's rate limiter like any other, so
+/// `n` concepts take about `n / 10` seconds rather than `n` round trips.
+/// The first error is returned, and the remaining concepts not fetched.
+pub fn fetch_concepts_parallel(sec_client: &Arc<SecClient>, cik: u64,
+                               concepts: &[(String, String)])
+                               -> Result<HashMap<String, ConceptSeries>, SecFilingsError> {
+    let mut keys: Vec<(&str, &str)> = concepts.iter()
+                                              .map(|(taxonomy, tag)| (&**taxonomy, &**tag))
+                                              .collect();
+    keys.sort();
+    keys.dedup();
+
+    let next = AtomicUsize::new(0);
+    let fetched = Mutex::new(HashMap::new());
+    let failed: Mutex<Option<SecFilingsError>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..keys.len().min(CONCEPT_FETCHERS) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(&(taxonomy, tag)) = keys.get(i) else { return };
+                if failed.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+                    return;
+                }
+
+                match company_concept(sec_client, cik, taxonomy, tag) {
+                    Ok(series) => {
+                        fetched.lock().unwrap_or_else(|e| e.into_inner())
+                               .insert(format!("{}:{}", taxonomy, tag), series);
+                    }
+                    Err(e) => {
+                        failed.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(e);
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = failed.into_inner().unwrap_or_else(|e| e.into_inner()) {
+        return Err(e);
+    }
+    return Ok(fetched.into_inner().unwrap_or_else(|e| e.into_inner()));
+}
+
+
+
+/// `InvalidInput` unless `taxonomy` is one of `CONCEPT_TAXONOMIES` and
+/// `tag` a concept name, which goes into the URL as it is.
+fn check_concept(taxonomy: &str, tag: &str) -> Result<(), SecFilingsError> {
+    if !CONCEPT_TAXONOMIES.contains(&taxonomy) {
+        return Err(SecFilingsError::InvalidInput(format!("taxonomy '{}', expected one of {}",
+                                                         taxonomy, CONCEPT_TAXONOMIES.join(", "))));
+    }
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(SecFilingsError::InvalidInput(format!("concept '{}', expected letters, \
+                                                          digits and underscores", tag)));
+    }
+    return Ok(());
+}
+
+
+/// `InvalidInput` unless `unit` is a unit name, e.g. "USD-per-shares", and
+/// `period` one of the frames API's: "CY2023", "CY2023Q1" or "CY2023Q1I".
+fn check_frame(unit: &str, period: &str) -> Result<(), SecFilingsError> {
+    if unit.is_empty() || !unit.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(SecFilingsError::InvalidInput(format!("unit '{}', expected letters, digits, \
+                                                          hyphens and underscores", unit)));
+    }
+
+    let year = period.strip_prefix("CY").filter(|rest| rest.len() >= 4)
+                     .map(|rest| rest.split_at(4));
+    let valid = match year {
+        Some((year, quarter)) if year.bytes().all(|b| b.is_ascii_digit()) =>
+            matches!(quarter.strip_suffix('I').unwrap_or(quarter), "" | "Q1" | "Q2" | "Q3" | "Q4")
+            && quarter != "I",
+        _ => false,
+    };
+    if !valid {
+        return Err(SecFilingsError::InvalidInput(format!("period '{}', expected e.g. CY2023, \
+                                                          CY2023Q1 or CY2023Q1I", period)));
+    }
+    return Ok(());
+}
+
+
+
+/// Value of concept `tag` in `taxonomy`, in `unit` ("USD", "shares",
+/// "USD-per-shares"), for `period` across every company that reported it.
+/// Periods follow the API: "CY2023" for a year, "CY2023Q4" for a quarter
+/// and "CY2023Q4I" for an instant, e.g. a balance sheet date. Anything
+/// else is `InvalidInput`, without a request.
+pub fn frames(sec_client: &SecClient, taxonomy: &str, tag: &str, unit: &str, period: &str)
+              -> Result<FrameData, SecFilingsError> {
+    check_concept(taxonomy, tag)?;
+    check_frame(unit, period)?;
+
+    let url = format!("{}{}/{}/{}/{}.json", FRAMES_URL, taxonomy, tag, unit, period);
+    return sec_client.get_with_reader(&url, |reader| Ok(serde_json::from_reader(reader)?));
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Revenues as Apple reports them: each 10-Q tags the quarter and the
+    /// year to date, the 10-K the year and prior years.
+    const FACTS: &str = r#"{"cik": 320193, "entityName": "Apple Inc.", "facts": {"us-gaap": {
+        "Revenues": {"label": "Revenues", "units": {"USD": [
+            {"start": "2023-10-01", "end": "2023-12-30", "val": 119575, "accn": "q1",
+             "fy": 2024, "fp": "Q1", "form": "10-Q", "filed": "2024-02-02"},
+            {"start": "2023-12-31", "end": "2024-03-30", "val": 90753, "accn": "q2",
+             "fy": 2024, "fp": "Q2", "form": "10-Q", "filed": "2024-05-03"},
+            {"start": "2023-10-01", "end": "2024-03-30", "val": 210328, "accn": "q2",
+             "fy": 2024, "fp": "Q2", "form": "10-Q", "filed": "2024-05-03"},
+            {"start": "2023-10-01", "end": "2024-09-28", "val": 391035, "accn": "k",
+             "fy": 2024, "fp": "FY", "form": "10-K", "filed": "2024-11-01"},
+            {"start": "2024-06-30", "end": "2024-09-28", "val": 94930, "accn": "k",
+             "fy": 2024, "fp": "FY", "form": "10-K", "filed": "2024-11-01"},
+            {"start": "2022-09-25", "end": "2023-09-30", "val": 383285, "accn": "k",
+             "fy": 2024, "fp": "FY", "form": "10-K", "filed": "2024-11-01"}
+        ]}},
+        "Assets": {"units": {"USD": [
+            {"end": "2023-12-30", "val": 353514, "accn": "q1", "fy": 2024, "fp": "Q1",
+             "form": "10-Q", "filed": "2024-02-02"}
+        ]}}
+    }}}"#;
+
+
+    fn facts() -> CompanyFacts {
+        return read_company_facts(&mut FACTS.as_bytes(), None).unwrap();
+    }
+
+
+    fn vals(series: Vec<&FactValue>) -> Vec<f64> {
+        return series.into_iter().map(|value| value.val).collect();
+    }
+
+
+
+    #[test]
+    fn quarterly_series_leaves_out_year_to_date() {
+        let facts = facts();
+        assert_eq!(vals(facts.series("Revenues", "USD", "10-Q", None)), [119575.0, 90753.0]);
+        assert_eq!(vals(facts.series("Revenues", "USD", "10-Q", Some("Q2"))), [90753.0]);
+        assert_eq!(vals(facts.series("Assets", "USD", "10-Q", None)), [353514.0]);
+    }
+
+
+    #[test]
+    fn annual_series_leaves_out_quarters() {
+        let facts = facts();
+        assert_eq!(vals(facts.series("Revenues", "USD", "10-K", Some("FY"))),
+                   [383285.0, 391035.0]);
+    }
+
+
+    #[test]
+    fn latest_values() {
+        let facts = facts();
+
+        let quarterly = facts.latest_quarterly_values("us-gaap");
+        assert_eq!(quarterly.len(), 2);
+        assert_eq!(quarterly["Revenues"], 90753.0);
+        assert_eq!(quarterly["Assets"], 353514.0);
+
+        let annual = facts.latest_annual_values("us-gaap");
+        assert_eq!(annual, HashMap::from([("Revenues".to_string(), 391035.0)]));
+
+        assert!(facts.latest_quarterly_values("ifrs-full").is_empty());
+    }
+
+
+    #[test]
+    fn latest_values_prefer_usd() {
+        let json = r#"{"cik": 1, "facts": {"dei": {"EntityCommonStockSharesOutstanding": {
+            "units": {"shares": [{"end": "2024-01-19", "val": 15441881000, "accn": "q1",
+                                  "form": "10-Q", "filed": "2024-02-02"}]}},
+            "Dual": {"units": {
+                "EUR": [{"end": "2024-01-19", "val": 1, "form": "10-Q", "filed": "2024-02-02"}],
+                "USD": [{"end": "2023-01-19", "val": 2, "form": "10-Q", "filed": "2023-02-02"}]
+            }}
+        }}}"#;
+        let facts = read_company_facts(&mut json.as_bytes(), None).unwrap();
+
+        let quarterly = facts.latest_quarterly_values("dei");
+        assert_eq!(quarterly["EntityCommonStockSharesOutstanding"], 15441881000.0);
+        assert_eq!(quarterly["Dual"], 2.0);
+    }
+
+
+    #[test]
+    fn company_facts_answers_from_fundamentals_cache() {
+        let client = crate::client::SecClientBuilder::new()
+                     .user_agent("Example Corp admin@example.com")
+                     .with_fundamentals_cache(2, std::time::Duration::from_secs(60))
+                     .build().unwrap();
+        client.fundamentals_cache().unwrap().lock().unwrap().insert(320193, facts());
+
+        // nothing is sent for a cached company
+        let all = company_facts(&client, 320193, None).unwrap();
+        assert_eq!(all, facts());
+
+        let assets = company_facts(&client, 320193, Some(&["us-gaap:Assets"])).unwrap();
+        assert_eq!(assets.facts["us-gaap"].keys().collect::<Vec<_>>(), ["Assets"]);
+        let none = company_facts(&client, 320193, Some(&["dei:Assets"])).unwrap();
+        assert!(none.facts.is_empty());
+        assert_eq!(client.requests_sent(), 0);
+    }
+
+
+    #[test]
+    fn fetch_concepts_parallel_checks_every_concept() {
+        let client = Arc::new(crate::client::SecClientBuilder::new()
+                              .user_agent("Example Corp admin@example.com")
+                              .build().unwrap());
+
+        assert!(fetch_concepts_parallel(&client, 320193, &[]).unwrap().is_empty());
+
+        let concepts = [("gaap".to_string(), "Assets".to_string()),
+                        ("us-gaap".to_string(), "Net Income".to_string())];
+        let result = fetch_concepts_parallel(&client, 320193, &concepts);
+        assert!(matches!(result, Err(SecFilingsError::InvalidInput(_))));
+        assert_eq!(client.requests_sent(), 0);
+    }
+
+
+    #[test]
+    fn concept_arguments_are_checked() {
+        assert!(check_concept("us-gaap", "AccountsPayableCurrent").is_ok());
+        assert!(check_concept("ifrs-full", "Revenue_2").is_ok());
+
+        for (taxonomy, tag) in [("gaap", "Assets"), ("US-GAAP", "Assets"), ("us-gaap", ""),
+                                ("us-gaap", "Assets/../x"), ("us-gaap", "Assets?x=1"),
+                                ("us-gaap", "Net Income"), ("us-gaap", "Acti\u{f6}n")] {
+            assert!(matches!(check_concept(taxonomy, tag), Err(SecFilingsError::InvalidInput(_))),
+                    "{} {}", taxonomy, tag);
+        }
+    }
+
+
+    #[test]
+    fn frames_arguments_are_checked_before_any_request() {
+        let client = crate::client::SecClientBuilder::new()
+                     .user_agent("Example Corp admin@example.com")
+                     .build().unwrap();
+
+        for period in ["CY2023", "CY2023Q1", "CY2023Q4I"] {
+            assert!(check_frame("USD-per-shares", period).is_ok(), "{}", period);
+        }
+        for (unit, period) in [("", "CY2023"), ("USD/shares", "CY2023"), ("USD", "CY2023I"),
+                               ("USD", "2023"), ("USD", "CY23"), ("USD", "CY2023Q5"),
+                               ("USD", "CY2023Q1II"), ("USD", "cy2023"), ("USD", "CY2023Q1.json")] {
+            assert!(matches!(check_frame(unit, period), Err(SecFilingsError::InvalidInput(_))),
+                    "{} {}", unit, period);
+        }
+
+        for (taxonomy, tag, unit, period) in [("gaap", "Assets", "USD", "CY2023Q1I"),
+                                              ("us-gaap", "Net Income", "USD", "CY2023"),
+                                              ("us-gaap", "Assets", "USD", "FY2023")] {
+            let result = frames(&client, taxonomy, tag, unit, period);
+            assert!(matches!(result, Err(SecFilingsError::InvalidInput(_))), "{:?}", result);
+        }
+        assert_eq!(client.requests_sent(), 0);
+    }
+
+
+    #[test]
+    fn duration_days() {
+        let facts = facts();
+        let revenues = facts.get("Revenues", "USD").unwrap();
+        assert_eq!(revenues[0].duration_days(), Some(90));
+        assert_eq!(revenues[3].duration_days(), Some(363));
+        assert_eq!(facts.get("Assets", "USD").unwrap()[0].duration_days(), None);
+    }
+
+
+    #[test]
+    fn streamed_filtering_matches_buffered_parsing() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/facts/companyfacts.json");
+        let json = std::fs::read_to_string(path).unwrap();
+        let whole: CompanyFacts = serde_json::from_str(&json).unwrap();
+
+        // the document parsed whole, then filtered by hand
+        let buffered = |wanted: &[&str]| {
+            let mut facts = whole.clone();
+            for (taxonomy, concepts) in facts.facts.iter_mut() {
+                concepts.retain(|name, _| wanted.iter().any(|w| {
+                    *w == name.as_str() || *w == format!("{}:{}", taxonomy, name)
+                }));
+            }
+            facts.facts.retain(|_, concepts| !concepts.is_empty());
+            facts
+        };
+
+        assert_eq!(read_company_facts(&mut json.as_bytes(), None).unwrap(), whole);
+        for wanted in [&["Assets"][..], &["Revenues"], &["ifrs-full:Revenues"],
+                       &["us-gaap:Revenues", "dei:EntityCommonStockSharesOutstanding"],
+                       &["EarningsPerShareDiluted", "dei:Assets"], &["Missing"], &[]] {
+            let streamed = read_company_facts(&mut json.as_bytes(), Some(wanted)).unwrap();
+            assert_eq!(streamed, buffered(wanted), "{:?}", wanted);
+        }
+
+        // unqualified names are kept in every taxonomy
+        let revenues = read_company_facts(&mut json.as_bytes(), Some(&["Revenues"])).unwrap();
+        assert_eq!(revenues.facts.len(), 2);
+    }
+}
@@ -0,0 +1,63 @@
+//! Canned responses standing in for EDGAR, to run the pipeline offline.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reqwest::Url;
+
+use crate::client::HttpFetch;
+use crate::error::SecFilingsError;
+
+
+/// `HttpFetch` serving files from disk by URL, e.g. responses saved from
+/// a real run, so `documents()` through `balance_sheet_first()` can run
+/// without network access.
+#[derive(Debug, Clone, Default)]
+pub struct FixtureClient {
+    responses: HashMap<String, PathBuf>,
+}
+
+
+impl FixtureClient {
+
+    pub fn new() -> Self {
+        return FixtureClient::default();
+    }
+
+
+    /// Serve the file at `path` for `url`. URLs with a query are matched
+    /// as written out by `get_with_params()`, e.g.
+    /// "https://www.sec.gov/cgi-bin/browse-edgar?action=getcompany&ticker=aapl&...".
+    pub fn with_response<P: AsRef<Path>>(mut self, url: &str, path: P) -> Self {
+        self.responses.insert(url.to_string(), path.as_ref().to_path_buf());
+        return self;
+    }
+
+
+    /// URLs with a response.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        return self.responses.keys().map(String::as_str);
+    }
+}
+
+
+impl HttpFetch for FixtureClient {
+
+    fn get(&self, url: &str) -> Result<String, SecFilingsError> {
+        let path = self.responses.get(url).ok_or_else(||
+                   SecFilingsError::NotFound(format!("fixture for {}", url)))?;
+
+        return fs::read_to_string(path).map_err(|e|
+               SecFilingsError::NotFound(format!("fixture {}: {}", path.display(), e)));
+    }
+
+
+    fn get_with_params(&self, url: &str, params: &[(&str, String)])
+                       -> Result<String, SecFilingsError> {
+        let url = Url::parse_with_params(url, params).map_err(|e|
+                  SecFilingsError::UnexpectedStructure(format!("{}: {}", url, e)))?;
+
+        return self.get(url.as_str());
+    }
+}
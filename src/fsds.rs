@@ -0,0 +1,500 @@
+//! The SEC's quarterly Financial Statement Data Sets: every filer's XBRL
+//! numbers for a quarter, as TSV files in one zip, e.g. "2024q1.zip"
+//! from https://www.sec.gov/dera/data/financial-statement-data-sets.
+//!
+//! Only sub.txt (filings), num.txt (values) and pre.txt (statement
+//! layout) are read. num.txt runs past a gigabyte, so rows are streamed
+//! and only those of the wanted companies kept.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use chrono::{Months, NaiveDate};
+use log::debug;
+use zip::ZipArchive;
+
+use crate::classify::StatementKind;
+use crate::error::SecFilingsError;
+use crate::parse::{row_id, StatementData};
+use crate::scale::format_cell;
+
+
+/// A filing in sub.txt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    /// Accession number, e.g. "0000320193-24-000006".
+    pub adsh: String,
+    pub cik: u64,
+    pub name: String,
+    /// Standard Industrial Classification code, e.g. "3571".
+    pub sic: Option<String>,
+    /// e.g. "10-Q".
+    pub form: String,
+    /// Balance sheet date.
+    pub period: Option<NaiveDate>,
+    /// Fiscal year and period ("Q1".."Q3", "FY").
+    pub fy: Option<i32>,
+    pub fp: String,
+    pub filed: Option<NaiveDate>,
+}
+
+
+/// A value in num.txt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericFact {
+    pub adsh: String,
+    /// Concept name, e.g. "Assets".
+    pub tag: String,
+    /// Taxonomy of the tag, e.g. "us-gaap/2023", or the accession number
+    /// for company specific tags.
+    pub version: String,
+    /// End of the period or the instant.
+    pub ddate: NaiveDate,
+    /// Length of the period in quarters, 0 for instants.
+    pub qtrs: u32,
+    /// e.g. "USD" or "shares".
+    pub uom: String,
+    /// Co-registrant the value is for, empty for the filer itself.
+    pub coreg: String,
+    /// Dimensions the value is broken down by, empty for the total.
+    pub segments: String,
+    /// `None` where the filing left it nil.
+    pub value: Option<f64>,
+}
+
+
+/// A statement line in pre.txt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Presentation {
+    pub adsh: String,
+    /// Number of the R-file report the line is in.
+    pub report: u32,
+    /// Position within the report.
+    pub line: u32,
+    /// "BS", "IS", "CF", "EQ", "CI", "CP" or "UN".
+    pub stmt: String,
+    /// Whether the report is a parenthetical.
+    pub inpth: bool,
+    pub tag: String,
+    pub version: String,
+    /// Label as the filing shows it.
+    pub plabel: String,
+    /// Whether the filing shows the value with its sign flipped.
+    pub negating: bool,
+}
+
+
+/// One quarter of the Financial Statement Data Sets, indexed by
+/// accession number.
+#[derive(Debug, Clone, Default)]
+pub struct FinancialStatementDataSet {
+    submissions: HashMap<String, Submission>,
+    by_cik: HashMap<u64, Vec<String>>,
+    facts: HashMap<String, Vec<NumericFact>>,
+    presentations: HashMap<String, Vec<Presentation>>,
+}
+
+
+
+/// Column positions of a TSV file, from its header line.
+struct Columns(HashMap<String, usize>);
+
+
+impl Columns {
+
+    fn new(header: &str) -> Self {
+        return Columns(header.split('\t').enumerate()
+                             .map(|(i, name)| (name.trim().to_string(), i)).collect());
+    }
+
+
+    /// Field `name` of `fields`, empty if the file has no such column.
+    fn get<'a>(&self, fields: &[&'a str], name: &str) -> &'a str {
+        return self.0.get(name).and_then(|&i| fields.get(i)).map_or("", |field| field.trim());
+    }
+}
+
+
+/// Call `row` with every row of a TSV file after its header line. Lines
+/// are decoded lossily, older quarters are not all UTF-8.
+fn for_each_row<F>(reader: &mut dyn Read, mut row: F) -> Result<(), SecFilingsError>
+where
+    F: FnMut(&Columns, &[&str]),
+{
+    let mut reader = BufReader::new(reader);
+    let mut line = vec![];
+
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(());
+    }
+    let columns = Columns::new(String::from_utf8_lossy(&line).trim_end());
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        let fields: Vec<&str> = text.trim_end_matches(['\r', '\n']).split('\t').collect();
+        row(&columns, &fields);
+    }
+}
+
+
+/// "YYYYMMDD" as a date.
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    return NaiveDate::parse_from_str(raw, "%Y%m%d").ok();
+}
+
+
+/// Hand the file called `name` in `path` to `read`, `path` being a data
+/// set zip or a folder it was extracted to.
+fn read_member<F>(path: &Path, name: &str, read: F) -> Result<(), SecFilingsError>
+where
+    F: FnOnce(&mut dyn Read) -> Result<(), SecFilingsError>,
+{
+    if path.is_dir() {
+        return read(&mut File::open(path.join(name))?);
+    }
+
+    let invalid = |e: zip::result::ZipError| SecFilingsError::UnexpectedStructure(
+                  format!("{} in {}: {}", name, path.display(), e));
+
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(invalid)?;
+    let mut member = archive.by_name(name).map_err(invalid)?;
+    return read(&mut member);
+}
+
+
+
+/// Days an instant may lie off the start of a period and still be its
+/// opening balance, fiscal quarters end on weekdays rather than dates.
+const PERIOD_START_SLACK: i64 = 7;
+
+
+/// Instant of `facts` that is the balance of a line labelled `label` in
+/// the period of `qtrs` quarters ending `ddate`: at its start if the
+/// label says "beginning", at its end otherwise.
+fn balance_of<'a>(facts: &[&'a NumericFact], ddate: NaiveDate, qtrs: u32, label: &str)
+                  -> Option<&'a NumericFact> {
+    if !label.to_lowercase().contains("beginning") {
+        return facts.iter().copied().find(|fact| fact.ddate == ddate);
+    }
+
+    let start = ddate.checked_sub_months(Months::new(qtrs * 3))?;
+    return facts.iter().copied()
+                .filter(|fact| (fact.ddate - start).num_days().abs() <= PERIOD_START_SLACK)
+                .min_by_key(|fact| (fact.ddate - start).num_days().abs());
+}
+
+
+
+impl FinancialStatementDataSet {
+
+    /// Load the data set at `path`, a quarter's zip or the folder it was
+    /// extracted to, keeping only the filings of `ciks` if given.
+    ///
+    /// Loading a whole quarter takes several gigabytes of memory, pass
+    /// the companies of interest where possible.
+    pub fn open(path: &Path, ciks: Option<&[u64]>) -> Result<Self, SecFilingsError> {
+        let mut data_set = FinancialStatementDataSet::default();
+        let ciks: Option<HashSet<u64>> = ciks.map(|ciks| ciks.iter().copied().collect());
+
+        read_member(path, "sub.txt", |reader| for_each_row(reader, |columns, fields| {
+            let cik = match columns.get(fields, "cik").parse() {
+                Ok(cik) => cik,
+                Err(_) => return,
+            };
+            if ciks.as_ref().is_some_and(|ciks| !ciks.contains(&cik)) {
+                return;
+            }
+
+            let sic = columns.get(fields, "sic");
+            let submission = Submission {
+                adsh: columns.get(fields, "adsh").to_string(),
+                cik,
+                name: columns.get(fields, "name").to_string(),
+                sic: (!sic.is_empty()).then(|| sic.to_string()),
+                form: columns.get(fields, "form").to_string(),
+                period: parse_date(columns.get(fields, "period")),
+                fy: columns.get(fields, "fy").parse().ok(),
+                fp: columns.get(fields, "fp").to_string(),
+                filed: parse_date(columns.get(fields, "filed")),
+            };
+
+            data_set.by_cik.entry(cik).or_default().push(submission.adsh.clone());
+            data_set.submissions.insert(submission.adsh.clone(), submission);
+        }))?;
+
+        let wanted: HashSet<String> = data_set.submissions.keys().cloned().collect();
+
+        read_member(path, "num.txt", |reader| for_each_row(reader, |columns, fields| {
+            let adsh = columns.get(fields, "adsh");
+            if !wanted.contains(adsh) {
+                return;
+            }
+            let ddate = match parse_date(columns.get(fields, "ddate")) {
+                Some(ddate) => ddate,
+                None => {
+                    debug!("skipping {} value without a date", adsh);
+                    return;
+                }
+            };
+
+            data_set.facts.entry(adsh.to_string()).or_default().push(NumericFact {
+                adsh: adsh.to_string(),
+                tag: columns.get(fields, "tag").to_string(),
+                version: columns.get(fields, "version").to_string(),
+                ddate,
+                qtrs: columns.get(fields, "qtrs").parse().unwrap_or(0),
+                uom: columns.get(fields, "uom").to_string(),
+                coreg: columns.get(fields, "coreg").to_string(),
+                segments: columns.get(fields, "segments").to_string(),
+                value: columns.get(fields, "value").parse().ok(),
+            });
+        }))?;
+
+        read_member(path, "pre.txt", |reader| for_each_row(reader, |columns, fields| {
+            let adsh = columns.get(fields, "adsh");
+            if !wanted.contains(adsh) {
+                return;
+            }
+
+            data_set.presentations.entry(adsh.to_string()).or_default().push(Presentation {
+                adsh: adsh.to_string(),
+                report: columns.get(fields, "report").parse().unwrap_or(0),
+                line: columns.get(fields, "line").parse().unwrap_or(0),
+                stmt: columns.get(fields, "stmt").to_string(),
+                inpth: columns.get(fields, "inpth") == "1",
+                tag: columns.get(fields, "tag").to_string(),
+                version: columns.get(fields, "version").to_string(),
+                plabel: columns.get(fields, "plabel").to_string(),
+                negating: columns.get(fields, "negating") == "1",
+            });
+        }))?;
+
+        return Ok(data_set);
+    }
+
+
+    /// The filing with accession number `adsh`.
+    pub fn submission(&self, adsh: &str) -> Option<&Submission> {
+        return self.submissions.get(adsh);
+    }
+
+
+    /// Filings of the company with `cik`, in file order.
+    pub fn submissions_of(&self, cik: u64) -> Vec<&Submission> {
+        return self.by_cik.get(&cik).into_iter().flatten()
+                   .filter_map(|adsh| self.submissions.get(adsh)).collect();
+    }
+
+
+    /// Values reported in the filing `adsh`.
+    pub fn facts(&self, adsh: &str) -> &[NumericFact] {
+        return self.facts.get(adsh).map_or(&[], Vec::as_slice);
+    }
+
+
+    /// Statement lines of the filing `adsh`.
+    pub fn presentation(&self, adsh: &str) -> &[Presentation] {
+        return self.presentations.get(adsh).map_or(&[], Vec::as_slice);
+    }
+
+
+    /// Statement of `kind` in the filing `adsh`, laid out as its R-file
+    /// report: one row per line holding a value, one column per period,
+    /// most recent first. Values are in units, of the filer itself rather
+    /// than co-registrants, and without dimensional breakdowns.
+    ///
+    /// Lines of tags reported as instants, such as the cash balances of a
+    /// cash flow statement, show the balance at the end of each period,
+    /// or at its start where the label says "beginning".
+    pub fn statement(&self, adsh: &str, kind: StatementKind)
+                     -> Result<StatementData, SecFilingsError> {
+
+        let (code, title) = match kind {
+            StatementKind::BalanceSheet => ("BS", "Balance Sheet"),
+            StatementKind::IncomeStatement => ("IS", "Income Statement"),
+            StatementKind::ComprehensiveIncome => ("CI", "Comprehensive Income"),
+            StatementKind::CashFlow => ("CF", "Cash Flow Statement"),
+            StatementKind::StockholdersEquity => ("EQ", "Stockholders' Equity"),
+        };
+        let not_found = || SecFilingsError::NotFound(format!("{} of {}", title, adsh));
+
+        // the first report of the kind, leaving out parentheticals
+        let lines: Vec<&Presentation> = self.presentation(adsh).iter()
+            .filter(|line| line.stmt == code && !line.inpth).collect();
+        let report = lines.iter().map(|line| line.report).min().ok_or_else(not_found)?;
+        let mut lines: Vec<&Presentation> = lines.into_iter()
+            .filter(|line| line.report == report).collect();
+        lines.sort_by_key(|line| line.line);
+
+        let facts: Vec<&NumericFact> = self.facts(adsh).iter()
+            .filter(|fact| fact.coreg.is_empty() && fact.segments.is_empty())
+            .filter(|fact| fact.value.is_some())
+            .filter(|fact| lines.iter().any(|line| line.tag == fact.tag
+                                                   && line.version == fact.version))
+            .collect();
+
+        // columns are instants on a balance sheet, periods elsewhere
+        let instants = kind == StatementKind::BalanceSheet;
+        let mut periods: Vec<(NaiveDate, u32)> = facts.iter()
+            .filter(|fact| (fact.qtrs == 0) == instants)
+            .map(|fact| (fact.ddate, fact.qtrs))
+            .collect::<HashSet<_>>().into_iter().collect();
+        periods.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        let mut statement = StatementData::default();
+
+        let dates = periods.iter().map(|(date, _)| date.format("%b. %d, %Y").to_string());
+        if kind == StatementKind::BalanceSheet {
+            statement.headers.push(std::iter::once(title.to_string()).chain(dates).collect());
+        } else {
+            let lengths = periods.iter().map(|(_, qtrs)| format!("{} Months Ended", qtrs * 3));
+            statement.headers.push(std::iter::once(title.to_string()).chain(lengths).collect());
+            statement.headers.push(std::iter::once(String::new()).chain(dates).collect());
+        }
+
+        let mut occurrences: HashMap<&str, usize> = HashMap::new();
+
+        for line in lines {
+            let line_facts: Vec<&NumericFact> = facts.iter().copied()
+                .filter(|fact| fact.tag == line.tag && fact.version == line.version).collect();
+            let instant_tag = line_facts.iter().all(|fact| fact.qtrs == 0);
+
+            let values: Vec<Option<&NumericFact>> = periods.iter().map(|&(ddate, qtrs)| {
+                if instant_tag && !instants {
+                    return balance_of(&line_facts, ddate, qtrs, &line.plabel);
+                }
+                line_facts.iter().copied().find(|fact| fact.ddate == ddate && fact.qtrs == qtrs)
+            }).collect();
+            if values.iter().all(Option::is_none) {
+                continue;
+            }
+
+            if statement.currency.is_none() {
+                statement.currency = values.iter().flatten()
+                    .map(|fact| fact.uom.as_str())
+                    .find(|uom| uom.len() == 3 && uom.chars().all(|c| c.is_ascii_uppercase()))
+                    .filter(|uom| *uom != "USD").map(str::to_string);
+            }
+
+            let mut row = vec![line.plabel.clone()];
+            row.extend(values.iter().map(|fact| match fact.and_then(|fact| fact.value) {
+                Some(value) if line.negating => format_cell(-value),
+                Some(value) => format_cell(value),
+                None => String::new(),
+            }));
+
+            let occurrence = occurrences.entry(line.plabel.as_str()).or_default();
+            statement.row_ids.push(row_id(&line.plabel, "", *occurrence));
+            *occurrence += 1;
+
+            statement.row_sections.push(None);
+            statement.data.push(row);
+        }
+
+        if statement.data.is_empty() {
+            return Err(not_found());
+        }
+
+        statement.filing_date = self.submission(adsh).and_then(|submission| submission.filed)
+                                    .map(|filed| filed.format("%Y-%m-%d").to_string());
+
+        return Ok(statement);
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+
+    const APPLE: &str = "0000320193-24-000006";
+    const MICROSOFT: &str = "0000789019-24-000008";
+
+
+    fn fixture() -> PathBuf {
+        return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fsds");
+    }
+
+
+    fn data_set() -> FinancialStatementDataSet {
+        return FinancialStatementDataSet::open(&fixture(), None).unwrap();
+    }
+
+
+
+    #[test]
+    fn open_keeps_only_the_given_companies() {
+        let apple = FinancialStatementDataSet::open(&fixture(), Some(&[320193, 1])).unwrap();
+        assert!(apple.submission(APPLE).is_some());
+        assert!(apple.submission(MICROSOFT).is_none());
+        assert!(apple.facts(MICROSOFT).is_empty());
+
+        let all = data_set();
+        assert_eq!(all.submissions_of(789019)[0].name, "MICROSOFT CORP");
+        assert_eq!(all.facts(APPLE).len(), 9);
+    }
+
+
+    #[test]
+    fn cash_flow_keeps_opening_and_closing_balances() {
+        let statement = data_set().statement(APPLE, StatementKind::CashFlow).unwrap();
+
+        assert_eq!(statement.headers[0][1..], ["3 Months Ended", "3 Months Ended"]);
+        assert_eq!(statement.headers[1][1..], ["Dec. 31, 2023", "Dec. 31, 2022"]);
+        assert_eq!(statement.data, [
+            ["Cash, cash equivalents, and restricted cash, beginning balances", "30737000000",
+             "24977000000"],
+            ["Net income", "33916000000", "29998000000"],
+            ["Cash, cash equivalents, and restricted cash, ending balances", "41705000000",
+             "40297000000"],
+        ]);
+    }
+
+
+    #[test]
+    fn balance_sheet_and_income_statement() {
+        let data_set = data_set();
+
+        let balance_sheet = data_set.statement(APPLE, StatementKind::BalanceSheet).unwrap();
+        assert_eq!(balance_sheet.headers, [["Balance Sheet", "Dec. 31, 2023", "Sep. 30, 2023"]]);
+        assert_eq!(balance_sheet.data, [["Total assets", "353514000000", "352583000000"]]);
+
+        // the segment value is left out
+        let income = data_set.statement(APPLE, StatementKind::IncomeStatement).unwrap();
+        assert_eq!(income.data, [["Net income", "33916000000", "29998000000"]]);
+
+        assert!(matches!(data_set.statement(APPLE, StatementKind::StockholdersEquity),
+                         Err(SecFilingsError::NotFound(_))));
+    }
+
+
+    #[test]
+    fn opening_balance_allows_for_fiscal_calendars() {
+        let fact = |ddate: &str| NumericFact {
+            adsh: APPLE.to_string(), tag: "Cash".to_string(), version: String::new(),
+            ddate: NaiveDate::parse_from_str(ddate, "%Y-%m-%d").unwrap(), qtrs: 0,
+            uom: "USD".to_string(), coreg: String::new(), segments: String::new(),
+            value: Some(1.0),
+        };
+        let facts = [fact("2023-09-24"), fact("2023-12-30"), fact("2023-06-24")];
+        let facts: Vec<&NumericFact> = facts.iter().collect();
+        let end = NaiveDate::from_ymd_opt(2023, 12, 30).unwrap();
+
+        let opening = balance_of(&facts, end, 1, "Cash, Beginning of period").unwrap();
+        assert_eq!(opening.ddate.to_string(), "2023-09-24");
+        let closing = balance_of(&facts, end, 1, "Cash, end of period").unwrap();
+        assert_eq!(closing.ddate, end);
+        assert!(balance_of(&facts, end, 4, "Cash, beginning of year").is_none());
+    }
+}
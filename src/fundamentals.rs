@@ -0,0 +1,160 @@
+//! In-memory cache of `CompanyFacts`, see
+//! `SecClientBuilder::with_fundamentals_cache`.
+
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+
+use crate::facts::CompanyFacts;
+
+
+/// `CompanyFacts` by CIK, the least recently used dropped beyond
+/// `capacity`, and each kept for `ttl` after it was inserted.
+///
+/// A company's facts run to megabytes and take seconds to download, and
+/// only change when the company files, so a TTL of hours is sensible.
+#[derive(Debug)]
+pub struct FundamentalsCache {
+    entries: LruCache<u64, (Instant, CompanyFacts)>,
+    ttl: Duration,
+}
+
+
+impl FundamentalsCache {
+
+    /// Cache of up to `capacity` companies, at least one.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        return Self {
+            entries: LruCache::new(capacity),
+            ttl,
+        };
+    }
+
+
+    /// Facts of the company with `cik`, `None` if missing or expired.
+    /// Marks them as recently used.
+    pub fn get(&mut self, cik: u64) -> Option<&CompanyFacts> {
+        return self.get_at(cik, Instant::now());
+    }
+
+
+    /// Keep `facts` for `cik`, replacing what was kept and dropping the
+    /// least recently used company if the cache is full.
+    pub fn insert(&mut self, cik: u64, facts: CompanyFacts) {
+        self.entries.put(cik, (Instant::now(), facts));
+    }
+
+
+    /// Drop every expired entry, which `get()` only does for the one
+    /// asked for.
+    pub fn evict_expired(&mut self) {
+        self.evict_expired_at(Instant::now());
+    }
+
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+
+    fn is_expired(&self, inserted: Instant, now: Instant) -> bool {
+        return now.saturating_duration_since(inserted) >= self.ttl;
+    }
+
+
+    fn get_at(&mut self, cik: u64, now: Instant) -> Option<&CompanyFacts> {
+        let inserted = self.entries.peek(&cik)?.0;
+        if self.is_expired(inserted, now) {
+            self.entries.pop(&cik);
+            return None;
+        }
+        return self.entries.get(&cik).map(|(_, facts)| facts);
+    }
+
+
+    fn evict_expired_at(&mut self, now: Instant) {
+        let expired: Vec<u64> = self.entries.iter()
+                                    .filter(|(_, (inserted, _))| self.is_expired(*inserted, now))
+                                    .map(|(cik, _)| *cik)
+                                    .collect();
+        for cik in expired {
+            self.entries.pop(&cik);
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+
+    fn facts(cik: u64) -> CompanyFacts {
+        return CompanyFacts { cik, entity_name: String::new(), facts: HashMap::new() };
+    }
+
+
+    const TTL: Duration = Duration::from_secs(60);
+
+
+
+    #[test]
+    fn least_recently_used_is_dropped() {
+        let mut cache = FundamentalsCache::new(2, TTL);
+        cache.insert(1, facts(1));
+        cache.insert(2, facts(2));
+
+        // 1 used after 2, so 2 goes
+        assert_eq!(cache.get(1).map(|f| f.cik), Some(1));
+        cache.insert(3, facts(3));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(1).is_some() && cache.get(3).is_some());
+    }
+
+
+    #[test]
+    fn expired_entries_are_missing() {
+        let mut cache = FundamentalsCache::new(4, TTL);
+        let start = Instant::now();
+        cache.insert(1, facts(1));
+
+        assert!(cache.get_at(1, start + TTL / 2).is_some());
+        assert!(cache.get_at(1, start + TTL * 2).is_none());
+        assert!(cache.is_empty());
+    }
+
+
+    #[test]
+    fn evict_expired_drops_only_expired() {
+        let mut cache = FundamentalsCache::new(4, TTL);
+        cache.insert(1, facts(1));
+        cache.insert(2, facts(2));
+        let later = Instant::now() + TTL * 2;
+        cache.entries.put(3, (later, facts(3)));
+
+        cache.evict_expired_at(later);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get_at(3, later).is_some());
+    }
+
+
+    #[test]
+    fn zero_capacity_keeps_one() {
+        let mut cache = FundamentalsCache::new(0, TTL);
+        cache.insert(1, facts(1));
+        cache.insert(2, facts(2));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(2).is_some());
+    }
+}
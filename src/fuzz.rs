@@ -0,0 +1,217 @@
+//! Generated adversarial input for the parsers of filer text, plus the
+//! regression corpus in tests/fixtures/corpus.
+
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use serde_json::Value;
+
+use crate::parse::{parse_number, CellValue, CollapsePolicy};
+use crate::validation::parse_column_date;
+
+
+const NUMBERS_CORPUS: &str = include_str!("../tests/fixtures/corpus/numbers.jsonl");
+const HEADINGS_CORPUS: &str = include_str!("../tests/fixtures/corpus/headings.jsonl");
+
+const UNICODE_SPACES: [&str; 5] = ["\u{a0}", "\u{2009}", "\u{202f}", "\u{3000}", "\t"];
+const CURRENCY_SIGNS: [&str; 4] = ["$", "\u{20ac}", "\u{a3}", "\u{a5}"];
+
+/// Pieces filer text is made of, and some it should not be.
+const FRAGMENTS: [&str; 24] = ["0", "1", "7", "9", "123", ",", ".", "-", "\u{2212}", "\u{2014}",
+                               "(", ")", "$", "\u{20ac}", "%", " ", "\u{a0}", "\u{3000}", "[1]",
+                               "[", "]", "Sep", "\u{e9}", "\u{65e5}"];
+
+/// Longest input generated, a few times any cap the parsers apply.
+const HUGE_LEN: usize = 1 << 20;
+
+
+
+/// Text built from `FRAGMENTS`, now and then blown up to megabytes.
+#[derive(Debug, Clone)]
+struct Adversarial(String);
+
+
+impl Arbitrary for Adversarial {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let len = usize::arbitrary(g) % (g.size() + 1);
+        let mut text: String = (0..len).map(|_| *g.choose(&FRAGMENTS).unwrap()).collect();
+
+        if u8::arbitrary(g) % 16 == 0 && !text.is_empty() {
+            text = text.repeat(HUGE_LEN / text.len() + 1);
+        }
+        return Adversarial(text);
+    }
+}
+
+
+/// An amount as a filer would write it: grouped digits, maybe a currency
+/// sign and parentheses for negatives.
+#[derive(Debug, Clone)]
+struct Amount {
+    value: i64,
+    currency: bool,
+}
+
+
+impl Arbitrary for Amount {
+    fn arbitrary(g: &mut Gen) -> Self {
+        return Amount { value: i64::arbitrary(g) % 1_000_000_000_000,
+                        currency: bool::arbitrary(g) };
+    }
+}
+
+
+impl Amount {
+
+    fn digits(&self) -> String {
+        let digits = self.value.unsigned_abs().to_string();
+        let groups: Vec<&str> = digits.as_bytes().rchunks(3).rev()
+                                      .map(|chunk| std::str::from_utf8(chunk).unwrap())
+                                      .collect();
+        return groups.join(",");
+    }
+
+
+    fn cell(&self) -> String {
+        let sign = if self.currency { "$ " } else { "" };
+        if self.value < 0 {
+            return format!("{}({})", sign, self.digits());
+        }
+        return format!("{}{}", sign, self.digits());
+    }
+}
+
+
+/// Loose bound on parsing `len` bytes, slow debug builds included.
+fn time_bound(len: usize) -> Duration {
+    return Duration::from_millis(50) + Duration::from_nanos(500) * len as u32;
+}
+
+
+fn quickcheck() -> QuickCheck {
+    return QuickCheck::new().tests(500).rng(Gen::new(80));
+}
+
+
+fn corpus(lines: &str) -> Vec<(String, Value)> {
+    return lines.lines().filter(|line| !line.trim().is_empty()).map(|line| {
+        let entry: Value = serde_json::from_str(line).unwrap();
+        (entry["input"].as_str().unwrap().to_string(), entry["expected"].clone())
+    }).collect();
+}
+
+
+
+#[test]
+fn parse_number_is_total_and_bounded() {
+    fn prop(input: Adversarial) -> bool {
+        let start = Instant::now();
+        let number = parse_number(&input.0);
+        let cell = CellValue::parse(&input.0);
+
+        return number.is_none_or(f64::is_finite)
+               && (input.0.len() <= 64 || number.is_none())
+               && cell.to_option(CollapsePolicy::default()).is_none_or(f64::is_finite)
+               && start.elapsed() < time_bound(input.0.len());
+    }
+    quickcheck().quickcheck(prop as fn(Adversarial) -> bool);
+}
+
+
+#[test]
+fn parse_column_date_is_total_and_bounded() {
+    fn prop(input: Adversarial) -> bool {
+        let start = Instant::now();
+        let date = parse_column_date(&input.0);
+
+        return (input.0.len() <= 40 || date.is_none())
+               && start.elapsed() < time_bound(input.0.len());
+    }
+    quickcheck().quickcheck(prop as fn(Adversarial) -> bool);
+}
+
+
+#[test]
+fn amounts_round_trip() {
+    fn prop(amount: Amount) -> bool {
+        return parse_number(&amount.cell()) == Some(amount.value as f64);
+    }
+    quickcheck().quickcheck(prop as fn(Amount) -> bool);
+}
+
+
+#[test]
+fn unbalanced_parens_are_not_numbers() {
+    fn prop(amount: Amount, open: bool) -> bool {
+        let digits = amount.digits();
+        let cell = if open { format!("({}", digits) } else { format!("{})", digits) };
+        return parse_number(&cell).is_none()
+               && parse_number(&format!("(({}))", digits)).is_none();
+    }
+    quickcheck().quickcheck(prop as fn(Amount, bool) -> bool);
+}
+
+
+#[test]
+fn one_currency_sign_per_number() {
+    fn prop(amount: Amount, first: usize, second: usize) -> bool {
+        let first = CURRENCY_SIGNS[first % CURRENCY_SIGNS.len()];
+        let second = CURRENCY_SIGNS[second % CURRENCY_SIGNS.len()];
+        let digits = amount.digits();
+
+        return parse_number(&format!("{} {}", first, digits)) == parse_number(&digits)
+               && parse_number(&format!("{}{} {}", first, second, digits)).is_none()
+               && parse_number(&format!("{} {} {}", first, digits, second)).is_none();
+    }
+    quickcheck().quickcheck(prop as fn(Amount, usize, usize) -> bool);
+}
+
+
+#[test]
+fn unicode_spaces_are_ignored() {
+    fn prop(amount: Amount, spaces: Vec<(usize, usize)>) -> bool {
+        let mut cell: Vec<String> = amount.cell().chars().map(String::from).collect();
+        for (at, space) in spaces.into_iter().take(8) {
+            cell.insert(at % (cell.len() + 1), UNICODE_SPACES[space % UNICODE_SPACES.len()]
+                                                   .to_string());
+        }
+        let cell = cell.concat();
+
+        return cell.len() > 64 || parse_number(&cell) == Some(amount.value as f64);
+    }
+    quickcheck().quickcheck(prop as fn(Amount, Vec<(usize, usize)>) -> bool);
+}
+
+
+#[test]
+fn huge_inputs_are_rejected_quickly() {
+    for len in [1 << 10, 1 << 16, 1 << 22] {
+        for fill in ["9", "(", "$", "\u{3000}", "[1]", "Sep. 30, 2024"] {
+            let input = fill.repeat(len / fill.len());
+
+            let start = Instant::now();
+            assert_eq!(parse_number(&input), None);
+            assert_eq!(parse_column_date(&input), None);
+            assert!(start.elapsed() < time_bound(len), "{} x {:?}", len, fill);
+        }
+    }
+}
+
+
+#[test]
+fn numbers_corpus() {
+    for (input, expected) in corpus(NUMBERS_CORPUS) {
+        assert_eq!(parse_number(&input), expected.as_f64(), "{:?}", input);
+    }
+}
+
+
+#[test]
+fn headings_corpus() {
+    for (input, expected) in corpus(HEADINGS_CORPUS) {
+        let expected = expected.as_str().map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                                                        .unwrap());
+        assert_eq!(parse_column_date(&input), expected, "{:?}", input);
+    }
+}
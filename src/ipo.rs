@@ -0,0 +1,139 @@
+//! S-1 registration statements of companies going public.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use scraper::{Html, Selector};
+
+use crate::classify::StatementKind;
+use crate::client::SecClient;
+use crate::disclosure::document_text;
+use crate::edgar::{filing_summaries, master_reports};
+use crate::error::SecFilingsError;
+use crate::parse::{parse_html_statement_data, StatementData};
+use crate::submissions::submissions;
+
+
+/// What an S-1 tells about the company and its offering.
+pub struct IpoData {
+    /// Visible text of the prospectus, whitespace collapsed.
+    pub prospectus_text: String,
+    /// Statements found in the filing's XBRL reports, one per kind. Empty
+    /// for S-1s filed without XBRL.
+    pub financial_statements: Vec<(StatementKind, StatementData)>,
+    pub use_of_proceeds: Option<String>,
+    /// Number of risk factor headings, see `count_risk_factors`.
+    pub risk_factors_count: usize,
+    /// Firm signing the audit report, e.g. "Ernst & Young LLP".
+    pub auditor: Option<String>,
+}
+
+
+// headings are matched in upper case, as prospectuses set them, so
+// mentions like "see Risk Factors" in the text are not taken for one
+const SUMMARY_HEADING: &str = "PROSPECTUS SUMMARY";
+const RISK_FACTORS_HEADING: &str = "RISK FACTORS";
+const USE_OF_PROCEEDS_HEADING: &str = "USE OF PROCEEDS";
+
+const RISK_FACTORS_END: [&str; 4] = ["SPECIAL NOTE REGARDING FORWARD", "CAUTIONARY NOTE REGARDING",
+                                     "MARKET AND INDUSTRY DATA", USE_OF_PROCEEDS_HEADING];
+const USE_OF_PROCEEDS_END: [&str; 3] = ["DIVIDEND POLICY", "CAPITALIZATION", "DILUTION"];
+
+
+
+/// Text under `heading` up to the next of `ends`. The table of contents
+/// lists the headings too, so the longest such stretch is taken.
+fn section<'a>(text: &'a str, heading: &str, ends: &[&str]) -> Option<&'a str> {
+    return text.match_indices(heading).map(|(start, _)| {
+        let body = start + heading.len();
+        let end = ends.iter().filter_map(|e| text[body..].find(e)).min()
+                      .map_or(text.len(), |offset| body + offset);
+        text[body..end].trim()
+    }).max_by_key(|section| section.len()).filter(|section| !section.is_empty());
+}
+
+
+
+/// The "Prospectus Summary" section of an S-1.
+pub fn extract_s1_summary(html: &str) -> Option<String> {
+    let text = document_text(html);
+    return section(&text, SUMMARY_HEADING, &[RISK_FACTORS_HEADING]).map(str::to_string);
+}
+
+
+
+/// Number of risk factors in an S-1, counted as the bold or italic
+/// sentences heading each one within the "Risk Factors" section.
+pub fn count_risk_factors(html: &str) -> usize {
+    let text = document_text(html);
+    let risk_factors = match section(&text, RISK_FACTORS_HEADING, &RISK_FACTORS_END) {
+        Some(section) => section,
+        None => return 0,
+    };
+
+    let document = Html::parse_document(html);
+    let emphasis = Selector::parse("b, strong, i, em, [style*=bold], [style*=italic], \
+                                    [style*='font-weight:700'], [style*='font-weight: 700']")
+                           .expect("valid selector");
+
+    let headings: HashSet<String> = document.select(&emphasis)
+        .map(|el| el.text().collect::<Vec<_>>().join(" ").split_whitespace()
+                    .collect::<Vec<_>>().join(" ").replace('\u{2019}', "'"))
+        .filter(|heading| heading.len() > 30 && heading.ends_with('.'))
+        .filter(|heading| risk_factors.contains(heading.as_str()))
+        .collect();
+
+    return headings.len();
+}
+
+
+
+/// Audit firm signing the "Report of Independent Registered Public
+/// Accounting Firm".
+fn auditor(text: &str) -> Option<String> {
+    static SIGNATURE: OnceLock<Regex> = OnceLock::new();
+    let signature = SIGNATURE.get_or_init(|| Regex::new(r"/s/\s*([A-Z][\w&.,' -]*?\bLLP)\b")
+                                          .expect("valid regex"));
+
+    let report = text.to_lowercase().find("independent registered public accounting firm")?;
+    return signature.captures(&text[report..]).map(|m| m[1].trim().to_string());
+}
+
+
+
+/// Financial data of the latest S-1 (or S-1/A) filed by `cik`.
+pub fn ipo_financial_data(sec_client: &SecClient, cik: u64) -> Result<IpoData, SecFilingsError> {
+    let filing = submissions(sec_client, cik)?.filings.into_iter()
+                 .find(|filing| filing.form == "S-1" || filing.form == "S-1/A")
+                 .ok_or_else(|| SecFilingsError::NotFound(format!("S-1 of CIK {}", cik)))?;
+
+    let html = sec_client.get(&filing.primary_document_url())?;
+    let prospectus_text = document_text(&html);
+
+    // shortnames in S-1s are less standard, take the first report of
+    // each kind the classifier recognizes
+    let summaries = filing_summaries(sec_client, &[filing.index_url()])?;
+    let mut financial_statements: Vec<(StatementKind, StatementData)> = vec![];
+
+    for report in master_reports(sec_client, &summaries)? {
+        let kind = match report.statement_kind {
+            Some(kind) if !financial_statements.iter().any(|(k, _)| *k == kind) => kind,
+            _ => continue,
+        };
+
+        let statement = parse_html_statement_data(&sec_client.get(&report.url)?)?;
+        financial_statements.push((kind, statement));
+    }
+
+    let use_of_proceeds = section(&prospectus_text, USE_OF_PROCEEDS_HEADING, &USE_OF_PROCEEDS_END)
+                          .map(str::to_string);
+
+    return Ok(IpoData {
+        use_of_proceeds,
+        risk_factors_count: count_risk_factors(&html),
+        auditor: auditor(&prospectus_text),
+        prospectus_text,
+        financial_statements,
+    });
+}
@@ -0,0 +1,145 @@
+//! Human readable labels of US GAAP taxonomy concepts, read from the
+//! taxonomy's XBRL label linkbase.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_xml_rs::from_str;
+
+use crate::client::SecClient;
+use crate::error::SecFilingsError;
+
+
+/// Taxonomy release whose labels `concept_label()` uses.
+pub const US_GAAP_TAXONOMY_YEAR: u16 = 2024;
+
+
+/// URL of the US GAAP label linkbase of a taxonomy release.
+pub fn label_linkbase_url(year: u16) -> String {
+    return format!("https://xbrl.fasb.org/us-gaap/{}/elts/us-gaap-lab-{}.xml", year, year);
+}
+
+
+/// Role of a concept's standard label, as opposed to terse, verbose,
+/// total or documentation labels.
+const STANDARD_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/label";
+
+
+
+/// Root `<link:linkbase>` element.
+#[derive(Debug, Deserialize)]
+struct Linkbase {
+    #[serde(rename = "labelLink", default)]
+    label_links: Vec<LabelLink>,
+}
+
+/// `<link:labelLink>`, whose locators, labels and arcs come interleaved.
+#[derive(Debug, Deserialize)]
+struct LabelLink {
+    #[serde(rename = "$value", default)]
+    items: Vec<LinkItem>,
+}
+
+#[derive(Debug, Deserialize)]
+enum LinkItem {
+    /// Points at a concept, e.g. "us-gaap-2024.xsd#us-gaap_Assets".
+    #[serde(rename = "loc")]
+    Loc {
+        href: String,
+        label: String,
+    },
+    #[serde(rename = "label")]
+    Label {
+        label: String,
+        role: Option<String>,
+        lang: Option<String>,
+        #[serde(rename = "$value", default)]
+        text: String,
+    },
+    /// Connects a locator to its labels.
+    #[serde(rename = "labelArc")]
+    Arc {
+        from: String,
+        to: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+
+
+/// Concept name from a locator href, "us-gaap-2024.xsd#us-gaap_Assets"
+/// gives "us-gaap:Assets".
+fn concept_name(href: &str) -> Option<String> {
+    let fragment = href.rsplit_once('#')?.1;
+    let (prefix, name) = fragment.split_once('_')?;
+    return Some(format!("{}:{}", prefix, name));
+}
+
+
+
+/// Map of concept name ("us-gaap:Assets") to its standard English label,
+/// from a label linkbase.
+pub fn parse_label_linkbase(xml: &str) -> Result<HashMap<String, String>, SecFilingsError> {
+    let linkbase: Linkbase = from_str(xml)?;
+    let mut labels = HashMap::new();
+
+    for link in linkbase.label_links {
+        // locator label -> concept, and label resource label -> text
+        let mut concepts: HashMap<&str, String> = HashMap::new();
+        let mut texts: HashMap<&str, &str> = HashMap::new();
+        let mut arcs = vec![];
+
+        for item in &link.items {
+            match item {
+                LinkItem::Loc { href, label } => {
+                    if let Some(concept) = concept_name(href) {
+                        concepts.insert(label, concept);
+                    }
+                }
+                LinkItem::Label { label, role, lang, text } => {
+                    let standard = role.as_deref().is_none_or(|r| r == STANDARD_LABEL_ROLE);
+                    let english = lang.as_deref().is_none_or(|l| l.starts_with("en"));
+                    if standard && english {
+                        texts.insert(label, text.trim());
+                    }
+                }
+                LinkItem::Arc { from, to } => arcs.push((from.as_str(), to.as_str())),
+                LinkItem::Other => {}
+            }
+        }
+
+        for (from, to) in arcs {
+            if let (Some(concept), Some(text)) = (concepts.get(from), texts.get(to)) {
+                labels.entry(concept.clone()).or_insert_with(|| text.to_string());
+            }
+        }
+    }
+
+    return Ok(labels);
+}
+
+
+
+/// Download and parse the label linkbase of a taxonomy release. Prefer
+/// `concept_label()`, which downloads it once per client.
+pub fn fetch_label_linkbase(sec_client: &SecClient, year: u16)
+                            -> Result<HashMap<String, String>, SecFilingsError> {
+    let response = sec_client.get(&label_linkbase_url(year))?;
+    return parse_label_linkbase(&response);
+}
+
+
+
+/// Standard label of a US GAAP concept, e.g. "Assets" or
+/// "us-gaap:NetIncomeLoss" gives "Net Income (Loss)".
+pub fn concept_label(sec_client: &SecClient, concept: &str) -> Result<String, SecFilingsError> {
+    let concept = if concept.contains(':') {
+        concept.to_string()
+    } else {
+        format!("us-gaap:{}", concept)
+    };
+
+    return sec_client.us_gaap_labels()?.get(&concept).cloned().ok_or_else(||
+           SecFilingsError::NotFound(format!("label of {}", concept)));
+}
@@ -0,0 +1,98 @@
+//! Fetch SEC EDGAR filings and parse their financial statements.
+//!
+//! The pipeline goes `documents` (filings of a ticker),
+//! `filing_summaries` (FilingSummary.xml URLs), `master_reports` (report
+//! names and URLs) and finally `balance_sheet_first`, which parses the
+//! statement table into `StatementData`. Each step takes any `HttpFetch`,
+//! a `SecClient` or a `FixtureClient` serving saved responses.
+//!
+//! The crate does not build for WebAssembly: `SecClient` sits on
+//! `reqwest::blocking` and paces requests with `std::thread::sleep`,
+//! neither of which wasm32-unknown-unknown has. `AsyncSecClient` (the
+//! `async` feature) would be the starting point, with its tokio timers
+//! swapped for browser ones.
+
+#![allow(clippy::needless_return)]
+
+pub mod accession;
+#[cfg(feature = "tokio")]
+pub mod async_client;
+#[cfg(feature = "serde")]
+pub mod bundle;
+pub mod cache;
+pub mod classify;
+pub mod client;
+pub mod compare;
+pub mod crossref;
+pub mod dates;
+pub mod disclosure;
+pub mod download;
+pub mod edgar;
+pub mod error;
+pub mod export;
+pub mod facts;
+pub mod fixture;
+#[cfg(test)]
+mod fuzz;
+#[cfg(feature = "fsds")]
+pub mod fsds;
+pub mod fundamentals;
+pub mod ipo;
+pub mod labels;
+pub mod minimize;
+pub mod parse;
+pub mod related_party;
+pub mod scale;
+pub mod search;
+pub mod submissions;
+pub mod summary;
+pub mod tickers;
+pub mod validation;
+
+pub use accession::{extract_accession_from_url, AccessionNumber};
+#[cfg(feature = "tokio")]
+pub use async_client::AsyncSecClient;
+#[cfg(feature = "serde")]
+pub use bundle::{company_bundle, company_bundle_by_cik, BundleOptions, CompanyBundle,
+                 BUNDLE_SCHEMA_VERSION};
+pub use cache::ResponseCache;
+pub use classify::{StatementClassifier, StatementKind};
+pub use client::{HttpFetch, RequestRecord, SecClient, SecClientBuilder, SecResponse,
+                 SharedLimiter};
+pub use crossref::{extract_cross_references, CrossReference, CrossReferenceType};
+pub use dates::{eastern_date, parse_acceptance_time, reconcile_filing_dates};
+pub use disclosure::{audit_flags, AuditFlags};
+pub use download::{download_filing, DownloadOptions};
+#[allow(deprecated)]
+pub use edgar::balance_sheets;
+pub use edgar::{balance_sheet_first, balance_sheets_all, cash_flow_statement,
+                cash_flow_statement_all, document_urls, documents, documents_all, documents_by_cik,
+                documents_paged, filing_index, filing_reports, filing_summaries, income_statement,
+                income_statement_all, master_reports, parse_documents_feed, selected_report,
+                DateRange, DirItem, Directory, DocumentsQuery, FilingIndex, FilingRef,
+                FilingType, OwnerFilter, ReportEntry, ReportFileType,
+                FILING_SUMMARY_PARSER_VERSION, MAX_DOCUMENTS_COUNT};
+pub use error::{SecError, SecFilingsError};
+pub use facts::{company_concept, company_facts, fetch_concepts_parallel, frames, CompanyFacts,
+                Concept, ConceptHistory, ConceptSeries, FactValue, FrameData, FrameEntry,
+                CONCEPT_TAXONOMIES};
+pub use fixture::FixtureClient;
+#[cfg(feature = "fsds")]
+pub use fsds::FinancialStatementDataSet;
+pub use fundamentals::FundamentalsCache;
+pub use ipo::{extract_s1_summary, ipo_financial_data, IpoData};
+pub use labels::{concept_label, parse_label_linkbase};
+pub use parse::{parse_html_statement_data, parse_html_statement_data_all_tables, row_id,
+                CellValue, CollapsePolicy, NumericRow, StatementData};
+pub use related_party::{detect_related_party_transactions, related_party_reports,
+                        RelatedPartyDisclosure};
+pub use scale::{detect_scale, detect_units, normalize_time_series_to_usd, normalize_to_usd,
+                Currency, Scale, Units};
+pub use search::{full_text_search, FullTextQuery, FullTextResult};
+pub use submissions::{all_filings, form_type_summary, form_type_summary_by_cik,
+                      parse_fiscal_year_end, submissions, summarize_form_types,
+                      CompanySubmissions, Filing, Submissions};
+pub use summary::{generate_financial_summary, FinancialSummary};
+pub use tickers::{cik_for_ticker, ticker_to_cik, tickers_for_cik, TickerMap};
+pub use validation::{reconcile_statement, validate_revenue_disaggregation,
+                     ReconciliationReport};
@@ -1,393 +1,559 @@
-use std::cell::Cell;
+#![allow(clippy::needless_return)]
+
 use std::error::Error;
-use std::time::{Duration, Instant};
-use std::thread::sleep;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use reqwest::blocking::Client;
-use reqwest::header::USER_AGENT;
+use chrono::NaiveDate;
+use serde_json::json;
 
-use serde::Deserialize;
-use serde_json::Value;
-use serde_xml_rs::from_str;
+use sec_filings::minimize;
+use sec_filings::client::USER_AGENT_ENV;
+use sec_filings::{balance_sheet_first, cash_flow_statement, documents, documents_paged,
+                  filing_reports, filing_summaries, form_type_summary, income_statement,
+                  master_reports, parse_html_statement_data, selected_report};
+use sec_filings::{CellValue, CollapsePolicy, DocumentsQuery, FilingType, ReportEntry, SecClient,
+                  StatementData, StatementKind};
 
-use scraper::{Html, Selector};
 
+/// Directory the commands cache documents and parsed reports in, if set.
+const CACHE_DIR_ENV: &str = "SEC_CACHE_DIR";
 
-/// Root element of the SEC Atom XML response.
-#[derive(Debug, Deserialize)]
-#[serde(rename = "feed")]
-struct Feed {
-    #[serde(rename = "entry", default)]
-    entries: Vec<Entry>,
-}
 
-/// Individual filing entry.
-#[derive(Debug, Deserialize)]
-struct Entry {
-    #[serde(rename = "link")]
-    link: Link,
+/// Client for the commands, `User-Agent` from `SEC_USER_AGENT`, caching
+/// in `SEC_CACHE_DIR` if it is set.
+fn client() -> Result<SecClient, Box<dyn Error>> {
+    let mut builder = SecClient::builder();
+    if let Ok(user_agent) = std::env::var(USER_AGENT_ENV) {
+        builder = builder.user_agent(&user_agent);
+    }
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+        builder = builder.cache_dir(Path::new(&dir));
+    }
+    return Ok(builder.build()?);
 }
 
-/// Represents link to a specific filing.
-#[derive(Debug, Deserialize)]
-struct Link {
-    #[serde(rename = "href")]
-    href: String,
-}
 
 
-pub struct SecClient {
-    client: Client,
-    header: String,
+/// `minimize-fixture <r-file> "<assertion>" [output]`, writes the reduced
+/// HTML to `output` or stdout.
+fn minimize_fixture(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: minimize-fixture <r-file> \"row '<label>' parses to <value>\" [output]";
+
+    let (input, expr) = match args {
+        [input, expr, ..] => (input, expr),
+        _ => return Err(usage.into()),
+    };
+
+    let html = std::fs::read_to_string(input)?;
+    let assertion = minimize::Assertion::parse(expr)?;
+    let minimized = minimize::minimize(&html, &assertion)?;
+
+    match args.get(2) {
+        Some(output) => std::fs::write(output, minimized)?,
+        None => println!("{}", minimized),
+    }
 
-    time_start: Cell<Instant>,
-    request_count: Cell<u8>,
-    request_threshold: u8
+    return Ok(());
 }
 
 
-impl SecClient {
 
-    pub fn new() -> Result<Self, reqwest::Error> {
-        // required for successful access
-        let header = String::from("my.email@address.com");
-        let client = Client::builder().build()?;
+const SELECTABLE: [StatementKind; 4] = [StatementKind::BalanceSheet,
+                                        StatementKind::IncomeStatement,
+                                        StatementKind::CashFlow,
+                                        StatementKind::StockholdersEquity];
 
-        // required to resepct threshold of 10 requests per second
-        // replace cell with mutex to make it multi-core later on
-        let time_start = Cell::new(Instant::now());
-        let request_count = Cell::new(0);
-        let request_threshold = 10;
 
-        let instance = Self {
-            client,
-            header,
-            time_start,
-            request_count,
-            request_threshold,
-        };
+/// Statement kinds whose accessor would pick `report`.
+fn selected_for(reports: &[ReportEntry], report: &ReportEntry) -> Vec<StatementKind> {
+    return SELECTABLE.iter().copied()
+                     .filter(|kind| selected_report(reports, *kind) == Some(report))
+                     .collect();
+}
 
-        return Ok(instance);
-    }
+
+/// Width of the terminal from `COLUMNS`, 100 if unset.
+fn terminal_width() -> usize {
+    return std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).unwrap_or(100);
+}
 
 
-    fn threshold_reset(&self) {
-            self.time_start.set(Instant::now());
-            self.request_count.set(0);
+/// Shorten `text` to `width` characters, marking the cut with "...".
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
     }
+    let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+    return format!("{}...", kept);
+}
 
 
-    fn threshold_status(&self) {
-        let time_now = Instant::now();
-        let time_elapsed = time_now.duration_since(self.time_start.get());
+/// Reports grouped by menu category, in order of first appearance, each
+/// group sorted by position.
+fn report_groups(reports: &[ReportEntry]) -> Vec<(&str, Vec<&ReportEntry>)> {
+    let mut groups: Vec<(&str, Vec<&ReportEntry>)> = vec![];
 
-        // if more than a second has past, reset 'request_count'
-        if time_elapsed >= Duration::from_secs(1) {
-            self.threshold_reset();
+    for report in reports {
+        let category = report.menu_category.as_deref().unwrap_or("Uncategorized");
+        match groups.iter_mut().find(|(name, _)| *name == category) {
+            Some((_, members)) => members.push(report),
+            None => groups.push((category, vec![report])),
         }
+    }
 
+    for (_, members) in &mut groups {
+        members.sort_by_key(|report| (report.position.unwrap_or(u32::MAX), report.sequence));
+    }
 
-        // if we have reached threshold
-        if self.request_count.get() >= self.request_threshold {
-            let sleep_needed = Duration::from_secs(1)
-                                        .saturating_sub(time_elapsed);
-            sleep(sleep_needed);
-            self.threshold_reset();
-        }
+    return groups;
+}
 
-        // increment request count
-        self.request_count.set(self.request_count.get() + 1);
-    }
 
+fn render_tree(reports: &[ReportEntry], width: usize) -> String {
+    let mut out = String::new();
 
+    for (category, members) in report_groups(reports) {
+        out.push_str(category);
+        out.push('\n');
 
-    // GET request from basic URL
-    pub fn get(&self, url: &str) -> Result<String, reqwest::Error> {
+        for (i, report) in members.iter().enumerate() {
+            let branch = if i + 1 == members.len() { "`--" } else { "|--" };
 
-        self.threshold_status();
+            let mut notes = vec![];
+            if let Some(kind) = report.statement_kind {
+                notes.push(format!("{:?}", kind));
+            }
+            for kind in selected_for(reports, report) {
+                notes.push(format!("selected as {:?}", kind));
+            }
 
-        return self.client.get(url).header(USER_AGENT,
-               self.header.as_str()).send()?.text();
+            let position = report.position.map(|p| p.to_string()).unwrap_or_default();
+            let suffix = if notes.is_empty() {
+                String::new()
+            } else {
+                format!("  [{}]", notes.join(", "))
+            };
+
+            let prefix = format!("{} {:>3} ", branch, position);
+            let room = width.saturating_sub(prefix.len() + suffix.len()).max(16);
+            out.push_str(&format!("{}{}{}\n", prefix, truncate(&report.shortname, room),
+                                  suffix));
+        }
     }
 
-    // GET request from URL with query parameters
-    pub fn get_with_params(&self, url: &str, params: &[(&str, &str)]) ->
-                           Result<String, reqwest::Error> {
+    return out;
+}
 
-        self.threshold_status();
 
-        return self.client.get(url).query(params).header(USER_AGENT,
-               self.header.as_str()).send()?.text();
-    }
+fn render_json(reports: &[ReportEntry]) -> serde_json::Value {
+    let groups: Vec<serde_json::Value> = report_groups(reports).into_iter()
+        .map(|(category, members)| json!({
+            "category": category,
+            "reports": members.iter().map(|report| json!({
+                "position": report.position,
+                "shortname": report.shortname,
+                "url": report.url,
+                "statement_kind": report.statement_kind.map(|k| format!("{:?}", k)),
+                "selected_as": selected_for(reports, report).iter()
+                               .map(|k| format!("{:?}", k)).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })).collect();
+
+    return json!(groups);
 }
 
 
+/// `reports <ticker> [--accession X] [--tree|--json]`, prints the reports
+/// of the latest 10-Q (or the given filing) and which statement each
+/// accessor would pick.
+fn reports_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: reports <ticker> [--accession <accession>] [--tree|--json]";
 
+    let ticker = args.first().filter(|a| !a.starts_with("--")).ok_or(usage)?;
+    let mut accession = None;
+    let mut as_json = false;
 
-/// Get document URLs for a given ticker and date
-pub fn documents(sec_client: &SecClient, ticker: &str, date: &str)
-                 -> Result<Vec<String>, Box<dyn Error>> {
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--accession" => accession = Some(rest.next().ok_or(usage)?.clone()),
+            "--json" => as_json = true,
+            "--tree" => as_json = false,
+            _ => return Err(usage.into()),
+        }
+    }
 
-    let params = [
-        ("action", "getcompany"),
-        ("ticker", ticker),
-        ("type", "10-Q"),
-        ("dateb", date),
-        ("owner", "exclude"),
-        ("start", ""),
-        ("output", "atom"),
-        ("count", "100"),
-    ];
+    let sec_client = client()?;
+
+    let doc = match &accession {
+        // any form type, the accession number picks the filing
+        Some(accession) => documents(&sec_client, ticker, &DocumentsQuery::new(),
+                                     FilingType::Other(String::new()))?
+                               .into_iter().find(|doc| doc.accession.as_str() == accession
+                                                 || doc.accession.without_dashes() == *accession)
+                               .ok_or(format!("no filing {} for {}", accession, ticker))?,
+        None => documents(&sec_client, ticker, &DocumentsQuery::new(), FilingType::TenQ)?
+                    .into_iter().next().ok_or(format!("no 10-Q filings for {}", ticker))?,
+    };
 
-    let base_url = "https://www.sec.gov/cgi-bin/browse-edgar";
-    let response = sec_client.get_with_params(base_url, &params)?;
+    let reports = filing_reports(&sec_client, &doc)?;
 
-    // deserialize the Atom feed XML into 'Feed' struct
-    let feed: Feed = from_str(&response)?;
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&render_json(&reports))?);
+    } else {
+        print!("{}", render_tree(&reports, terminal_width()));
+    }
 
-    let mut documents_list = Vec::new();
+    return Ok(());
+}
 
-    for entry in feed.entries {
 
-        let mut documents_url = entry.link.href
-            .replace("-index.html", "/index.json")
-            .replace("-index.htm", "/index.json")
-            .replace("-", "");
 
-        // adjust the URL if it has exactly 10 parts
-        let split_items: Vec<&str> = documents_url.split("/").collect();
-        if split_items.len() == 10 {
-            let mut items = split_items;
-            items.remove(7);
-            documents_url = items.join("/");
-        }
 
-        documents_list.push(documents_url);
-    }
-    Ok(documents_list)
+/// Current period value of a row, i.e. its first numeric cell.
+fn current_value(row: &[String]) -> Option<f64> {
+    return row.iter().skip(1).find_map(|cell| CellValue::parse(cell)
+                                           .to_option(CollapsePolicy::default()));
 }
 
 
+/// One line of the comparison: a label and its current period value in
+/// each filing, most recent first.
+struct ComparedRow {
+    label: String,
+    values: Vec<Option<f64>>,
+    // filings the row appears in at all
+    present: Vec<bool>,
+}
 
-fn filing_summaries(sec_client: &SecClient, documents_list: &Vec<String>)
-                     -> Result<Vec<String>, Box<dyn Error>> {
-
-    // store all summeries from documents_list in here
-    let mut summaries = vec![];
 
-    // iterate over each JSON index URL
-    for document in &documents_list[0..1] {
+/// Align the rows of `statements` (most recent first) by row id, in the
+/// order of the most recent filing, followed by rows it no longer has.
+fn align_rows(statements: &[StatementData]) -> Vec<ComparedRow> {
+    let mut rows: Vec<(String, ComparedRow)> = vec![];
+
+    for (i, statement) in statements.iter().enumerate() {
+        for (id, row) in statement.row_ids.iter().zip(&statement.data) {
+            let index = match rows.iter().position(|(known, _)| known == id) {
+                Some(index) => index,
+                None => {
+                    rows.push((id.clone(), ComparedRow {
+                        label: row.first().cloned().unwrap_or_default(),
+                        values: vec![None; statements.len()],
+                        present: vec![false; statements.len()],
+                    }));
+                    rows.len() - 1
+                }
+            };
+
+            let compared = &mut rows[index].1;
+            if !compared.present[i] {
+                compared.values[i] = current_value(row);
+                compared.present[i] = true;
+            }
+        }
+    }
 
-        // GET request
-        let response = sec_client.get(&document).unwrap();
+    return rows.into_iter().map(|(_, row)| row).collect();
+}
 
-        // parse the JSON content
-        let json_data: Value = serde_json::from_str(&response)?;
 
-        // In the JSON, find the directory and its items.
-        let directory = &json_data["directory"];
-        let dir_name = directory["name"].as_str().ok_or(
-                       "Directory name not found in JSON")?;
+fn format_value(value: Option<f64>) -> String {
+    return value.map(|v| format!("{}", v)).unwrap_or_else(|| "-".to_string());
+}
 
-        let items = directory["item"].as_array().ok_or(
-                    "No items found in directory")?;
 
-        // look for "FilingSummary.xml" in the directory items
-        let base_url = "https://www.sec.gov";
-        let mut xml_summary_url = None;
+/// Side-by-side table of `statements`, with the change from the previous
+/// to the most recent filing.
+fn render_comparison(periods: &[String], statements: &[StatementData], width: usize)
+                     -> String {
+    let column = 14;
+    let fixed = column * (periods.len() + 2) + 8;
+    let label_width = width.saturating_sub(fixed).max(20);
 
-        for item in items {
-            if item["name"].as_str() == Some("FilingSummary.xml") {
-                // construct the URL for the FilingSummary.xml
-                xml_summary_url = Some(format!("{}/{}/{}", base_url,
-                                    dir_name, "FilingSummary.xml"));
-                break;
+    let mut out = format!("{:<label_width$}", "");
+    for period in periods {
+        out.push_str(&format!(" {:>column$}", truncate(period, column)));
+    }
+    out.push_str(&format!(" {:>column$} {:>column$}\n", "change", "change %"));
+
+    for row in align_rows(statements) {
+        let (change, percent) = match (row.values.first(), row.values.get(1)) {
+            (Some(Some(current)), Some(Some(previous))) => {
+                let percent = if *previous != 0.0 {
+                    format!("{:.1}%", (current - previous) / previous.abs() * 100.0)
+                } else {
+                    "-".to_string()
+                };
+                (format!("{}", current - previous), percent)
             }
-        }
+            _ => ("-".to_string(), "-".to_string()),
+        };
 
-        match xml_summary_url {
-            // found it
-            Some(url) => {
-                summaries.push(url);
-            }
-            // if not found, simply jump to next doc
-            None => {
-                continue;
-            }
+        let marker = match (row.present.first(), row.present.get(1)) {
+            (Some(true), Some(false)) => " (new)",
+            (Some(false), _) => " (removed)",
+            _ => "",
         };
+
+        let label = truncate(&format!("{}{}", row.label, marker), label_width);
+        out.push_str(&format!("{:<label_width$}", label));
+        for value in &row.values {
+            out.push_str(&format!(" {:>column$}", format_value(*value)));
+        }
+        out.push_str(&format!(" {:>column$} {:>column$}\n", change, percent));
     }
 
-    Ok(summaries)
+    return out;
 }
 
 
+/// `compare <ticker> [--kind balance|income|cash] [--periods N]`, prints
+/// a statement of the last N 10-Q filings side by side.
+fn compare_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: compare <ticker> [--kind balance|income|cash] [--periods N]";
 
-/// Root element of the SEC XML response.
-#[derive(Debug, Deserialize)]
-struct FilingSummary {
-    #[serde(rename = "MyReports")]
-    filing: Reports,
-}
+    let ticker = args.first().filter(|a| !a.starts_with("--")).ok_or(usage)?;
+    let mut kind = "income".to_string();
+    let mut periods: usize = 2;
 
-/// Represents the `<MyReports>` section in the XML.
-#[derive(Debug, Deserialize)]
-#[serde(rename = "MyReports")]
-struct Reports {
-    #[serde(rename = "Report", default)]
-    reports: Vec<Report>,
-}
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--kind" => kind = rest.next().ok_or(usage)?.clone(),
+            "--periods" => periods = rest.next().ok_or(usage)?.parse()
+                                         .map_err(|_| usage)?,
+            _ => return Err(usage.into()),
+        }
+    }
+    if periods < 2 {
+        return Err("--periods must be at least 2".into());
+    }
 
-/// Individual report entries inside `<MyReports>`.
-#[derive(Debug, Deserialize)]
-struct Report {
-    #[serde(rename = "ShortName")]
-    shortname: Option<String>,
-    #[serde(rename = "HtmlFileName")]
-    htmlfilename: Option<String>,
-    #[serde(rename = "XmlFileName")]
-    xmlfilename: Option<String>,
-}
+    let extract = match kind.as_str() {
+        "balance" => balance_sheet_first,
+        "income" => income_statement,
+        "cash" => cash_flow_statement,
+        _ => return Err(usage.into()),
+    };
 
+    let sec_client = client()?;
+    let docs = documents(&sec_client, ticker, &DocumentsQuery::new(), FilingType::TenQ)?;
 
-fn master_reports(sec_client: &SecClient, xml_summaries: &[String])
-                  -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut labels = vec![];
+    let mut statements = vec![];
 
-    let mut all_reports = vec![];
+    for doc in docs.iter().take(periods) {
+        let reports = filing_reports(&sec_client, doc)?;
+        let statement = extract(&sec_client, &reports)?;
 
-    for xml_url in xml_summaries {
-        // GET request
-        let xml_content = sec_client.get(xml_url)?;
+        // the first date column; multi-row headers put the title and
+        // period lengths ("3 Months Ended") above the dates
+        let label = match statement.headers.as_slice() {
+            [only] => only.get(1).cloned(),
+            [.., last] => last.first().cloned(),
+            [] => None,
+        };
+        let label = label.unwrap_or_else(|| doc.accession.to_string());
+        labels.push(label);
+        statements.push(statement);
+    }
 
-        // get base URL
-        let base_url = xml_url.replace("FilingSummary.xml", "");
+    if statements.len() < 2 {
+        return Err(format!("{} has fewer than 2 10-Q filings", ticker).into());
+    }
 
-        // deserialize filing elements
-        let xml_summary: FilingSummary = from_str(&xml_content)?;
+    print!("{}", render_comparison(&labels, &statements, terminal_width()));
+    return Ok(());
+}
 
-        // extract reports from the XML summary
-        let mut reports = xml_summary.filing.reports;
 
-        // exclude the last report which should aways be the 'base_url'
-        if reports.len() > 1 {
-            reports.pop();
-        }
 
-        // process each report
-        for report in reports {
-            // prefer htmlfilename over xmlfilename
-            let file = report.htmlfilename.or(report.xmlfilename).unwrap_or_default();
+const EMIT_FORMATS: [&str; 3] = ["csv", "json", "raw"];
 
-            // grab url and its short description
-            let url = format!("{}{}", base_url, file);
-            let shortname = report.shortname.unwrap_or_default();
-            all_reports.push((shortname, url));
+
+/// Write the requested formats of one statement and its meta.json into
+/// `dir`.
+fn write_bundle(dir: &Path, formats: &[String], statement: &StatementData, html: &str,
+                meta: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+    for format in formats {
+        match format.as_str() {
+            "csv" => fs::write(dir.join("statement.csv"), statement.to_csv())?,
+            #[cfg(feature = "serde")]
+            "json" => fs::write(dir.join("statement.json"), statement.to_json())?,
+            "raw" => fs::write(dir.join("statement.html"), html)?,
+            _ => return Err(format!("cannot emit {}", format).into()),
         }
     }
-    Ok(all_reports)
+
+    fs::write(dir.join("meta.json"), serde_json::to_string_pretty(meta)?)?;
+    return Ok(());
 }
 
 
+/// Create `out/name` with the files `write` puts in the directory it is
+/// given: a staging directory renamed into place once `write` succeeds,
+/// and removed if it fails, so there is never a partial bundle.
+fn stage_bundle<F>(out: &Path, name: &str, write: F) -> Result<PathBuf, Box<dyn Error>>
+where
+    F: FnOnce(&Path) -> Result<(), Box<dyn Error>>,
+{
+    let target = out.join(name);
+    if target.exists() {
+        return Err(format!("{} already exists", target.display()).into());
+    }
+
+    let staging = out.join(format!(".{}.staging", name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    let written = write(&staging).and_then(|_| Ok(fs::rename(&staging, &target)?));
 
-/// Struct to hold the parsed table data.
-pub struct StatementData {
-    pub headers: Vec<Vec<String>>,
-    pub sections: Vec<String>,
-    pub data: Vec<Vec<String>>,
+    if let Err(e) = written {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    return Ok(target);
 }
 
 
+/// `extract <ticker> [--kind balance|income|cash] [--emit csv,json,raw]
+/// [--out <dir>]`, writes the statement of the latest 10-Q in every
+/// requested format to `<dir>/<TICKER>-<accession>-<kind>/`.
+///
+/// The files are written through `stage_bundle()`, so a failure leaves
+/// no partial output behind.
+fn extract_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: extract <ticker> [--kind balance|income|cash] [--emit csv,json,raw] \
+                 [--out <dir>]";
+
+    let ticker = args.first().filter(|a| !a.starts_with("--")).ok_or(usage)?;
+    let mut kind = "balance".to_string();
+    let mut formats = vec!["csv".to_string()];
+    let mut out = PathBuf::from(".");
+
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--kind" => kind = rest.next().ok_or(usage)?.clone(),
+            "--emit" => formats = rest.next().ok_or(usage)?.split(',')
+                                      .map(|f| f.trim().to_string()).collect(),
+            "--out" => out = PathBuf::from(rest.next().ok_or(usage)?),
+            _ => return Err(usage.into()),
+        }
+    }
 
-/// Parses HTML content of a SEC filing page, extract statement
-/// data.
-pub fn parse_html_statement_data(html: &str) -> StatementData {
+    if let Some(format) = formats.iter().find(|f| !EMIT_FORMATS.contains(&f.as_str())) {
+        return Err(format!("unknown format '{}', expected csv, json or raw", format).into());
+    }
+    if cfg!(not(feature = "serde")) && formats.iter().any(|f| f == "json") {
+        return Err("json output needs the serde feature".into());
+    }
 
-    let mut statement_data = StatementData {
-        headers: Vec::new(),
-        sections: Vec::new(),
-        data: Vec::new(),
+    let statement_kind = match kind.as_str() {
+        "balance" => StatementKind::BalanceSheet,
+        "income" => StatementKind::IncomeStatement,
+        "cash" => StatementKind::CashFlow,
+        _ => return Err(usage.into()),
     };
 
+    let sec_client = client()?;
+    let doc = documents(&sec_client, ticker, &DocumentsQuery::new(), FilingType::TenQ)?
+              .into_iter().next().ok_or(format!("no 10-Q filings for {}", ticker))?;
+
+    let reports = filing_reports(&sec_client, &doc)?;
+    let report = selected_report(&reports, statement_kind)
+                 .ok_or(format!("no {} statement in {}", kind, doc.accession))?;
+
+    let html = sec_client.get(&report.url)?;
+    let mut statement = parse_html_statement_data(&html)?;
+    statement.source_url = Some(report.url.clone());
+    statement.report_shortname = Some(report.shortname.clone());
+    statement.ticker = Some(ticker.to_uppercase());
+    statement.filing_date = doc.filed_on.map(|date| date.to_string());
+
+    let meta = json!({
+        "ticker": ticker.to_uppercase(),
+        "accession": doc.accession.as_str(),
+        "form_type": doc.form_type,
+        "filed_date": statement.filing_date,
+        "kind": kind,
+        "report": report.shortname,
+        "url": report.url,
+        "formats": formats,
+    });
+
+    let name = format!("{}-{}-{}", ticker.to_uppercase(), doc.accession, kind);
+    let target = stage_bundle(&out, &name, |dir| write_bundle(dir, &formats, &statement,
+                                                               &html, &meta))?;
+
+    println!("{}", target.display());
+    return Ok(());
+}
 
-    // parse html
-    let document = Html::parse_document(html);
-    let table_selector = Selector::parse("table").expect("Failed to parse 'table' tag");
-    let tr_selector = Selector::parse("tr").expect("Failed to parse 'tr' tag");
-    let th_selector = Selector::parse("th").expect("Failed to parse 'th' tag");
-    let td_selector = Selector::parse("td").expect("Failed to parse 'td' tag");
-    let strong_selector = Selector::parse("strong").expect("Failed to parse 'strong' tag");
-
-
-    // find the first table element
-    if let Some(table) = document.select(&table_selector).next() {
-        for tr in table.select(&tr_selector) {
-            let ths: Vec<_> = tr.select(&th_selector).collect();
-            let tds: Vec<_> = tr.select(&td_selector).collect();
-            let strongs: Vec<_> = tr.select(&strong_selector).collect();
-
-            // document header
-            if !ths.is_empty() {
-                let header_row = ths.iter().map(|col| col.text()
-                                 .collect::<Vec<_>>().join(" ").trim()
-                                 .to_string()).collect();
-                statement_data.headers.push(header_row);
-            }
 
-            // document section row (under header)
-            else if !tds.is_empty() && !strongs.is_empty() {
-                let section_row = tds[0].text().collect::<Vec<_>>().join(" ")
-                                  .trim().to_string();
-                statement_data.sections.push(section_row);
-            }
+/// `cache warm <ticker> [--last N]`, reads the reports of the company's
+/// latest N filings of any form into the cache in `SEC_CACHE_DIR`, so
+/// `reports` lists them without fetching them again.
+fn cache_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: cache warm <ticker> [--last <n>]";
 
-            // data rows (under section)
-            else if !tds.is_empty() && strongs.is_empty() {
-                let data_row: Vec<String> = tds.iter().map(|col| col.text()
-                                            .collect::<Vec<_>>().join(" ").trim()
-                                            .to_string()).collect();
-                statement_data.data.push(data_row);
-            }
+    let ticker = match args {
+        [warm, ticker, ..] if warm == "warm" && !ticker.starts_with("--") => ticker,
+        _ => return Err(usage.into()),
+    };
+    let mut last: usize = 4;
 
-            else {
-                println!("\nERROR: Unrecognized HTML structure in a <tr>.\n");
-            }
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--last" => last = rest.next().ok_or(usage)?.parse().map_err(|_| usage)?,
+            _ => return Err(usage.into()),
         }
     }
 
-    else {
-        println!("\nERROR: No <table> found in the HTML.\n");
+    if cfg!(not(feature = "serde")) {
+        return Err("caching reports needs the serde feature".into());
+    }
+    if std::env::var_os(CACHE_DIR_ENV).is_none() {
+        return Err(format!("{} is not set, it should hold the cache directory",
+                           CACHE_DIR_ENV).into());
     }
 
-    return statement_data;
-}
-
-
+    let sec_client = client()?;
+    let filings = documents_paged(&sec_client, ticker, &DocumentsQuery::new(),
+                                  FilingType::Other(String::new()), Some(last))?;
 
-pub fn balance_sheets(sec_client: &SecClient, xml_summaries:
-                      &Vec<(String, String)>) -> Result<StatementData, Box<dyn Error>> {
+    for filing in &filings {
+        let reports = filing_reports(&sec_client, filing)?;
+        println!("{}  {:<8} {} reports", filing.accession, filing.form_type, reports.len());
+    }
 
-    let keywords = ["balance sheets", "financial condition"];
+    return Ok(());
+}
 
 
-    // find the shortname == keywords, and parse its url
-    for (name, url) in xml_summaries.iter() {
-        if keywords.iter().any(|&kw| name.to_lowercase().contains(kw)) {
+/// `forms <ticker>`, prints every form type the company ever filed.
+fn forms_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let ticker = args.first().ok_or("usage: forms <ticker>")?;
 
-            println!("{}", url);
+    let sec_client = client()?;
 
-            // GET html
-            let html = sec_client.get(&url)?;
+    print!("{}", render_forms(&form_type_summary(&sec_client, ticker)?));
+    return Ok(());
+}
 
-            // parse html
-            let statement_data = parse_html_statement_data(&html);
 
-            return Ok(statement_data);
-        }
+/// Table of form types, their number of filings and latest filing date.
+fn render_forms(summary: &[(FilingType, u32, NaiveDate)]) -> String {
+    let mut out = format!("{:<12} {:>6}  latest\n", "form", "count");
+    for (form, count, latest) in summary {
+        out.push_str(&format!("{:<12} {:>6}  {}\n", form.as_str(), count, latest));
     }
-
-    return Err(Box::new(std::io::Error::new(
-        std::io::ErrorKind::Other,
-        "Could not find balance sheet url.",
-    )));
+    return out;
 }
 
 
@@ -395,18 +561,44 @@ pub fn balance_sheets(sec_client: &SecClient, xml_summaries:
 
 fn main() {
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let command = match args.first().map(String::as_str) {
+        Some("minimize-fixture") => minimize_fixture,
+        Some("extract") => extract_command,
+        Some("forms") => forms_command,
+        Some("compare") => compare_command,
+        Some("reports") => reports_command,
+        Some("cache") => cache_command,
+        _ => {
+            demo();
+            return;
+        }
+    };
+
+    if let Err(e) = command(&args[1..]) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+
+/// Without a command, the balance sheet of Apple's latest 10-Q.
+fn demo() {
     let sec_client = SecClient::new().expect("Failed to create client");
 
-    let docs = documents(&sec_client, "aapl", "").unwrap();
+    let docs = documents(&sec_client, "aapl", &DocumentsQuery::new(), FilingType::TenQ).unwrap();
 
     /*
     println!("\ndocuments:");
     for d in &docs {
-        println!("{}", d);
+        println!("{}: {}", d.accession, d.url);
     }
     */
 
-    let filings = filing_summaries(&sec_client, &docs).unwrap();
+    // only the most recent filing
+    let latest: Vec<String> = docs.iter().take(1).map(|doc| doc.url.clone()).collect();
+    let filings = filing_summaries(&sec_client, &latest).unwrap();
 
     /*
     println!("\nfiling summary:");
@@ -419,16 +611,188 @@ fn main() {
 
     println!("\nmaster reports:");
     for r in &reports {
-        let (desc, url) = r;
-        println!("{}:", desc);
-        println!("{}\n", url);
+        println!("{}:", r.shortname);
+        println!("{}\n", r.url);
     }
 
 
-    let bs = balance_sheets(&sec_client, &reports).unwrap();
+    let bs = balance_sheet_first(&sec_client, &reports).unwrap();
 
     println!("\n{:?}", bs.headers);
     println!("\n{:?}", bs.sections);
     println!("\n{:?}", bs.data);
 
 }
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use sec_filings::{form_type_summary_by_cik, FixtureClient};
+
+    use super::*;
+
+
+    fn fixture(path: &str) -> PathBuf {
+        return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(path);
+    }
+
+
+    /// Fail unless `actual` is what tests/fixtures/golden/`name` holds.
+    /// Run with `UPDATE_GOLDEN=1` to write it there instead.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = fixture("golden").join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_default();
+        assert!(actual == expected, "{} differs, got:\n{}", path.display(), actual);
+    }
+
+
+    fn aapl_reports() -> Vec<ReportEntry> {
+        let url = "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/\
+                   FilingSummary.xml";
+        let client = FixtureClient::new()
+                     .with_response(url, fixture("aapl_10q/FilingSummary.xml"));
+        return master_reports(&client, &[url.to_string()]).unwrap();
+    }
+
+
+    fn aapl_statement(name: &str) -> StatementData {
+        let html = fs::read_to_string(fixture("aapl_10q").join(name)).unwrap();
+        return parse_html_statement_data(&html).unwrap();
+    }
+
+
+
+    #[test]
+    fn reports_tree() {
+        assert_golden("reports_tree.txt", &render_tree(&aapl_reports(), 80));
+    }
+
+
+    #[test]
+    fn reports_json() {
+        let json = serde_json::to_string_pretty(&render_json(&aapl_reports())).unwrap();
+        assert_golden("reports.json", &format!("{}\n", json));
+    }
+
+
+    #[test]
+    fn comparison() {
+        let current = aapl_statement("R4.htm");
+
+        // the prior quarter, as if one row were added and one dropped since
+        let mut previous = current.clone();
+        let total = previous.data.iter().position(|row| row[0] == "Total assets").unwrap();
+        previous.data[total][1] = "340,000".to_string();
+        previous.data.remove(0);
+        previous.row_ids.remove(0);
+        previous.data.push(vec!["Goodwill".to_string(), "(1,200)".to_string()]);
+        previous.row_ids.push(sec_filings::row_id("Goodwill", "", 0));
+
+        let periods = ["Dec. 30, 2023".to_string(), "Sep. 30, 2023".to_string()];
+        assert_golden("compare.txt", &render_comparison(&periods, &[current, previous], 100));
+    }
+
+
+    #[test]
+    fn forms() {
+        let client = FixtureClient::new()
+                     .with_response("https://data.sec.gov/submissions/CIK0000320193.json",
+                                    fixture("aapl_10q/submissions.json"))
+                     .with_response("https://data.sec.gov/submissions/\
+                                     CIK0000320193-submissions-001.json",
+                                    fixture("aapl_10q/submissions-001.json"));
+        let summary = form_type_summary_by_cik(&client, 320193).unwrap();
+        assert_golden("forms.txt", &render_forms(&summary));
+    }
+
+
+    /// An empty directory for the bundles of `test`.
+    fn out_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sec_filings_{}_{}", test,
+                                                    std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+
+    fn bundle_meta() -> serde_json::Value {
+        return json!({ "ticker": "AAPL", "accession": "0000320193-24-000006" });
+    }
+
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir).unwrap()
+                                     .map(|entry| entry.unwrap().file_name().to_string_lossy()
+                                                       .into_owned())
+                                     .collect();
+        names.sort();
+        return names;
+    }
+
+
+    #[test]
+    fn bundle_holds_every_format() {
+        let out = out_dir("bundle_formats");
+        let html = fs::read_to_string(fixture("aapl_10q/R2.htm")).unwrap();
+        let statement = parse_html_statement_data(&html).unwrap();
+        let formats: Vec<String> = EMIT_FORMATS.iter()
+                                               .filter(|f| cfg!(feature = "serde") || **f != "json")
+                                               .map(|f| f.to_string()).collect();
+
+        let target = stage_bundle(&out, "AAPL-balance", |dir| {
+            write_bundle(dir, &formats, &statement, &html, &bundle_meta())
+        }).unwrap();
+
+        assert_eq!(target, out.join("AAPL-balance"));
+        assert_eq!(names(&out), ["AAPL-balance"]);
+        assert_eq!(fs::read_to_string(target.join("statement.csv")).unwrap(), statement.to_csv());
+        assert_eq!(fs::read_to_string(target.join("statement.html")).unwrap(), html);
+        #[cfg(feature = "serde")]
+        assert_eq!(StatementData::from_json(&fs::read_to_string(target.join("statement.json"))
+                                                .unwrap()).unwrap(), statement);
+
+        let meta: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+                                          target.join("meta.json")).unwrap()).unwrap();
+        assert_eq!(meta, bundle_meta());
+
+        // never over an existing bundle
+        assert!(stage_bundle(&out, "AAPL-balance", |_| Ok(())).is_err());
+
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+
+    #[test]
+    fn failed_writes_leave_no_partial_bundle() {
+        let out = out_dir("bundle_failure");
+        let html = fs::read_to_string(fixture("aapl_10q/R2.htm")).unwrap();
+        let statement = parse_html_statement_data(&html).unwrap();
+
+        // the CSV written, then the disk full
+        let failed = stage_bundle(&out, "AAPL-balance", |dir| {
+            write_bundle(dir, &["csv".to_string()], &statement, &html, &bundle_meta())?;
+            assert!(dir.join("statement.csv").exists());
+            return Err("No space left on device".into());
+        });
+        assert_eq!(failed.unwrap_err().to_string(), "No space left on device");
+        assert!(names(&out).is_empty());
+
+        // a format write_bundle() cannot emit, after one it did
+        let formats = ["raw".to_string(), "xlsx".to_string()];
+        assert!(stage_bundle(&out, "AAPL-balance", |dir| {
+            write_bundle(dir, &formats, &statement, &html, &bundle_meta())
+        }).is_err());
+        assert!(names(&out).is_empty());
+
+        fs::remove_dir_all(&out).unwrap();
+    }
+}
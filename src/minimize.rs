@@ -0,0 +1,289 @@
+//! Fixture minimizer: shrinks a saved R-file down to the smallest HTML
+//! that still satisfies an assertion about the parsed statement, so a
+//! parser bug report can be committed as a tiny regression fixture.
+//!
+//! The reduction is delta-debugging style and deterministic: the same
+//! input and assertion always produce the same output.
+
+use ego_tree::NodeId;
+use scraper::{Html, Node};
+
+use crate::parse::{parse_html_statement_data, parse_number};
+
+
+/// Assertion about a parsed statement, written as
+/// `row '<label>' parses to <value>`.
+pub struct Assertion {
+    label: String,
+    expected: f64,
+}
+
+
+impl Assertion {
+
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let usage = "expected: row '<label>' parses to <value>";
+
+        let rest = expr.trim().strip_prefix("row ").ok_or(usage)?.trim_start();
+        let rest = rest.strip_prefix('\'').ok_or(usage)?;
+        let (label, rest) = rest.split_once('\'').ok_or(usage)?;
+        let value = rest.trim().strip_prefix("parses to").ok_or(usage)?.trim();
+
+        let expected = parse_number(value).ok_or(format!(
+                       "'{}' is not a number, {}", value, usage))?;
+
+        return Ok(Self {
+            label: label.trim().to_string(),
+            expected,
+        });
+    }
+
+
+    /// True if a data row labelled `label` has a cell equal to `expected`.
+    pub fn holds(&self, html: &str) -> bool {
+        let statement_data = match parse_html_statement_data(html) {
+            Ok(statement_data) => statement_data,
+            Err(_) => return false,
+        };
+
+        return statement_data.data.iter().any(|row| {
+            row.first().is_some_and(|label| label.eq_ignore_ascii_case(&self.label))
+            && row.iter().skip(1).any(|cell| parse_number(cell) == Some(self.expected))
+        });
+    }
+}
+
+
+
+/// Nodes reachable from the document root, in document order.
+fn reachable_nodes(document: &Html) -> Vec<NodeId> {
+    return document.tree.root().descendants().skip(1).map(|node| node.id())
+                   .collect();
+}
+
+
+fn reachable_elements(document: &Html) -> Vec<NodeId> {
+    return document.tree.root().descendants()
+                   .filter(|node| node.value().is_element())
+                   // keep <html> itself, everything else is fair game
+                   .filter(|node| node.parent().is_some_and(|p| !p.value().is_document()))
+                   .map(|node| node.id()).collect();
+}
+
+
+
+/// Repeatedly try `edit` on chunks of candidates, halving the chunk size
+/// whenever a full pass makes no progress, until single-candidate edits
+/// no longer help.
+fn reduce<F, E>(mut current: Html, assertion: &Assertion, candidates: F, edit: E)
+                -> Html
+where
+    F: Fn(&Html) -> Vec<NodeId>,
+    E: Fn(&mut Html, NodeId) -> bool,
+{
+    let mut chunk = (candidates(&current).len() / 2).max(1);
+
+    loop {
+        let ids = candidates(&current);
+        let mut changed = false;
+
+        for group in ids.chunks(chunk) {
+            let mut trial = current.clone();
+
+            // skip groups where no edit applies, they cannot make progress
+            let mut edited = false;
+            for id in group {
+                edited |= edit(&mut trial, *id);
+            }
+
+            if edited && assertion.holds(&trial.html()) {
+                current = trial;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            if chunk == 1 {
+                break;
+            }
+            chunk = (chunk / 2).max(1);
+        }
+    }
+
+    return current;
+}
+
+
+
+fn remove_node(document: &mut Html, id: NodeId) -> bool {
+    if document.tree.get(id).is_none_or(|node| node.parent().is_none()) {
+        return false;
+    }
+
+    document.tree.get_mut(id).expect("node checked above").detach();
+    return true;
+}
+
+
+/// Replace an element by its children.
+fn unwrap_element(document: &mut Html, id: NodeId) -> bool {
+    let children: Vec<NodeId> = match document.tree.get(id) {
+        Some(node) if node.parent().is_some() => node.children().map(|c| c.id())
+                                                     .collect(),
+        _ => return false,
+    };
+
+    let mut node = document.tree.get_mut(id).expect("node checked above");
+    for child in children {
+        node.insert_id_before(child);
+    }
+    node.detach();
+
+    return true;
+}
+
+
+fn strip_attributes(document: &mut Html, id: NodeId) -> bool {
+    let mut node = match document.tree.get_mut(id) {
+        Some(node) => node,
+        None => return false,
+    };
+
+    match node.value() {
+        Node::Element(element) if !element.attrs.is_empty() => {
+            element.attrs.clear();
+            return true;
+        }
+        _ => return false,
+    }
+}
+
+
+
+/// Reduce `html` while `assertion` holds, removing rows and other nodes,
+/// unwrapping wrapper elements and stripping attributes until a fixpoint.
+pub fn minimize(html: &str, assertion: &Assertion) -> Result<String, String> {
+    if !assertion.holds(html) {
+        return Err("assertion does not hold for the input".to_string());
+    }
+
+    let mut current = Html::parse_document(html);
+
+    loop {
+        let before = current.html();
+
+        current = reduce(current, assertion, reachable_nodes, remove_node);
+        current = reduce(current, assertion, reachable_elements, unwrap_element);
+        current = reduce(current, assertion, reachable_elements, strip_attributes);
+
+        if current.html() == before {
+            break;
+        }
+    }
+
+    return Ok(current.html());
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Balance sheet R-file as EDGAR renders them, cut down to a few rows.
+    const R_FILE: &str = r#"<html><head><title>R2.htm</title>
+<script type="text/javascript">function toggleNextSibling(e) {}</script></head>
+<body><span style="display: none;">v3.24.2</span>
+<div class="wrapper"><div class="inner">
+<table class="report" border="0" cellspacing="2" id="idm140">
+<tr><th class="tl" colspan="1" rowspan="2"><div style="width: 200px;"><strong>
+CONDENSED CONSOLIDATED BALANCE SHEETS - USD ($)<br/> $ in Millions</strong></div></th>
+<th class="th"><div>Dec. 30, 2023</div></th><th class="th"><div>Sep. 30, 2023</div></th></tr>
+<tr class="re"><td class="pl"><a class="a" onclick="top.Show.showAR(this, 'defref_x', window);">
+Cash and cash equivalents</a></td><td class="nump">$ 40,760</td><td class="nump">$ 29,965</td></tr>
+<tr class="ro"><td class="pl"><a class="a">Total current assets</a></td>
+<td class="nump">143,692</td><td class="nump">143,566</td></tr>
+<tr class="re"><td class="pl"><a class="a">Total assets</a></td>
+<td class="nump">353,514</td><td class="nump">352,583</td></tr>
+<tr class="ro"><td class="pl"><a class="a">Treasury stock</a></td>
+<td class="num">(1,234)</td><td class="num">(1,100)</td></tr>
+</table></div></div></body></html>"#;
+
+
+
+    #[test]
+    fn assertion_syntax() {
+        let assertion = Assertion::parse("row 'Total assets' parses to 352,583").unwrap();
+        assert_eq!((assertion.label.as_str(), assertion.expected), ("Total assets", 352583.0));
+        assert_eq!(Assertion::parse("  row 'Treasury stock'  parses to (1,234) ").unwrap()
+                             .expected, -1234.0);
+
+        for expr in ["Total assets parses to 1", "row 'Total assets parses to 1",
+                     "row 'Total assets' is 1", "row 'Total assets' parses to one"] {
+            assert!(Assertion::parse(expr).is_err(), "{}", expr);
+        }
+    }
+
+
+    #[test]
+    fn assertion_holds() {
+        assert!(Assertion::parse("row 'total ASSETS' parses to 353514").unwrap().holds(R_FILE));
+        assert!(Assertion::parse("row 'Treasury stock' parses to -1100").unwrap().holds(R_FILE));
+        assert!(!Assertion::parse("row 'Total assets' parses to 1").unwrap().holds(R_FILE));
+        assert!(!Assertion::parse("row 'Goodwill' parses to 353514").unwrap().holds(R_FILE));
+        assert!(!Assertion::parse("row 'Total assets' parses to 1").unwrap().holds("<p>"));
+    }
+
+
+    #[test]
+    fn minimize_keeps_only_the_asserted_row() {
+        let assertion = Assertion::parse("row 'Total assets' parses to 352583").unwrap();
+        let minimized = minimize(R_FILE, &assertion).unwrap();
+
+        assert!(assertion.holds(&minimized));
+        assert_eq!(minimized, "<html><table><td>Total assets</td><td>352,583</td></table></html>");
+        for gone in ["Cash and cash", "Total current", "Treasury", "script", "wrapper", "onclick",
+                     "353,514", "Dec. 30"] {
+            assert!(!minimized.contains(gone), "{} in {}", gone, minimized);
+        }
+
+        // what is left parses as the asserted row of the original did
+        let original = parse_html_statement_data(R_FILE).unwrap();
+        let shrunk = parse_html_statement_data(&minimized).unwrap();
+        let total_assets = original.get_row_exact("Total assets").unwrap();
+        assert_eq!(shrunk.data.len(), 1);
+        assert_eq!(shrunk.data[0][0], total_assets[0]);
+        assert!(shrunk.data[0][1..].iter().all(|cell| total_assets[1..].contains(cell)));
+    }
+
+
+    #[test]
+    fn minimize_is_deterministic_and_a_fixpoint() {
+        let assertion = Assertion::parse("row 'Treasury stock' parses to (1,234)").unwrap();
+        let minimized = minimize(R_FILE, &assertion).unwrap();
+
+        assert_eq!(minimize(R_FILE, &assertion).unwrap(), minimized);
+        assert_eq!(minimize(&minimized, &assertion).unwrap(), minimized);
+    }
+
+
+    #[test]
+    fn minimal_input_parses_to_the_same_statement() {
+        let assertion = Assertion::parse("row 'Total assets' parses to 353514").unwrap();
+        let minimal = minimize(R_FILE, &assertion).unwrap();
+        let padded = format!("<div class=\"x\"><span>{}</span></div>",
+                             minimal.trim_start_matches("<html>").trim_end_matches("</html>"));
+
+        let shrunk = minimize(&padded, &assertion).unwrap();
+        assert_eq!(parse_html_statement_data(&shrunk).unwrap().data,
+                   parse_html_statement_data(&padded).unwrap().data);
+    }
+
+
+    #[test]
+    fn minimize_refuses_input_the_assertion_fails_on() {
+        let assertion = Assertion::parse("row 'Total assets' parses to 1").unwrap();
+        assert!(minimize(R_FILE, &assertion).is_err());
+    }
+}
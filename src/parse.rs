@@ -0,0 +1,767 @@
+//! Parsing of statement tables (R-files) into `StatementData`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use log::warn;
+use scraper::{ElementRef, Html, Selector};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::edgar::ReportEntry;
+use crate::error::SecFilingsError;
+use crate::scale::Scale;
+
+
+/// Struct to hold the parsed table data.
+///
+/// As JSON: `{"headers": [[...]], "sections": [...], "data": [[...]],
+/// "row_ids": [...], "row_sections": [...], "monetary_scale": "Millions",
+/// "share_scale": "Units", "currency": null, "source_url": "...",
+/// "report_shortname": "...", "ticker": null, "filing_date": null}`,
+/// everything after "data" may be omitted.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StatementData {
+    pub headers: Vec<Vec<String>>,
+    pub sections: Vec<String>,
+    pub data: Vec<Vec<String>>,
+    /// One id per row in `data`, see `row_id()`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub row_ids: Vec<String>,
+    /// Index into `sections` of the section each row in `data` sits
+    /// under, `None` for rows before the first section.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub row_sections: Vec<Option<usize>>,
+    /// Scale of monetary amounts declared in the header, see
+    /// `normalized_value()`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub monetary_scale: Scale,
+    /// Scale of share counts declared in the header.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub share_scale: Scale,
+    /// ISO code of the reporting currency, `None` for USD or when the
+    /// header names none.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub currency: Option<String>,
+    /// URL of the R-file the statement was parsed from, set by
+    /// `balance_sheet_first()` and the other statement accessors.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub source_url: Option<String>,
+    /// Shortname of that report, e.g. "CONSOLIDATED BALANCE SHEETS".
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub report_shortname: Option<String>,
+    /// Left for the caller to fill in, the report list does not know it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ticker: Option<String>,
+    /// "YYYY-MM-DD", left for the caller like `ticker`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub filing_date: Option<String>,
+}
+
+
+/// A data row with its values parsed, see `StatementData::to_numeric()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericRow {
+    pub label: String,
+    /// One per value column, `None` for dashes, blanks and text.
+    pub values: Vec<Option<f64>>,
+}
+
+
+impl StatementData {
+
+    /// `data` with every cell after the label parsed as a number, see
+    /// `CellValue` for finer control over dashes and blanks.
+    pub fn to_numeric(&self) -> Vec<NumericRow> {
+        return self.data.iter().map(|row| NumericRow {
+            label: row.first().cloned().unwrap_or_default(),
+            values: row.iter().skip(1).map(|cell| CellValue::parse(cell)
+                                                  .to_option(CollapsePolicy::default()))
+                       .collect(),
+        }).collect();
+    }
+
+
+    /// First data row whose label contains `label`, ignoring case, e.g.
+    /// "total assets". See `find_rows()` for every match.
+    pub fn get_row(&self, label: &str) -> Option<&[String]> {
+        return self.find_rows(label).into_iter().next();
+    }
+
+
+    /// First data row labelled exactly `label`.
+    pub fn get_row_exact(&self, label: &str) -> Option<&[String]> {
+        return self.data.iter().find(|row| row.first().is_some_and(|first| first == label))
+                   .map(Vec::as_slice);
+    }
+
+
+    /// Every data row whose label contains `label`, ignoring case.
+    pub fn find_rows(&self, label: &str) -> Vec<&[String]> {
+        let label = label.to_lowercase();
+        return self.data.iter().filter(|row| row.first().is_some_and(|first|
+                                              first.to_lowercase().contains(&label)))
+                   .map(Vec::as_slice).collect();
+    }
+
+
+    /// `data` with every cell after the label parsed as a `CellValue`.
+    pub fn cell_values(&self) -> Vec<Vec<CellValue>> {
+        return self.data.iter().map(|row| row.iter().skip(1)
+                                             .map(|cell| CellValue::parse(cell)).collect())
+                   .collect();
+    }
+
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string(self).expect("StatementData serializes to JSON");
+    }
+
+
+    /// Record the report the statement was parsed from.
+    pub(crate) fn set_source(&mut self, reports: &[ReportEntry], url: &str) {
+        self.source_url = Some(url.to_string());
+        self.report_shortname = reports.iter().find(|report| report.url == url)
+                                       .map(|report| report.shortname.clone());
+    }
+
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        return serde_json::from_str(s);
+    }
+}
+
+
+#[cfg(feature = "serde")]
+impl TryFrom<serde_json::Value> for StatementData {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        return serde_json::from_value(value);
+    }
+}
+
+
+
+/// Lowercase a row label and collapse punctuation and whitespace, so
+/// "Other, net" and "other net" normalize the same way.
+pub(crate) fn normalize_label(label: &str) -> String {
+    let mut normalized = String::with_capacity(label.len());
+
+    for word in label.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.extend(word.chars().map(|c| c.to_ascii_lowercase()));
+    }
+    return normalized;
+}
+
+
+
+/// Stable identifier for a data row, derived from its normalized label,
+/// the section it sits under and how many rows with the same label came
+/// before it in that section.
+///
+/// The id is stable for a given filing, and best-effort stable across
+/// filings of the same company as long as the filer keeps its labels and
+/// section headings. Uses FNV-1a since std's hasher may change between
+/// Rust releases.
+pub fn row_id(label: &str, section: &str, occurrence: usize) -> String {
+    return normalized_row_id(&normalize_label(label), &normalize_label(section), occurrence);
+}
+
+
+/// `row_id()` of a label and section already through `normalize_label()`.
+fn normalized_row_id(label: &str, section: &str, occurrence: usize) -> String {
+    let key = format!("{}|{}|{}", section, label, occurrence);
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    return format!("{:016x}", hash);
+}
+
+
+
+/// Longest cell text considered a number, anything longer is filer
+/// prose or garbage and is rejected before scanning it.
+const MAX_NUMBER_LEN: usize = 64;
+
+
+/// `cell` without footnote references such as "[1]" or "[a]", which some
+/// filers append to numbers.
+fn strip_footnotes(cell: &str) -> String {
+    let mut out = String::with_capacity(cell.len());
+    let mut rest = cell;
+
+    while let Some(open) = rest.find('[') {
+        let close = rest[open..].find(']').map(|i| open + i);
+        match close {
+            Some(close) if close - open <= 4 && rest[open + 1..close].chars()
+                                                   .all(char::is_alphanumeric) => {
+                out.push_str(&rest[..open]);
+                rest = &rest[close + 1..];
+            }
+            _ => {
+                out.push_str(&rest[..=open]);
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    return out;
+}
+
+
+/// Signs a number may carry, at most one per cell.
+const CURRENCY_SIGNS: [char; 4] = ['$', '\u{20ac}', '\u{a3}', '\u{a5}'];
+
+
+/// Whether `cell` has at most one pair of parentheses, opened before it
+/// is closed.
+fn balanced_parens(cell: &str) -> bool {
+    let open = cell.match_indices('(').map(|(i, _)| i).collect::<Vec<_>>();
+    let close = cell.match_indices(')').map(|(i, _)| i).collect::<Vec<_>>();
+
+    return match (open.as_slice(), close.as_slice()) {
+        ([], []) => true,
+        ([open], [close]) => open < close,
+        _ => false,
+    };
+}
+
+
+/// Strip "$", ",", "%", whitespace and footnote references, parenthesized
+/// values are negative. Percentages keep their scale, "12.5%" is 12.5.
+/// Unbalanced parentheses or more than one currency sign make the cell
+/// text, not a number.
+///
+/// Total: returns `None` instead of panicking on any input, and looks at
+/// no more than `MAX_NUMBER_LEN` bytes of it.
+pub(crate) fn parse_number(cell: &str) -> Option<f64> {
+    if cell.len() > MAX_NUMBER_LEN {
+        return None;
+    }
+    let cell = if cell.contains('[') { Cow::Owned(strip_footnotes(cell)) }
+               else { Cow::Borrowed(cell) };
+
+    // prose that happens to contain digits, e.g. "See Note 5"
+    if cell.chars().any(char::is_alphabetic) {
+        return None;
+    }
+    if !balanced_parens(&cell) || cell.matches(&CURRENCY_SIGNS[..]).count() > 1 {
+        return None;
+    }
+
+    let negative = cell.contains('(');
+    let digits: String = cell.chars().map(|c| if c == '\u{2212}' { '-' } else { c })
+                             .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+                             .collect();
+    let value: f64 = digits.parse().ok()?;
+
+    if negative {
+        return Some(-value.abs());
+    }
+    return Some(value);
+}
+
+
+
+/// A statement cell, keeping apart the ways filers write "no number":
+/// a dash means not applicable, a blank means no value this period.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    /// A non-zero number.
+    Number(f64),
+    Zero,
+    /// "—", "–" or "-", possibly with a currency sign.
+    Dash,
+    Empty,
+    /// Anything else, e.g. a footnote or a label.
+    Text(String),
+}
+
+
+/// How `CellValue::to_option()` collapses non-numbers. By default only
+/// numbers and zeros have a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CollapsePolicy {
+    pub dash_as_zero: bool,
+    pub empty_as_zero: bool,
+}
+
+
+impl CellValue {
+
+    /// A footnote reference is not part of the value, "— [1]" is a dash
+    /// and a cell holding nothing but "[1]" is empty.
+    pub fn parse(cell: &str) -> Self {
+        // longer cells are text whatever they hold, leave them whole
+        let cell = if cell.contains('[') && cell.len() <= MAX_NUMBER_LEN {
+            Cow::Owned(strip_footnotes(cell))
+        } else {
+            Cow::Borrowed(cell)
+        };
+        let trimmed = cell.trim();
+        if trimmed.is_empty() {
+            return CellValue::Empty;
+        }
+
+        let mut marks = trimmed.chars().filter(|c| !c.is_whitespace()
+                                                  && !matches!(c, '$' | '(' | ')'))
+                               .peekable();
+        if marks.peek().is_some() && marks.all(|c| matches!(c, '-' | '\u{2013}'
+                                                 | '\u{2014}' | '\u{2212}')) {
+            return CellValue::Dash;
+        }
+
+        match parse_number(trimmed) {
+            Some(0.0) => CellValue::Zero,
+            Some(value) => CellValue::Number(value),
+            None => CellValue::Text(trimmed.to_string()),
+        }
+    }
+
+
+    /// The cell as a plain number under `policy`.
+    pub fn to_option(&self, policy: CollapsePolicy) -> Option<f64> {
+        match self {
+            CellValue::Number(value) => Some(*value),
+            CellValue::Zero => Some(0.0),
+            CellValue::Dash if policy.dash_as_zero => Some(0.0),
+            CellValue::Empty if policy.empty_as_zero => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+
+
+/// Selectors the statement parser uses, parsed once.
+struct Selectors {
+    table: Selector,
+    report: Selector,
+}
+
+
+fn selectors() -> &'static Selectors {
+    static SELECTORS: OnceLock<Selectors> = OnceLock::new();
+
+    return SELECTORS.get_or_init(|| Selectors {
+        table: Selector::parse("table").expect("Failed to parse 'table' tag"),
+        report: Selector::parse("table.report").expect("Failed to parse 'table.report'"),
+    });
+}
+
+
+/// Elements under `element` named one of `names`, in document order.
+/// What `select()` gives for a tag selector, without the cost of
+/// selector matching on every row.
+fn descendants_named<'a>(element: ElementRef<'a>, names: &'a [&'a str])
+                         -> impl Iterator<Item = ElementRef<'a>> + 'a {
+    return element.descendants().skip(1).filter_map(ElementRef::wrap)
+                  .filter(move |el| names.contains(&el.value().name()));
+}
+
+
+
+/// Rows of `table` itself, leaving out those of tables nested in it.
+fn own_rows<'a>(table: ElementRef<'a>) -> Vec<ElementRef<'a>> {
+    return descendants_named(table, &["tr"]).filter(|tr| {
+        tr.ancestors().filter_map(ElementRef::wrap)
+          .find(|el| el.value().name() == "table")
+          .is_some_and(|el| el.id() == table.id())
+    }).collect();
+}
+
+
+/// The statement table of a page: the first `table.report`, which is
+/// what the XBRL viewer emits, otherwise the table with the most rows of
+/// its own, so an outer layout table is passed over.
+fn primary_table(document: &Html) -> Option<ElementRef<'_>> {
+    if let Some(table) = document.select(&selectors().report).next() {
+        return Some(table);
+    }
+
+    // max_by_key keeps the last of equals, prefer the first
+    return document.select(&selectors().table).rev().max_by_key(|table| own_rows(*table).len());
+}
+
+
+
+/// Text of a cell, its text nodes joined by spaces and trimmed.
+fn cell_text(cell: ElementRef<'_>) -> String {
+    let mut text = String::new();
+
+    for (i, piece) in cell.text().enumerate() {
+        if i > 0 {
+            text.push(' ');
+        }
+        text.push_str(piece);
+    }
+    return text.trim().to_string();
+}
+
+
+
+/// Append the rows of `table` to `statement_data`, counting label
+/// occurrences in `occurrences` for the row ids.
+fn parse_table_rows(table: ElementRef<'_>, statement_data: &mut StatementData,
+                    occurrences: &mut HashMap<(String, String), usize>) {
+
+    // the normalized heading of the current section, so it is not
+    // normalized again for every row under it
+    let mut section = statement_data.sections.last().map(|section| normalize_label(section))
+                                    .unwrap_or_default();
+
+    for (i, tr) in own_rows(table).into_iter().enumerate() {
+        let mut ths = vec![];
+        let mut tds = vec![];
+        let mut has_strong = false;
+
+        for element in descendants_named(tr, &["th", "td", "strong"]) {
+            match element.value().name() {
+                "th" => ths.push(element),
+                "td" => tds.push(element),
+                _ => has_strong = true,
+            }
+        }
+
+        // document header
+        if !ths.is_empty() {
+            let header_row = ths.iter().map(|col| cell_text(*col)).collect();
+            statement_data.headers.push(header_row);
+        }
+
+        // document section row (under header)
+        else if !tds.is_empty() && has_strong {
+            let section_row = cell_text(tds[0]);
+            section = normalize_label(&section_row);
+            statement_data.sections.push(section_row);
+        }
+
+        // data rows (under section)
+        else if !tds.is_empty() {
+            let data_row: Vec<String> = tds.iter().map(|col| cell_text(*col)).collect();
+
+            let label = normalize_label(&data_row[0]);
+            let occurrence = occurrences.entry((section.clone(), label.clone())).or_insert(0);
+            statement_data.row_ids.push(normalized_row_id(&label, &section, *occurrence));
+            *occurrence += 1;
+
+            statement_data.row_sections.push(statement_data.sections.len().checked_sub(1));
+
+            statement_data.data.push(data_row);
+        }
+
+        else {
+            warn!("skipping row {} of the statement table, it has no cells", i);
+        }
+    }
+}
+
+
+
+fn has_header_row(table: ElementRef<'_>) -> bool {
+    return own_rows(table).iter().any(|tr| descendants_named(*tr, &["th"]).next().is_some());
+}
+
+
+/// Widest row of `table`, in cells.
+fn column_count(table: ElementRef<'_>) -> usize {
+    return own_rows(table).iter().map(|tr| descendants_named(*tr, &["td", "th"]).count()).max()
+                          .unwrap_or(0);
+}
+
+
+
+/// Parses HTML content of a SEC filing page, extract statement
+/// data from its statement table, see `primary_table`. Tables right
+/// after it without a header row and with the same number of columns
+/// are taken as its continuation and merged in.
+///
+/// Fails if the page has no table, rows without cells are skipped with
+/// a warning.
+pub fn parse_html_statement_data(html: &str) -> Result<StatementData, SecFilingsError> {
+
+    let mut statement_data = StatementData::default();
+
+    // occurrences of each (section, label) pair, used for 'row_ids'
+    let mut occurrences: HashMap<(String, String), usize> = HashMap::new();
+
+    // parse html
+    let document = Html::parse_document(html);
+
+    // find the statement table
+    let table = primary_table(&document).ok_or_else(|| SecFilingsError::UnexpectedStructure(
+                "no <table> found in the HTML".to_string()))?;
+
+    parse_table_rows(table, &mut statement_data, &mut occurrences);
+
+    // merge continuation tables
+    let columns = column_count(table);
+    let following = document.select(&selectors().table)
+                            .skip_while(|other| other.id() != table.id()).skip(1)
+                            .filter(|other| !other.ancestors().any(|a| a.id() == table.id()));
+
+    for other in following {
+        if has_header_row(other) || column_count(other) != columns {
+            break;
+        }
+        parse_table_rows(other, &mut statement_data, &mut occurrences);
+    }
+
+    statement_data.apply_declared_units();
+    return Ok(statement_data);
+}
+
+
+
+/// Every table of the page parsed on its own, in document order, for
+/// pages holding several statements or disclosures. Tables wrapping
+/// others only lay out the page and are left out, the tables nested in
+/// them are parsed instead.
+pub fn parse_html_statement_data_all_tables(html: &str) -> Vec<StatementData> {
+    let document = Html::parse_document(html);
+
+    return document.select(&selectors().table)
+                   .filter(|table| !own_rows(*table).is_empty())
+                   .filter(|table| descendants_named(*table, &["table"]).next().is_none())
+                   .map(|table| {
+                       let mut statement_data = StatementData::default();
+                       parse_table_rows(table, &mut statement_data, &mut HashMap::new());
+                       statement_data.apply_declared_units();
+                       statement_data
+                   }).collect();
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+
+    #[test]
+    fn row_id_is_fnv_1a() {
+        // hashes of "||0", "|total assets|0", ... as any FNV-1a 64 gives them
+        assert_eq!(row_id("", "", 0), "9be2c6196b51752f");
+        assert_eq!(row_id("Total assets", "", 0), "20579e2b3e7d2478");
+        assert_eq!(row_id("Total assets", "", 1), "20579f2b3e7d262b");
+        assert_eq!(row_id("Total assets", "Current assets:", 0), "70d7772d4d9a971a");
+        assert_eq!(row_id("Net income", "", 0), "b1e53c314e97c475");
+    }
+
+
+    #[test]
+    fn row_id_ignores_case_and_punctuation() {
+        assert_eq!(row_id("TOTAL ASSETS", "", 0), row_id("Total assets", "", 0));
+        assert_eq!(row_id("  Total, assets. ", "", 0), row_id("Total assets", "", 0));
+        assert_eq!(row_id("Total assets", "CURRENT ASSETS", 0),
+                   row_id("Total assets", "Current assets:", 0));
+    }
+
+
+    fn statement_html(name: &str) -> String {
+        let path = format!("{}/tests/fixtures/statements/{}", env!("CARGO_MANIFEST_DIR"), name);
+        return std::fs::read_to_string(path).unwrap();
+    }
+
+
+    fn statement_fixture(name: &str) -> StatementData {
+        return parse_html_statement_data(&statement_html(name)).unwrap();
+    }
+
+
+    /// Index of the data row labelled `label` under the section `section`.
+    fn row_under(statement: &StatementData, section: &str, label: &str) -> usize {
+        return (0..statement.data.len()).find(|&i| {
+            statement.data[i][0] == label
+            && statement.row_sections[i].map(|s| statement.sections[s].as_str()) == Some(section)
+        }).unwrap();
+    }
+
+
+    #[test]
+    fn other_rows_in_different_sections_get_distinct_ids() {
+        let statement = statement_fixture("other_rows_q1.htm");
+
+        let others: Vec<&String> = statement.data.iter().zip(&statement.row_ids)
+                                            .filter(|(row, _)| row[0] == "Other")
+                                            .map(|(_, id)| id).collect();
+        assert_eq!(others.len(), 3);
+        assert_eq!(others.iter().collect::<HashSet<_>>().len(), 3);
+
+        // nor does any other row share an id
+        assert_eq!(statement.row_ids.iter().collect::<HashSet<_>>().len(), statement.data.len());
+    }
+
+
+    #[test]
+    fn rows_keep_their_ids_across_quarters() {
+        let q1 = statement_fixture("other_rows_q1.htm");
+        let q2 = statement_fixture("other_rows_q2.htm");
+
+        // q2 adds "Restricted cash" and moves the non-current "Other" up
+        for (i, row) in q1.data.iter().enumerate() {
+            let section = &q1.sections[q1.row_sections[i].unwrap()];
+            let j = row_under(&q2, section, &row[0]);
+            assert_eq!(q1.row_ids[i], q2.row_ids[j], "{} under {}", row[0], section);
+        }
+        let ppe = "Property, plant and equipment, net";
+        assert_ne!(row_under(&q1, "Non-current assets:", ppe),
+                   row_under(&q2, "Non-current assets:", ppe));
+
+        let added = row_under(&q2, "Current assets:", "Restricted cash");
+        assert!(!q1.row_ids.contains(&q2.row_ids[added]));
+    }
+
+
+    #[test]
+    fn all_tables_of_a_single_table_page() {
+        let html = statement_html("../aapl_10q/R2.htm");
+        assert_eq!(parse_html_statement_data_all_tables(&html),
+                   [parse_html_statement_data(&html).unwrap()]);
+    }
+
+
+    #[test]
+    fn all_tables_skips_layout_tables() {
+        let html = statement_html("nested_tables.htm");
+        let tables = parse_html_statement_data_all_tables(&html);
+
+        // the statement alone, not the table laying it out
+        assert_eq!(tables, [parse_html_statement_data(&html).unwrap()]);
+        assert_eq!(tables[0].sections, ["Revenue:", "Operating expenses:"]);
+        assert_eq!(tables[0].data.len(), 5);
+        assert_eq!(tables[0].monetary_scale, Scale::Thousands);
+    }
+
+
+    #[test]
+    fn all_tables_parses_each_table_on_its_own() {
+        let html = statement_html("multi_table.htm");
+        let tables = parse_html_statement_data_all_tables(&html);
+        assert_eq!(tables.len(), 2);
+
+        // the first is the statement, the parenthetical is not merged in
+        assert_eq!(tables[0], parse_html_statement_data(&html).unwrap());
+        assert_eq!(tables[0].data.len(), 3);
+        assert_eq!(tables[0].monetary_scale, Scale::Thousands);
+
+        let parenthetical = &tables[1];
+        assert!(parenthetical.headers[0][0].contains("(Parenthetical)"));
+        assert!(parenthetical.sections.is_empty());
+        assert_eq!(parenthetical.data[1], ["Common stock, shares issued", "25,112,340",
+                                           "24,806,115"]);
+        assert_eq!(parenthetical.row_sections, [None, None]);
+        assert_eq!(parenthetical.monetary_scale, Scale::Units);
+
+        // row ids are counted per table
+        assert_eq!(parenthetical.row_ids[0], row_id("Common stock, par value", "", 0));
+    }
+
+
+    #[test]
+    fn continuation_tables_are_merged() {
+        let statement = statement_fixture("split_statement.htm");
+
+        // both tables of the balance sheet, not the parenthetical after them
+        assert_eq!(statement.headers.len(), 1);
+        assert_eq!(statement.sections, ["Current assets:", "Current liabilities:"]);
+        let labels: Vec<&str> = statement.data.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(labels, ["Cash and cash equivalents", "Accounts receivable, net", "Other",
+                            "Inventories", "Total current assets", "Accounts payable", "Other"]);
+        assert_eq!(statement.monetary_scale, Scale::Millions);
+
+        // the continuation starts under the section the first table ended in
+        let inventories = row_under(&statement, "Current assets:", "Inventories");
+        assert_eq!(statement.row_sections[inventories], Some(0));
+        assert_eq!(statement.row_ids[inventories],
+                   row_id("Inventories", "Current assets:", 0));
+
+        assert_eq!(statement.row_ids.iter().collect::<HashSet<_>>().len(), labels.len());
+    }
+
+
+    #[test]
+    fn blank_cells_are_empty() {
+        for cell in ["", "   ", "\u{a0}", " \n\t", "[1]"] {
+            assert_eq!(CellValue::parse(cell), CellValue::Empty, "{:?}", cell);
+        }
+    }
+
+
+    #[test]
+    fn dash_cells_are_dashes() {
+        for cell in ["\u{2014}", "\u{2013}", "-", "$ \u{2014}", "$\u{2014}", "(\u{2014})",
+                     "\u{2212}", "\u{2014} [2]"] {
+            assert_eq!(CellValue::parse(cell), CellValue::Dash, "{:?}", cell);
+        }
+    }
+
+
+    #[test]
+    fn parenthesised_cells_are_negative() {
+        assert_eq!(CellValue::parse("(1,234)"), CellValue::Number(-1234.0));
+        assert_eq!(CellValue::parse("$ (96,458)"), CellValue::Number(-96458.0));
+        assert_eq!(CellValue::parse("(12.5)%"), CellValue::Number(-12.5));
+        assert_eq!(CellValue::parse("(0)"), CellValue::Zero);
+        assert_eq!(CellValue::parse("((5))"), CellValue::Text("((5))".to_string()));
+    }
+
+
+    #[test]
+    fn footnoted_cells_keep_their_value() {
+        assert_eq!(CellValue::parse("1,234 [1]"), CellValue::Number(1234.0));
+        assert_eq!(CellValue::parse("$ 73,812[a]"), CellValue::Number(73812.0));
+        assert_eq!(CellValue::parse("(215) [2]"), CellValue::Number(-215.0));
+        assert_eq!(CellValue::parse("0 [3]"), CellValue::Zero);
+        assert_eq!(CellValue::parse("See Note 5 [1]"), CellValue::Text("See Note 5".to_string()));
+    }
+
+
+    #[test]
+    fn collapse_policy_picks_what_has_a_value() {
+        let cells = ["$ 1,200", "0", "\u{2014}", "", "n/a"].map(CellValue::parse);
+        let collapse = |policy| cells.iter().map(|cell| cell.to_option(policy))
+                                     .collect::<Vec<_>>();
+
+        assert_eq!(collapse(CollapsePolicy::default()),
+                   [Some(1200.0), Some(0.0), None, None, None]);
+        assert_eq!(collapse(CollapsePolicy { dash_as_zero: true, empty_as_zero: false }),
+                   [Some(1200.0), Some(0.0), Some(0.0), None, None]);
+        assert_eq!(collapse(CollapsePolicy { dash_as_zero: false, empty_as_zero: true }),
+                   [Some(1200.0), Some(0.0), None, Some(0.0), None]);
+    }
+
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn statement_round_trips_through_json() {
+        let mut statement = statement_fixture("../aapl_10q/R4.htm");
+        statement.source_url = Some("https://www.sec.gov/Archives/edgar/data/320193/\
+                                     000032019324000006/R4.htm".to_string());
+        statement.report_shortname = Some("CONDENSED CONSOLIDATED BALANCE SHEETS".to_string());
+        statement.ticker = Some("AAPL".to_string());
+        statement.filing_date = Some("2024-02-02".to_string());
+
+        let json = statement.to_json();
+        assert_eq!(StatementData::from_json(&json).unwrap(), statement);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["monetary_scale"], "Millions");
+        assert_eq!(StatementData::try_from(value).unwrap(), statement);
+    }
+}
@@ -0,0 +1,162 @@
+//! Related-party transaction disclosures, usually a note of their own in
+//! 10-K and 10-Q filings.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::disclosure::document_text;
+use crate::edgar::ReportEntry;
+
+
+/// Transactions above this share of total assets are flagged as high
+/// value by `RelatedPartyDisclosure::is_high_value`.
+const HIGH_VALUE_SHARE: f64 = 0.01;
+
+
+/// What a filing discloses about related-party transactions.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RelatedPartyDisclosure {
+    pub has_disclosure: bool,
+    /// Largest dollar amount mentioned alongside a related party.
+    pub transaction_amount: Option<f64>,
+    /// First sentence mentioning a related party.
+    pub description: Option<String>,
+    /// Companies and people named in those sentences.
+    pub related_parties: Vec<String>,
+}
+
+
+impl RelatedPartyDisclosure {
+
+    /// Whether the largest transaction exceeds 1% of `total_assets`,
+    /// both in the same unit.
+    pub fn is_high_value(&self, total_assets: f64) -> bool {
+        return match self.transaction_amount {
+            Some(amount) if total_assets > 0.0 => amount > total_assets * HIGH_VALUE_SHARE,
+            _ => false,
+        };
+    }
+}
+
+
+struct Patterns {
+    mention: Regex,
+    amount: Regex,
+    company: Regex,
+    person: Regex,
+}
+
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+
+    return PATTERNS.get_or_init(|| Patterns {
+        mention: Regex::new(r"(?i)\brelated[- ]part(y|ies)\b").expect("valid regex"),
+        amount: Regex::new(r"\$\s?(\d[\d,]*(?:\.\d+)?)(?:\s+(thousand|million|billion))?")
+                .expect("valid regex"),
+        // capitalized words ending in an entity suffix
+        company: Regex::new(concat!(r"\b((?:[A-Z][\w&'-]*,? )+",
+                                    r"(?:(?:Inc|Corp|Ltd)\.|L\.P\.|(?:LLC|LP|Corporation|",
+                                    r"Limited|Holdings|Partners|Inc|Corp|Ltd)\b))"))
+                 .expect("valid regex"),
+        person: Regex::new(r"\b((?:Mr|Ms|Mrs|Dr)\. [A-Z][a-z]+(?: [A-Z][a-z]+)?)")
+                .expect("valid regex"),
+    });
+}
+
+
+
+/// Reports whose shortname suggests a related-party note, e.g.
+/// "Related Party Transactions" or "Related-Party Arrangements (Details)".
+pub fn related_party_reports(reports: &[ReportEntry]) -> Vec<&ReportEntry> {
+    let mention = &patterns().mention;
+    return reports.iter().filter(|report| mention.is_match(&report.shortname)).collect();
+}
+
+
+
+/// Sentences of `text`, not broken after abbreviations like "Mr." or
+/// "Inc." the way a plain split on ". " would.
+fn sentences(text: &str) -> Vec<&str> {
+    let abbreviations = ["Mr", "Ms", "Mrs", "Dr", "Inc", "Corp", "Co", "Ltd", "No", "St"];
+
+    let mut sentences = vec![];
+    let mut start = 0;
+
+    for (i, _) in text.match_indices(". ") {
+        let word = text[start..i].rsplit(' ').next().unwrap_or("");
+        let next_is_capital = text[i + 2..].starts_with(|c: char| c.is_uppercase());
+
+        if next_is_capital && !abbreviations.contains(&word) {
+            sentences.push(text[start..=i].trim());
+            start = i + 2;
+        }
+    }
+    sentences.push(text[start..].trim());
+
+    return sentences.into_iter().filter(|s| !s.is_empty()).collect();
+}
+
+
+
+/// Dollar amount of a match like "$1.5 million".
+fn amount(number: &str, scale: Option<&str>) -> Option<f64> {
+    let value: f64 = number.replace(',', "").parse().ok()?;
+
+    let multiplier = match scale {
+        Some("thousand") => 1e3,
+        Some("million") => 1e6,
+        Some("billion") => 1e9,
+        _ => 1.0,
+    };
+    return Some(value * multiplier);
+}
+
+
+
+/// Related-party transactions disclosed in `html`, typically the related
+/// party note found with `related_party_reports`. In a note titled as
+/// such every sentence counts, otherwise amounts and names are only taken
+/// from sentences mentioning a related party.
+pub fn detect_related_party_transactions(html: &str) -> RelatedPartyDisclosure {
+    let text = document_text(html);
+    let patterns = patterns();
+
+    // the title, e.g. "Note 12. Related Party Transactions"
+    let title_end = text.char_indices().nth(100).map_or(text.len(), |(i, _)| i);
+    let whole_note = patterns.mention.is_match(&text[..title_end]);
+
+    let mut disclosure = RelatedPartyDisclosure::default();
+
+    for sentence in sentences(&text) {
+        if !whole_note && !patterns.mention.is_match(sentence) {
+            continue;
+        }
+
+        disclosure.has_disclosure = true;
+        // skip the title, it describes nothing
+        if disclosure.description.is_none() && sentence.len() > 60 {
+            disclosure.description = Some(sentence.to_string());
+        }
+
+        for m in patterns.amount.captures_iter(sentence) {
+            if let Some(value) = amount(&m[1], m.get(2).map(|s| s.as_str())) {
+                disclosure.transaction_amount = Some(disclosure.transaction_amount
+                                                     .map_or(value, |max| max.max(value)));
+            }
+        }
+
+        let names = patterns.company.captures_iter(sentence)
+                            .chain(patterns.person.captures_iter(sentence))
+                            .map(|m| m[1].trim_end_matches(',').to_string());
+
+        for name in names {
+            if !disclosure.related_parties.contains(&name) {
+                disclosure.related_parties.push(name);
+            }
+        }
+    }
+
+    return disclosure;
+}
@@ -0,0 +1,500 @@
+//! Reporting scale and currency declared in a statement's header, e.g.
+//! "shares in Thousands, $ in Millions".
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
+use log::warn;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::SecFilingsError;
+use crate::facts::FactValue;
+use crate::parse::{parse_number, CellValue, CollapsePolicy, StatementData};
+
+
+/// Factor the numbers of a statement are reported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Scale {
+    /// As written, also used when the statement declares no scale.
+    #[default]
+    Units,
+    Thousands,
+    Millions,
+    Billions,
+}
+
+
+impl Scale {
+
+    pub fn multiplier(self) -> f64 {
+        return match self {
+            Scale::Units => 1.0,
+            Scale::Thousands => 1e3,
+            Scale::Millions => 1e6,
+            Scale::Billions => 1e9,
+        };
+    }
+
+
+    fn from_word(word: &str) -> Scale {
+        return match word.to_lowercase().as_str() {
+            "thousands" => Scale::Thousands,
+            "millions" => Scale::Millions,
+            "billions" => Scale::Billions,
+            _ => Scale::Units,
+        };
+    }
+}
+
+
+/// ISO 4217 currency code, e.g. "EUR", three uppercase ASCII letters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency([u8; 3]);
+
+
+impl Currency {
+
+    pub const USD: Currency = Currency(*b"USD");
+
+
+    /// `code` as a currency, ignoring case, `None` unless it is three
+    /// ASCII letters. Whether ISO assigned the code is not checked.
+    pub fn new(code: &str) -> Option<Currency> {
+        let bytes: [u8; 3] = code.trim().as_bytes().try_into().ok()?;
+        if !bytes.iter().all(u8::is_ascii_alphabetic) {
+            return None;
+        }
+        return Some(Currency(bytes.map(|byte| byte.to_ascii_uppercase())));
+    }
+
+
+    pub fn as_str(&self) -> &str {
+        return std::str::from_utf8(&self.0).expect("ASCII letters");
+    }
+
+
+    /// Whether ISO 4217 assigns the code to a currency in use.
+    pub fn is_iso(&self) -> bool {
+        return ISO_CODES.binary_search(&self.as_str()).is_ok();
+    }
+}
+
+
+/// Active ISO 4217 currency codes, sorted.
+const ISO_CODES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD",
+    "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ",
+    "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD", "JOD",
+    "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR",
+    "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR",
+    "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN",
+    "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR",
+    "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB",
+    "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS",
+    "VES", "VND", "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW", "ZWL",
+];
+
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(self.as_str());
+    }
+}
+
+
+impl FromStr for Currency {
+    type Err = SecFilingsError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        return Currency::new(code).ok_or_else(|| SecFilingsError::InvalidInput(
+            format!("{:?} is not a currency code", code)));
+    }
+}
+
+
+
+/// What `detect_units()` found in a header.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Units {
+    pub monetary_scale: Scale,
+    pub share_scale: Scale,
+    /// ISO code of a currency other than USD, e.g. "EUR".
+    pub currency: Option<String>,
+}
+
+
+struct Patterns {
+    /// "$ in Millions", "€ in Thousands", "CAD in Millions"
+    monetary: Regex,
+    /// "shares in Thousands"
+    shares: Regex,
+    /// older filings: "In Thousands, except Share data"
+    bare: Regex,
+    /// the title's "- EUR (€)", kept only for an ISO code
+    currency: Regex,
+}
+
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+
+    return PATTERNS.get_or_init(|| Patterns {
+        monetary: Regex::new(r"(?:[$€£¥]|\b[A-Z]{3}\b)\s+(?i:in\s+(thousands|millions|billions))\b")
+                  .expect("valid regex"),
+        shares: Regex::new(r"(?i)\bshares\s+in\s+(thousands|millions|billions)\b")
+                .expect("valid regex"),
+        bare: Regex::new(r"(?i)\bin\s+(thousands|millions|billions)\b(,\s*except\s+share\b)?")
+              .expect("valid regex"),
+        currency: Regex::new(r"\b([A-Z]{3}) \([^)]*\)").expect("valid regex"),
+    });
+}
+
+
+
+/// Scales and currency declared in `headers`, which hold them in the
+/// title cell, e.g. "Consolidated Balance Sheets - EUR (€) € in Millions".
+/// Whatever is not declared is taken as units, not guessed.
+pub fn detect_units(headers: &[Vec<String>]) -> Units {
+    let patterns = patterns();
+    let mut units = Units::default();
+
+    for cell in headers.iter().flatten() {
+        let monetary = patterns.monetary.captures(cell);
+        let shares = patterns.shares.captures(cell);
+
+        if let Some(m) = &monetary {
+            units.monetary_scale = Scale::from_word(&m[1]);
+        }
+        if let Some(m) = &shares {
+            units.share_scale = Scale::from_word(&m[1]);
+        }
+
+        if monetary.is_none() && shares.is_none() {
+            if let Some(m) = patterns.bare.captures(cell) {
+                units.monetary_scale = Scale::from_word(&m[1]);
+                // "except Per Share data" still scales share counts
+                if m.get(2).is_none() {
+                    units.share_scale = units.monetary_scale;
+                }
+            }
+        }
+        // "INCOME TAX (benefit)" looks the same as "EUR (€)"
+        let code = patterns.currency.captures_iter(cell)
+                                    .filter_map(|m| Currency::new(&m[1]))
+                                    .find(Currency::is_iso);
+        if let Some(code) = code {
+            if code != Currency::USD && units.currency.is_none() {
+                units.currency = Some(code.to_string());
+            }
+        }
+    }
+
+    return units;
+}
+
+
+
+/// Scale of monetary amounts declared in `headers`, `Units` if none is,
+/// see `detect_units()`.
+pub fn detect_scale(headers: &[Vec<String>]) -> Scale {
+    return detect_units(headers).monetary_scale;
+}
+
+
+
+impl StatementData {
+
+    /// `cell` as a number multiplied by `scale`. Handles "$", thousands
+    /// separators and parenthesized negatives, `None` for anything else.
+    pub fn parse_numeric(cell: &str, scale: Scale) -> Option<f64> {
+        return parse_number(cell).map(|value| value * scale.multiplier());
+    }
+
+
+    /// Every value column of `data` scaled to units, see
+    /// `normalized_value()`.
+    pub fn numeric_data(&self) -> Vec<Vec<Option<f64>>> {
+        return self.data.iter().enumerate().map(|(row, cells)|
+                   (0..cells.len().saturating_sub(1)).map(|col| self.normalized_value(row, col))
+                                                    .collect())
+                   .collect();
+    }
+
+
+    /// Fill in `monetary_scale`, `share_scale` and `currency` from the
+    /// headers.
+    pub(crate) fn apply_declared_units(&mut self) {
+        let units = detect_units(&self.headers);
+        self.monetary_scale = units.monetary_scale;
+        self.share_scale = units.share_scale;
+        self.currency = units.currency;
+    }
+
+
+    /// Value column `col` (0 being the first after the label, as in
+    /// `to_numeric()`) of data row `row`, multiplied by the declared
+    /// scale. Rows labelled per share are never scaled, other rows
+    /// mentioning shares take `share_scale`, the rest `monetary_scale`.
+    pub fn normalized_value(&self, row: usize, col: usize) -> Option<f64> {
+        let cells = self.data.get(row)?;
+        let value = CellValue::parse(cells.get(col + 1)?).to_option(CollapsePolicy::default())?;
+
+        let label = cells.first().map(|label| label.to_lowercase()).unwrap_or_default();
+        let scale = if label.contains("per share") {
+            Scale::Units
+        } else if is_share_count(&label) {
+            self.share_scale
+        } else {
+            self.monetary_scale
+        };
+
+        return Some(value * scale.multiplier());
+    }
+}
+
+
+
+/// Whether a row holds share counts rather than amounts of money.
+fn is_share_count(label: &str) -> bool {
+    let label = label.to_lowercase();
+    return label.contains("shares") && !label.contains("per share");
+}
+
+
+/// `value` as a cell, without trailing zeros.
+pub(crate) fn format_cell(value: f64) -> String {
+    let formatted = format!("{:.2}", value);
+    return formatted.trim_end_matches('0').trim_end_matches('.').to_string();
+}
+
+
+/// `value` written the way `original` is: with "$" if it has a currency
+/// sign, thousands separators if it has any and a negative value in
+/// parentheses if it uses them. Footnote marks are dropped.
+fn format_like(original: &str, value: f64) -> String {
+    let digits = format_cell(value.abs());
+    let digits = if original.contains(',') { group_thousands(&digits) } else { digits };
+
+    let amount = if value >= 0.0 || digits == "0" {
+        digits
+    } else if original.contains('(') {
+        format!("({})", digits)
+    } else {
+        format!("-{}", digits)
+    };
+
+    if original.contains(['$', '€', '£', '¥']) {
+        return format!("$ {}", amount);
+    }
+    return amount;
+}
+
+
+/// "1234567.5" as "1,234,567.5".
+fn group_thousands(digits: &str) -> String {
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits, None),
+    };
+    let groups: Vec<&str> = whole.as_bytes().rchunks(3).rev()
+                                 .map(|chunk| std::str::from_utf8(chunk).expect("ASCII digits"))
+                                 .collect();
+
+    return match fraction {
+        Some(fraction) => format!("{}.{}", groups.join(","), fraction),
+        None => groups.join(","),
+    };
+}
+
+
+
+/// Copy of `data`, reported in `source_currency`, with every amount of
+/// money converted to USD at `exchange_rate` USD per unit of
+/// `source_currency`. Share counts, percentages, dashes and text are left
+/// as they are, and so are the headers, while `currency` becomes `None`.
+///
+/// Converted cells keep their format: "€ (1,234)" at 1.1 becomes
+/// "$ (1,357.4)". Footnote marks in them are dropped.
+///
+/// The crate does not fetch exchange rates, the caller supplies one. A
+/// single rate for a whole statement is an approximation: balance sheets
+/// translate at the period end rate, income and cash flow statements at
+/// average rates over the period, see `normalize_time_series_to_usd()`.
+pub fn normalize_to_usd(data: &StatementData, exchange_rate: f64, source_currency: Currency)
+                        -> StatementData {
+    if let Some(currency) = &data.currency {
+        if !currency.eq_ignore_ascii_case(source_currency.as_str()) {
+            warn!("statement is reported in {}, converting it as {}", currency, source_currency);
+        }
+    }
+
+    let mut normalized = data.clone();
+    normalized.currency = None;
+
+    for row in normalized.data.iter_mut() {
+        if row.first().is_some_and(|label| is_share_count(label)) {
+            continue;
+        }
+
+        for cell in row.iter_mut().skip(1) {
+            if cell.contains('%') {
+                continue;
+            }
+            if let CellValue::Number(value) = CellValue::parse(cell) {
+                *cell = format_like(cell, value * exchange_rate);
+            }
+        }
+    }
+
+    return normalized;
+}
+
+
+
+/// `series` converted to USD, each value at the rate of `rate_series`
+/// (dates and USD per unit of the reporting currency) dated nearest to
+/// the end of its period, the earlier of two equally near.
+///
+/// Values whose end date does not parse are left out with a warning, and
+/// so is everything when `rate_series` is empty. As with
+/// `normalize_to_usd()`, the rates are the caller's: the period end rate
+/// suits instants, a rate series of period averages suits durations.
+pub fn normalize_time_series_to_usd(series: &[FactValue], rate_series: &[(NaiveDate, f64)])
+                                    -> Vec<FactValue> {
+    let mut rates = rate_series.to_vec();
+    rates.sort_by_key(|(date, _)| *date);
+
+    let mut normalized = vec![];
+    for value in series {
+        let end = match NaiveDate::parse_from_str(&value.end, "%Y-%m-%d") {
+            Ok(end) => end,
+            Err(_) => {
+                warn!("leaving out {} ending {:?}, not a date", value.val, value.end);
+                continue;
+            }
+        };
+        let rate = match nearest_rate(&rates, end) {
+            Some(rate) => rate,
+            None => {
+                warn!("leaving out {} ending {}, no exchange rate", value.val, end);
+                continue;
+            }
+        };
+
+        let mut converted = value.clone();
+        converted.val *= rate;
+        normalized.push(converted);
+    }
+
+    return normalized;
+}
+
+
+/// Rate of `rates`, sorted by date, dated nearest to `date`.
+fn nearest_rate(rates: &[(NaiveDate, f64)], date: NaiveDate) -> Option<f64> {
+    let after = rates.partition_point(|(rate_date, _)| *rate_date < date);
+    let before = after.checked_sub(1).map(|i| rates[i]);
+    let after = rates.get(after).copied();
+
+    return match (before, after) {
+        (Some(before), Some(after)) if after.0 - date < date - before.0 => Some(after.1),
+        (Some(before), _) => Some(before.1),
+        (None, after) => after.map(|(_, rate)| rate),
+    };
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn date(text: &str) -> NaiveDate {
+        return NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap();
+    }
+
+
+    fn fact(val: f64, end: &str) -> FactValue {
+        return FactValue { val, start: None, end: end.to_string(), accn: String::new(), fy: None,
+                           fp: None, form: String::new(), filed: String::new(), frame: None };
+    }
+
+
+
+    #[test]
+    fn currency_codes() {
+        assert_eq!(Currency::new(" eur").map(|c| c.to_string()), Some("EUR".to_string()));
+        assert_eq!("USD".parse::<Currency>().unwrap(), Currency::USD);
+        assert_eq!(Currency::new("EURO"), None);
+        assert_eq!(Currency::new("E1R"), None);
+        assert_eq!(Currency::new("€"), None);
+        assert!("".parse::<Currency>().is_err());
+
+        assert!(Currency::new("eur").unwrap().is_iso());
+        assert!(!Currency::new("TAX").unwrap().is_iso());
+        assert!(ISO_CODES.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+
+    #[test]
+    fn only_iso_codes_are_currencies() {
+        let headers = |title: &str| vec![vec![title.to_string()]];
+
+        let units = detect_units(&headers("INCOME TAX (BENEFIT) - USD ($) $ in Millions"));
+        assert_eq!(units.currency, None);
+        assert_eq!(units.monetary_scale, Scale::Millions);
+
+        let units = detect_units(&headers("TAX (Details) - EUR (€) € in Millions"));
+        assert_eq!(units.currency.as_deref(), Some("EUR"));
+
+        assert_eq!(detect_units(&headers("DEFERRED TAX (Details)")).currency, None);
+    }
+
+
+    #[test]
+    fn normalize_to_usd_keeps_cell_format() {
+        let data = StatementData { currency: Some("EUR".to_string()), data: vec![
+            vec!["Revenue".into(), "€ 1,000".into(), "(1,234)".into(), "-20".into()],
+            vec!["Shares outstanding".into(), "1,000".into(), "900".into(), "800".into()],
+            vec!["Margin".into(), "12%".into(), "—".into(), "1,000,000 [1]".into()],
+        ], ..StatementData::default() };
+
+        let usd = normalize_to_usd(&data, 1.1, Currency::new("EUR").unwrap());
+        assert_eq!(usd.currency, None);
+        assert_eq!(usd.data[0][1..], ["$ 1,100", "(1,357.4)", "-22"]);
+        assert_eq!(usd.data[1][1..], ["1,000", "900", "800"]);
+        assert_eq!(usd.data[2][1..], ["12%", "—", "1,100,000"]);
+    }
+
+
+    #[test]
+    fn time_series_takes_nearest_rate() {
+        let series = [fact(100.0, "2023-12-31"), fact(100.0, "2024-03-31"),
+                      fact(100.0, "2024-02-14"), fact(100.0, "2022-01-01"),
+                      fact(100.0, "2025-01-01"), fact(100.0, "not a date")];
+        let rates = [(date("2024-03-30"), 1.5), (date("2024-01-01"), 1.25)];
+
+        let usd = normalize_time_series_to_usd(&series, &rates);
+        let vals: Vec<(&str, f64)> = usd.iter().map(|v| (v.end.as_str(), v.val)).collect();
+        assert_eq!(vals, [("2023-12-31", 125.0), ("2024-03-31", 150.0), ("2024-02-14", 125.0),
+                          ("2022-01-01", 125.0), ("2025-01-01", 150.0)]);
+
+        assert!(normalize_time_series_to_usd(&series, &[]).is_empty());
+    }
+
+
+    #[test]
+    fn time_series_ties_take_earlier_rate() {
+        let rates = [(date("2024-01-01"), 1.0), (date("2024-01-03"), 2.0)];
+        let usd = normalize_time_series_to_usd(&[fact(1.0, "2024-01-02")], &rates);
+        assert_eq!(usd[0].val, 1.0);
+    }
+}
@@ -0,0 +1,154 @@
+//! EDGAR full-text search, covering the text of every filing since 2001.
+
+use chrono::NaiveDate;
+use log::warn;
+use serde::Deserialize;
+
+use crate::accession::AccessionNumber;
+use crate::client::SecClient;
+use crate::error::SecFilingsError;
+
+
+/// Endpoint of the full-text search API behind efts.sec.gov.
+pub const FULL_TEXT_SEARCH_URL: &str = "https://efts.sec.gov/LATEST/search-index";
+
+
+/// What `full_text_search()` looks for.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FullTextQuery {
+    /// Search terms, quoted for a phrase, e.g. "\"going concern\"".
+    pub q: String,
+    /// Filed on or after.
+    pub date_from: Option<NaiveDate>,
+    /// Filed on or before.
+    pub date_to: Option<NaiveDate>,
+    /// Form types to search, e.g. ["10-K", "10-Q"], all if empty.
+    pub forms: Vec<String>,
+    /// Number of hits to skip, the API returns 100 per page.
+    pub from: u32,
+}
+
+
+impl FullTextQuery {
+
+    pub fn new(q: &str) -> Self {
+        return FullTextQuery { q: q.to_string(), ..FullTextQuery::default() };
+    }
+
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("q", self.q.clone())];
+
+        if self.date_from.is_some() || self.date_to.is_some() {
+            params.push(("dateRange", "custom".to_string()));
+        }
+        if let Some(date) = self.date_from {
+            params.push(("startdt", date.format("%Y-%m-%d").to_string()));
+        }
+        if let Some(date) = self.date_to {
+            params.push(("enddt", date.format("%Y-%m-%d").to_string()));
+        }
+        if !self.forms.is_empty() {
+            params.push(("forms", self.forms.join(",")));
+        }
+        if self.from > 0 {
+            params.push(("from", self.from.to_string()));
+        }
+
+        return params;
+    }
+}
+
+
+/// A document matching a full-text search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullTextResult {
+    pub accession: AccessionNumber,
+    /// e.g. "Apple Inc.  (AAPL)  (CIK 0000320193)".
+    pub entity_name: String,
+    pub ciks: Vec<u64>,
+    /// "YYYY-MM-DD".
+    pub file_date: String,
+    /// Form type of the filing, e.g. "10-K".
+    pub form_type: String,
+    /// File name of the matching document within the filing.
+    pub document: String,
+}
+
+
+
+#[derive(Debug, Deserialize)]
+struct SearchJson {
+    hits: HitsJson,
+}
+
+#[derive(Debug, Deserialize)]
+struct HitsJson {
+    #[serde(default)]
+    hits: Vec<HitJson>,
+}
+
+/// `_id` is "<accession>:<document>".
+#[derive(Debug, Deserialize)]
+struct HitJson {
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(rename = "_source")]
+    source: SourceJson,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceJson {
+    #[serde(default)]
+    ciks: Vec<String>,
+    #[serde(default)]
+    display_names: Vec<String>,
+    #[serde(default)]
+    file_date: String,
+    #[serde(default)]
+    form: String,
+    adsh: Option<String>,
+}
+
+
+
+/// Results in a full-text search response.
+pub(crate) fn parse_full_text_results(json: &str)
+                                      -> Result<Vec<FullTextResult>, SecFilingsError> {
+    let search: SearchJson = serde_json::from_str(json)?;
+    let mut results = vec![];
+
+    for hit in search.hits.hits {
+        let (id_accession, document) = hit.id.split_once(':').unwrap_or((&hit.id, ""));
+        let raw_accession = hit.source.adsh.as_deref().unwrap_or(id_accession);
+
+        let accession = match AccessionNumber::parse(raw_accession) {
+            Some(accession) => accession,
+            None => {
+                warn!("skipping search hit {}: invalid accession number", hit.id);
+                continue;
+            }
+        };
+
+        results.push(FullTextResult {
+            accession,
+            entity_name: hit.source.display_names.join("; "),
+            ciks: hit.source.ciks.iter().filter_map(|cik| cik.parse().ok()).collect(),
+            file_date: hit.source.file_date,
+            form_type: hit.source.form,
+            document: document.to_string(),
+        });
+    }
+
+    return Ok(results);
+}
+
+
+
+/// One page of up to 100 documents matching `query`, most relevant
+/// first. Advance `query.from` for the next page.
+pub fn full_text_search(sec_client: &SecClient, query: &FullTextQuery)
+                        -> Result<Vec<FullTextResult>, SecFilingsError> {
+    let response = sec_client.get_with_params(FULL_TEXT_SEARCH_URL, &query.params())?;
+    return parse_full_text_results(&response);
+}
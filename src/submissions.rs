@@ -0,0 +1,419 @@
+//! Filing history from the data.sec.gov submissions API, a structured
+//! alternative to the browse-edgar Atom feed.
+
+use std::io::Read;
+
+use chrono::NaiveDate;
+use log::warn;
+use serde::Deserialize;
+
+use crate::accession::AccessionNumber;
+use crate::client::{HttpFetch, SecClient};
+use crate::edgar::FilingType;
+use crate::error::SecFilingsError;
+use crate::tickers::{padded_cik, ticker_to_cik};
+
+
+/// Base URL of the submissions API, followed by "CIK##########.json".
+pub const SUBMISSIONS_URL: &str = "https://data.sec.gov/submissions/";
+
+
+/// One filing listed in `Submissions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filing {
+    pub cik: u64,
+    pub accession_number: AccessionNumber,
+    /// Form type, e.g. "10-Q" or "8-K".
+    pub form: String,
+    /// "YYYY-MM-DD".
+    pub filing_date: String,
+    /// When EDGAR accepted the filing, UTC, e.g. "2024-08-01T18:04:43.000Z".
+    pub acceptance_date_time: String,
+    /// Period the filing reports on, "YYYY-MM-DD", empty if none.
+    pub report_date: String,
+    /// File name of the main document within the filing's folder.
+    pub primary_document: String,
+    pub primary_doc_description: String,
+    pub is_xbrl: bool,
+}
+
+
+impl Filing {
+
+    /// The filing's folder under Archives.
+    pub fn folder_url(&self) -> String {
+        return format!("https://www.sec.gov/Archives/edgar/data/{}/{}/", self.cik,
+                       self.accession_number.without_dashes());
+    }
+
+
+    /// index.json URL of the filing's folder, as accepted by
+    /// `filing_summaries()`.
+    pub fn index_url(&self) -> String {
+        return format!("{}index.json", self.folder_url());
+    }
+
+
+    /// URL of the primary document.
+    pub fn primary_document_url(&self) -> String {
+        return format!("{}{}", self.folder_url(), self.primary_document);
+    }
+
+
+    /// `filing_date` as a date.
+    pub fn filed_on(&self) -> Option<NaiveDate> {
+        return NaiveDate::parse_from_str(&self.filing_date, "%Y-%m-%d").ok();
+    }
+}
+
+
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubmissionsJson {
+    #[serde(default)]
+    name: String,
+    sic: Option<String>,
+    #[serde(default)]
+    sic_description: String,
+    #[serde(default)]
+    tickers: Vec<String>,
+    #[serde(default)]
+    exchanges: Vec<Option<String>>,
+    fiscal_year_end: Option<String>,
+    filings: FilingsJson,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilingsJson {
+    recent: RecentFilings,
+    #[serde(default)]
+    files: Vec<OverflowFile>,
+}
+
+/// File holding filings older than the "recent" block.
+#[derive(Debug, Deserialize)]
+struct OverflowFile {
+    name: String,
+}
+
+/// Column oriented: the n-th filing is made of the n-th entry of each array.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecentFilings {
+    accession_number: Vec<String>,
+    #[serde(default)]
+    form: Vec<String>,
+    #[serde(default)]
+    filing_date: Vec<String>,
+    #[serde(default)]
+    acceptance_date_time: Vec<String>,
+    #[serde(default)]
+    report_date: Vec<String>,
+    #[serde(default)]
+    primary_document: Vec<String>,
+    #[serde(default)]
+    primary_doc_description: Vec<String>,
+    #[serde(rename = "isXBRL", default)]
+    is_xbrl: Vec<u8>,
+}
+
+
+
+/// `column[i]`, or an empty string if the column is short.
+fn nth(column: &[String], i: usize) -> String {
+    return column.get(i).cloned().unwrap_or_default();
+}
+
+
+
+/// Company metadata and recent filings from the submissions API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submissions {
+    pub cik: u64,
+    pub name: String,
+    /// Standard Industrial Classification code, e.g. "3571".
+    pub sic: Option<String>,
+    /// e.g. "Electronic Computers".
+    pub sic_description: String,
+    /// Tickers of the company's listed securities, e.g. ["BRK-A", "BRK-B"].
+    pub tickers: Vec<String>,
+    /// Exchange of each ticker, e.g. "NYSE" or "Nasdaq", empty for none.
+    pub exchanges: Vec<String>,
+    /// Last day of the fiscal year as "MMDD", e.g. "0930", if reported.
+    pub fiscal_year_end: Option<String>,
+    /// Most recent first.
+    pub filings: Vec<Filing>,
+    /// Files listing older filings, e.g. "CIK0001067983-submissions-001.json",
+    /// see `all_filings()`.
+    pub overflow_files: Vec<String>,
+}
+
+
+/// Former name of `Submissions`.
+pub type CompanySubmissions = Submissions;
+
+
+impl Submissions {
+
+    /// Month (1-12) the company's fiscal year ends in.
+    pub fn fiscal_year_end_month(&self) -> Result<u8, SecFilingsError> {
+        let raw = self.fiscal_year_end.as_deref().ok_or_else(||
+                  SecFilingsError::NotFound(format!("fiscal year end of CIK {}", self.cik)))?;
+
+        return Ok(parse_fiscal_year_end(raw)?.0);
+    }
+}
+
+
+
+/// Parse a fiscal year end in the submissions API's "MMDD" format into
+/// (month, day), e.g. "0930" into (9, 30).
+pub fn parse_fiscal_year_end(raw: &str) -> Result<(u8, u8), SecFilingsError> {
+    let invalid = || SecFilingsError::UnexpectedStructure(format!(
+                     "'{}' is not a MMDD fiscal year end", raw));
+
+    let raw = raw.trim();
+    if raw.len() != 4 || !raw.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let month: u8 = raw[..2].parse().map_err(|_| invalid())?;
+    let day: u8 = raw[2..].parse().map_err(|_| invalid())?;
+
+    let days_in_month = match month {
+        2 => 29,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return Err(invalid()),
+    };
+    if day == 0 || day > days_in_month {
+        return Err(invalid());
+    }
+
+    return Ok((month, day));
+}
+
+
+
+/// Filings of one block of parallel arrays.
+fn filings_from_columns(cik: u64, columns: &RecentFilings) -> Vec<Filing> {
+    let mut filings = vec![];
+
+    for (i, accession) in columns.accession_number.iter().enumerate() {
+        let accession_number = match AccessionNumber::parse(accession) {
+            Some(accession_number) => accession_number,
+            None => {
+                warn!("skipping filing {}: invalid accession number", accession);
+                continue;
+            }
+        };
+
+        filings.push(Filing {
+            cik,
+            accession_number,
+            form: nth(&columns.form, i),
+            filing_date: nth(&columns.filing_date, i),
+            acceptance_date_time: nth(&columns.acceptance_date_time, i),
+            report_date: nth(&columns.report_date, i),
+            primary_document: nth(&columns.primary_document, i),
+            primary_doc_description: nth(&columns.primary_doc_description, i),
+            is_xbrl: columns.is_xbrl.get(i) == Some(&1),
+        });
+    }
+
+    return filings;
+}
+
+
+
+/// Company and filings in a submissions JSON document.
+pub(crate) fn read_submissions(cik: u64, reader: &mut dyn Read)
+                               -> Result<Submissions, SecFilingsError> {
+    let submissions: SubmissionsJson = serde_json::from_reader(reader)?;
+
+    return Ok(Submissions {
+        cik,
+        name: submissions.name,
+        sic: submissions.sic.filter(|sic| !sic.is_empty()),
+        sic_description: submissions.sic_description,
+        tickers: submissions.tickers,
+        exchanges: submissions.exchanges.into_iter().map(Option::unwrap_or_default).collect(),
+        fiscal_year_end: submissions.fiscal_year_end.filter(|fye| !fye.is_empty()),
+        filings: filings_from_columns(cik, &submissions.filings.recent),
+        overflow_files: submissions.filings.files.into_iter().map(|file| file.name).collect(),
+    });
+}
+
+
+
+/// Filings in an overflow file, which holds the arrays at its top level.
+pub(crate) fn read_overflow_file(cik: u64, reader: &mut dyn Read)
+                                 -> Result<Vec<Filing>, SecFilingsError> {
+    let columns: RecentFilings = serde_json::from_reader(reader)?;
+    return Ok(filings_from_columns(cik, &columns));
+}
+
+
+
+/// Company metadata and recent filings of the company with `cik`.
+///
+/// Only the "recent" block of the API is read, which holds the last
+/// thousand filings or at least a year's worth, see `all_filings()` for
+/// the rest.
+pub fn submissions<C>(sec_client: &C, cik: u64) -> Result<Submissions, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    let url = format!("{}CIK{}.json", SUBMISSIONS_URL, padded_cik(cik));
+    return read_submissions(cik, &mut sec_client.get_reader(&url)?);
+}
+
+
+
+/// Every filing of `company`, most recent first: its recent filings
+/// followed by those in its overflow files.
+pub(crate) fn with_older_filings<C>(sec_client: &C, company: &Submissions)
+                                    -> Result<Vec<Filing>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    let mut filings = company.filings.clone();
+
+    for file in &company.overflow_files {
+        let url = format!("{}{}", SUBMISSIONS_URL, file);
+        filings.extend(read_overflow_file(company.cik, &mut sec_client.get_reader(&url)?)?);
+    }
+
+    return Ok(filings);
+}
+
+
+
+/// Every filing of the company with `cik`, most recent first, including
+/// those in the overflow files of companies with a long history.
+pub fn all_filings<C>(sec_client: &C, cik: u64) -> Result<Vec<Filing>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    return with_older_filings(sec_client, &submissions(sec_client, cik)?);
+}
+
+
+
+/// Distinct form types among `filings` as (form type, count, most recent
+/// filing date), most frequent first. Filings without a valid date are
+/// left out.
+pub fn summarize_form_types(filings: &[Filing]) -> Vec<(FilingType, u32, NaiveDate)> {
+    let mut summary: Vec<(FilingType, u32, NaiveDate)> = vec![];
+
+    for filing in filings {
+        let filed_on = match filing.filed_on() {
+            Some(filed_on) => filed_on,
+            None => {
+                warn!("skipping filing {}: invalid filing date '{}'", filing.accession_number,
+                      filing.filing_date);
+                continue;
+            }
+        };
+
+        match summary.iter_mut().find(|(form, ..)| form.as_str() == filing.form) {
+            Some((_, count, latest)) => {
+                *count += 1;
+                *latest = filed_on.max(*latest);
+            }
+            None => summary.push((FilingType::from(filing.form.as_str()), 1, filed_on)),
+        }
+    }
+
+    summary.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+    return summary;
+}
+
+
+
+/// Every form type the company with `cik` ever filed, overflow files
+/// included, see `summarize_form_types()`.
+pub fn form_type_summary_by_cik<C>(sec_client: &C, cik: u64)
+                                   -> Result<Vec<(FilingType, u32, NaiveDate)>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+    return Ok(summarize_form_types(&all_filings(sec_client, cik)?));
+}
+
+
+
+/// Every form type `ticker` ever filed, see `form_type_summary_by_cik()`.
+pub fn form_type_summary(sec_client: &SecClient, ticker: &str)
+                         -> Result<Vec<(FilingType, u32, NaiveDate)>, SecFilingsError> {
+    let cik = ticker_to_cik(sec_client, ticker)?;
+    return form_type_summary_by_cik(sec_client, cik);
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::FixtureClient;
+
+    use super::*;
+
+
+    fn aapl_client() -> FixtureClient {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/aapl_10q/");
+        return FixtureClient::new()
+               .with_response("https://data.sec.gov/submissions/CIK0000320193.json",
+                              format!("{}submissions.json", fixtures))
+               .with_response("https://data.sec.gov/submissions/\
+                               CIK0000320193-submissions-001.json",
+                              format!("{}submissions-001.json", fixtures));
+    }
+
+
+    fn date(raw: &str) -> NaiveDate {
+        return NaiveDate::parse_from_str(raw, "%Y-%m-%d").unwrap();
+    }
+
+
+    #[test]
+    fn form_types_are_counted_across_overflow_files() {
+        let summary = form_type_summary_by_cik(&aapl_client(), 320193).unwrap();
+
+        // five recent filings and five in submissions-001.json
+        assert_eq!(summary, [(FilingType::TenQ, 4, date("2024-02-02")),
+                             (FilingType::EightK, 3, date("2024-02-01")),
+                             (FilingType::TenK, 2, date("2023-11-03")),
+                             (FilingType::Other("4".to_string()), 1, date("2024-01-03"))]);
+    }
+
+
+    #[test]
+    fn filings_without_a_date_are_left_out() {
+        let mut filings = all_filings(&aapl_client(), 320193).unwrap();
+        filings[0].filing_date = "unknown".to_string();
+
+        let summary = summarize_form_types(&filings);
+        assert_eq!(summary[0], (FilingType::TenQ, 3, date("2023-08-04")));
+    }
+
+
+    #[test]
+    fn fiscal_year_ends() {
+        assert_eq!(parse_fiscal_year_end("0930").unwrap(), (9, 30));
+        assert_eq!(parse_fiscal_year_end("1231").unwrap(), (12, 31));
+        // leap years included, a fiscal year may end on the 29th
+        assert_eq!(parse_fiscal_year_end("0229").unwrap(), (2, 29));
+        assert_eq!(parse_fiscal_year_end(" 0630 ").unwrap(), (6, 30));
+    }
+
+
+    #[test]
+    fn malformed_fiscal_year_ends() {
+        for raw in ["", "930", "09301", "09-30", "ab30", "+930", "0931", "0230", "1301", "0000",
+                    "0900", "\u{660}\u{669}"] {
+            assert!(matches!(parse_fiscal_year_end(raw),
+                             Err(SecFilingsError::UnexpectedStructure(_))), "{:?}", raw);
+        }
+    }
+}
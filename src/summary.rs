@@ -0,0 +1,283 @@
+//! Multi-quarter financial summary built from the statement R-files.
+
+use std::fmt::Write;
+
+use crate::client::{HttpFetch, SecClient};
+use crate::classify::{StatementQuery, BALANCE_SHEET, CASH_FLOW_STATEMENT, INCOME_STATEMENT};
+use crate::edgar::{documents, filing_summaries, find_statement, master_reports, DocumentsQuery,
+                   FilingType, ReportEntry};
+use crate::error::SecFilingsError;
+use crate::parse::{normalize_label, parse_number, StatementData};
+
+
+/// Company the summary was built for.
+#[derive(Debug, Clone)]
+pub struct CompanyInfo {
+    pub ticker: String,
+    /// index.json URL of each filing used, most recent first.
+    pub filings: Vec<String>,
+}
+
+
+/// One line item as a time series, one value per period (most recent
+/// first), `None` where the filing did not report it.
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+}
+
+
+/// Highlights of the last few quarterly filings of a company.
+///
+/// Values are as reported, in the scale stated in the statement header
+/// (usually millions). Cash flow figures in a 10-Q are year-to-date.
+#[derive(Debug, Clone)]
+pub struct FinancialSummary {
+    pub company: CompanyInfo,
+    /// Balance sheet date of each period, most recent first.
+    pub periods: Vec<String>,
+    pub income_statement: Vec<Highlight>,
+    pub balance_sheet: Vec<Highlight>,
+    pub cash_flow: Vec<Highlight>,
+    pub ratios: Vec<Highlight>,
+}
+
+
+// (line item, normalized label prefixes to look for)
+pub(crate) const INCOME_ITEMS: [(&str, &[&str]); 4] = [
+    ("Revenue", &["total net sales", "total revenues", "net sales", "revenues",
+                  "revenue", "total revenue"]),
+    ("Gross profit", &["gross margin", "gross profit", "total gross margin"]),
+    ("Operating income", &["operating income", "income from operations"]),
+    ("Net income", &["net income"]),
+];
+
+pub(crate) const BALANCE_ITEMS: [(&str, &[&str]); 6] = [
+    ("Cash and equivalents", &["cash and cash equivalents"]),
+    ("Total current assets", &["total current assets"]),
+    ("Total assets", &["total assets"]),
+    ("Total current liabilities", &["total current liabilities"]),
+    ("Total liabilities", &["total liabilities"]),
+    ("Total equity", &["total shareholders equity", "total stockholders equity",
+                       "total equity"]),
+];
+
+pub(crate) const CASH_FLOW_ITEMS: [(&str, &[&str]); 2] = [
+    ("Operating cash flow", &["cash generated by operating activities",
+                              "net cash provided by operating activities",
+                              "net cash provided by used in operating activities",
+                              "net cash from operating activities"]),
+    ("Capital expenditures", &["payments for acquisition of property plant and equipment",
+                               "purchases of property and equipment",
+                               "purchases of property plant and equipment",
+                               "capital expenditures"]),
+];
+
+
+
+/// Index of the first row with a number whose label starts with one of
+/// `prefixes`, tried in order.
+pub(crate) fn line_item_row(statement: &StatementData, prefixes: &[&str]) -> Option<usize> {
+    for prefix in prefixes {
+        for (i, row) in statement.data.iter().enumerate() {
+            let label = match row.first() {
+                Some(label) => normalize_label(label),
+                None => continue,
+            };
+
+            let matches = label == *prefix || label.starts_with(&format!("{} ", prefix));
+            if matches && row.iter().skip(1).any(|cell| parse_number(cell).is_some()) {
+                return Some(i);
+            }
+        }
+    }
+    return None;
+}
+
+
+
+/// First numeric value of the row `line_item_row()` finds, i.e. the
+/// current period column.
+fn line_item(statement: &StatementData, prefixes: &[&str]) -> Option<f64> {
+    let row = &statement.data[line_item_row(statement, prefixes)?];
+    return row.iter().skip(1).find_map(|cell| parse_number(cell));
+}
+
+
+
+/// `find_statement`, with a missing report mapped to `None`.
+pub(crate) fn optional_statement<C>(sec_client: &C, reports: &[ReportEntry],
+                                    query: &StatementQuery)
+                                    -> Result<Option<StatementData>, SecFilingsError>
+where
+    C: HttpFetch + ?Sized,
+{
+
+    match find_statement(sec_client, reports, query) {
+        Ok(statement) => Ok(Some(statement)),
+        Err(SecFilingsError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+
+
+fn ratio(numerator: Option<f64>, denominator: Option<f64>) -> Option<f64> {
+    match (numerator, denominator) {
+        (Some(n), Some(d)) if d != 0.0 => Some(n / d),
+        _ => None,
+    }
+}
+
+
+fn highlights(items: &[(&str, &[&str])], statements: &[Option<StatementData>])
+              -> Vec<Highlight> {
+    return items.iter().map(|(name, prefixes)| Highlight {
+        name: name.to_string(),
+        values: statements.iter().map(|statement| statement.as_ref()
+                          .and_then(|s| line_item(s, prefixes))).collect(),
+    }).collect();
+}
+
+
+fn values_of<'a>(highlights: &'a [Highlight], name: &str) -> &'a [Option<f64>] {
+    return highlights.iter().find(|h| h.name == name).map(|h| h.values.as_slice())
+                     .unwrap_or(&[]);
+}
+
+
+/// Element-wise `f(a, b)` over two series of equal length.
+fn combine(a: &[Option<f64>], b: &[Option<f64>],
+           f: fn(Option<f64>, Option<f64>) -> Option<f64>) -> Vec<Option<f64>> {
+    return a.iter().zip(b.iter()).map(|(x, y)| f(*x, *y)).collect();
+}
+
+
+
+/// Fetch the last `periods` 10-Q filings of `ticker` and summarize their
+/// income statement, balance sheet and cash flow statement, along with
+/// the usual margin, liquidity and leverage ratios.
+pub fn generate_financial_summary(sec_client: &SecClient, ticker: &str, periods: usize)
+                                  -> Result<FinancialSummary, SecFilingsError> {
+
+    let docs = documents(sec_client, ticker, &DocumentsQuery::new(), FilingType::TenQ)?;
+    let docs: Vec<String> = docs.into_iter().take(periods).map(|doc| doc.url).collect();
+
+    let mut period_labels = vec![];
+    let mut income = vec![];
+    let mut balance = vec![];
+    let mut cash_flow = vec![];
+
+    for doc in &docs {
+        let filings = filing_summaries(sec_client, std::slice::from_ref(doc))?;
+        let reports = master_reports(sec_client, &filings)?;
+
+        let balance_sheet = optional_statement(sec_client, &reports, &BALANCE_SHEET)?;
+        let label = balance_sheet.as_ref().and_then(|bs| bs.headers.first())
+                                 .and_then(|row| row.get(1)).cloned()
+                                 .unwrap_or_else(|| doc.clone());
+
+        period_labels.push(label);
+        balance.push(balance_sheet);
+        income.push(optional_statement(sec_client, &reports, &INCOME_STATEMENT)?);
+        cash_flow.push(optional_statement(sec_client, &reports, &CASH_FLOW_STATEMENT)?);
+    }
+
+    let income_statement = highlights(&INCOME_ITEMS, &income);
+    let balance_sheet = highlights(&BALANCE_ITEMS, &balance);
+    let cash_flow = highlights(&CASH_FLOW_ITEMS, &cash_flow);
+
+    let revenue = values_of(&income_statement, "Revenue");
+    let net_income = values_of(&income_statement, "Net income");
+    let equity = values_of(&balance_sheet, "Total equity");
+
+    let ratios = vec![
+        Highlight {
+            name: "Gross margin".to_string(),
+            values: combine(values_of(&income_statement, "Gross profit"), revenue, ratio),
+        },
+        Highlight {
+            name: "Operating margin".to_string(),
+            values: combine(values_of(&income_statement, "Operating income"), revenue, ratio),
+        },
+        Highlight {
+            name: "Net margin".to_string(),
+            values: combine(net_income, revenue, ratio),
+        },
+        Highlight {
+            name: "Current ratio".to_string(),
+            values: combine(values_of(&balance_sheet, "Total current assets"),
+                            values_of(&balance_sheet, "Total current liabilities"), ratio),
+        },
+        Highlight {
+            name: "Debt to equity".to_string(),
+            values: combine(values_of(&balance_sheet, "Total liabilities"), equity, ratio),
+        },
+        Highlight {
+            name: "Return on equity (quarter)".to_string(),
+            values: combine(net_income, equity, ratio),
+        },
+        Highlight {
+            name: "Return on assets (quarter)".to_string(),
+            values: combine(net_income, values_of(&balance_sheet, "Total assets"), ratio),
+        },
+    ];
+
+    let summary = FinancialSummary {
+        company: CompanyInfo {
+            ticker: ticker.to_uppercase(),
+            filings: docs,
+        },
+        periods: period_labels,
+        income_statement,
+        balance_sheet,
+        cash_flow,
+        ratios,
+    };
+
+    return Ok(summary);
+}
+
+
+
+impl FinancialSummary {
+
+    /// Markdown document with one table per section, suitable for
+    /// research notes.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# Financial summary: {}\n", self.company.ticker);
+        let _ = writeln!(out, "## Company\n");
+        let _ = writeln!(out, "- Ticker: {}", self.company.ticker);
+        let _ = writeln!(out, "- Filings: {}", self.company.filings.len());
+        for filing in &self.company.filings {
+            let _ = writeln!(out, "  - {}", filing);
+        }
+
+        let sections = [
+            ("Income statement highlights", &self.income_statement, false),
+            ("Balance sheet highlights", &self.balance_sheet, false),
+            ("Cash flow highlights", &self.cash_flow, false),
+            ("Key ratios", &self.ratios, true),
+        ];
+
+        for (title, items, is_ratio) in sections {
+            let _ = writeln!(out, "\n## {}\n", title);
+            let _ = writeln!(out, "| | {} |", self.periods.join(" | "));
+            let _ = writeln!(out, "|---|{}", "---:|".repeat(self.periods.len()));
+
+            for item in items.iter() {
+                let cells: Vec<String> = item.values.iter().map(|value| match value {
+                    Some(v) if is_ratio => format!("{:.2}", v),
+                    Some(v) => format!("{}", v),
+                    None => "-".to_string(),
+                }).collect();
+                let _ = writeln!(out, "| {} | {} |", item.name, cells.join(" | "));
+            }
+        }
+
+        return out;
+    }
+}
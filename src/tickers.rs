@@ -0,0 +1,128 @@
+//! Ticker to CIK resolution via SEC's company_tickers.json.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::client::SecClient;
+use crate::error::SecFilingsError;
+
+
+/// Ticker list published by SEC, about 1 MB.
+pub const COMPANY_TICKERS_URL: &str = "https://www.sec.gov/files/company_tickers.json";
+
+
+/// One entry of company_tickers.json.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompanyTicker {
+    pub cik_str: u64,
+    pub ticker: String,
+    pub title: String,
+}
+
+
+/// Lookup table between tickers and CIKs. A company with several share
+/// classes has one entry per ticker.
+#[derive(Debug, Clone)]
+pub struct TickerMap {
+    companies: Vec<CompanyTicker>,
+    // uppercase ticker -> index into 'companies'
+    by_ticker: HashMap<String, usize>,
+    // cik -> indices into 'companies'
+    by_cik: HashMap<u64, Vec<usize>>,
+}
+
+
+impl TickerMap {
+
+    /// Download company_tickers.json. Prefer `SecClient::ticker_map()`,
+    /// which downloads it once per client.
+    pub fn fetch(sec_client: &SecClient) -> Result<Self, SecFilingsError> {
+        let response = sec_client.get(COMPANY_TICKERS_URL)?;
+        return Self::from_json(&response);
+    }
+
+
+    /// Parse company_tickers.json, an object keyed by row index.
+    pub fn from_json(json: &str) -> Result<Self, SecFilingsError> {
+        let rows: HashMap<String, CompanyTicker> = serde_json::from_str(json)?;
+
+        // keep SEC's row order, it ranks companies by size
+        let mut rows: Vec<(u64, CompanyTicker)> = rows.into_iter()
+            .map(|(index, company)| (index.parse().unwrap_or(u64::MAX), company))
+            .collect();
+        rows.sort_by_key(|(index, _)| *index);
+
+        let companies: Vec<CompanyTicker> = rows.into_iter().map(|(_, c)| c).collect();
+
+        let mut by_ticker = HashMap::new();
+        let mut by_cik: HashMap<u64, Vec<usize>> = HashMap::new();
+
+        for (i, company) in companies.iter().enumerate() {
+            by_ticker.entry(company.ticker.to_uppercase()).or_insert(i);
+            by_cik.entry(company.cik_str).or_default().push(i);
+        }
+
+        return Ok(Self {
+            companies,
+            by_ticker,
+            by_cik,
+        });
+    }
+
+
+    /// Entry for `ticker`, matched case-insensitively.
+    pub fn company(&self, ticker: &str) -> Option<&CompanyTicker> {
+        let index = self.by_ticker.get(&ticker.trim().to_uppercase())?;
+        return self.companies.get(*index);
+    }
+
+
+    /// CIK of `ticker`, matched case-insensitively.
+    pub fn cik(&self, ticker: &str) -> Option<u64> {
+        return self.company(ticker).map(|company| company.cik_str);
+    }
+
+
+    /// Every ticker listed for `cik`.
+    pub fn tickers(&self, cik: u64) -> Vec<&CompanyTicker> {
+        return self.by_cik.get(&cik).map(|indices| indices.iter()
+                          .map(|i| &self.companies[*i]).collect())
+                   .unwrap_or_default();
+    }
+}
+
+
+
+/// CIK in the 10-digit zero-padded form data.sec.gov URLs use.
+pub fn padded_cik(cik: u64) -> String {
+    return format!("{:010}", cik);
+}
+
+
+
+/// 10-digit zero-padded CIK of `ticker`, e.g. "0000320193" for "aapl".
+pub fn cik_for_ticker(sec_client: &SecClient, ticker: &str) -> Result<String, SecFilingsError> {
+    return Ok(padded_cik(ticker_to_cik(sec_client, ticker)?));
+}
+
+
+
+/// CIK of `ticker` as a number.
+pub fn ticker_to_cik(sec_client: &SecClient, ticker: &str) -> Result<u64, SecFilingsError> {
+    return sec_client.ticker_map()?.cik(ticker).ok_or_else(||
+           SecFilingsError::NotFound(format!("ticker '{}'", ticker)));
+}
+
+
+
+/// Tickers listed for `cik`, the reverse of `cik_for_ticker`.
+pub fn tickers_for_cik(sec_client: &SecClient, cik: u64) -> Result<Vec<String>, SecFilingsError> {
+    let tickers: Vec<String> = sec_client.ticker_map()?.tickers(cik).iter()
+                                         .map(|company| company.ticker.clone()).collect();
+
+    if tickers.is_empty() {
+        return Err(SecFilingsError::NotFound(format!("CIK {}", cik)));
+    }
+    return Ok(tickers);
+}
@@ -0,0 +1,233 @@
+//! Consistency checks on extracted figures.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::error::SecFilingsError;
+use crate::parse::{normalize_label, StatementData};
+
+
+/// Differences above this percentage are reported as discrepancies by
+/// `reconcile_statement()`.
+const DISCREPANCY_PCT: f64 = 1.0;
+
+
+/// Checks that disaggregated revenue (ASC 606) sums back to the revenue
+/// reported on the income statement, within an absolute `tolerance`.
+/// A mismatch usually means table rows were miscounted while parsing.
+pub fn validate_revenue_disaggregation(disaggregation: &HashMap<String, f64>,
+                                       income_statement_revenue: f64,
+                                       tolerance: f64)
+                                       -> Result<(), SecFilingsError> {
+
+    let sum: f64 = disaggregation.values().sum();
+
+    if (sum - income_statement_revenue).abs() > tolerance {
+        return Err(SecFilingsError::ReconciliationError(sum,
+                   income_statement_revenue));
+    }
+
+    return Ok(());
+}
+
+
+
+/// Outcome of `reconcile_statement()`, labels as in the HTML statement
+/// except for `xbrl_only`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReconciliationReport {
+    /// (label, HTML value, XBRL value) of every label found in both.
+    pub matched_values: Vec<(String, f64, f64)>,
+    pub html_only: Vec<(String, f64)>,
+    pub xbrl_only: Vec<(String, f64)>,
+    /// (label, HTML value, XBRL value, difference in percent of the XBRL
+    /// value) of matched values more than 1% apart.
+    pub discrepancies: Vec<(String, f64, f64, f64)>,
+}
+
+
+impl ReconciliationReport {
+
+    /// Whether some values disagree by more than 1% and are worth a look.
+    pub fn requires_investigation(&self) -> bool {
+        return !self.discrepancies.is_empty();
+    }
+}
+
+
+
+/// Longest heading `parse_column_date()` reads, "September 30, 2024"
+/// with room for odd spacing. Longer headings are not a bare date.
+const MAX_HEADING_LEN: usize = 40;
+
+
+/// Date of a column heading like "Sep. 30, 2024", "May 31, 2024" or
+/// "September 30, 2024". Any run of whitespace, unicode spaces included,
+/// counts as one space.
+///
+/// Total: returns `None` instead of panicking on any input, and looks at
+/// no more than `MAX_HEADING_LEN` bytes of it.
+pub(crate) fn parse_column_date(heading: &str) -> Option<NaiveDate> {
+    if heading.len() > MAX_HEADING_LEN {
+        return None;
+    }
+
+    let heading = heading.replace('.', "").split_whitespace().collect::<Vec<_>>().join(" ")
+                         .replace("Sept ", "Sep ");
+    return NaiveDate::parse_from_str(&heading, "%b %d, %Y")
+           .or_else(|_| NaiveDate::parse_from_str(&heading, "%B %d, %Y")).ok();
+}
+
+
+
+/// Value column (0 being the first after the label) headed `period` in
+/// the last header row. That row starts with the label cell if it is as
+/// long as the data rows, and holds only the value headings if the title
+/// cell spans it from the row above, as in most R-files.
+fn period_column(html_data: &StatementData, period: &NaiveDate) -> Option<usize> {
+    let header = html_data.headers.last()?;
+    let position = header.iter().position(|cell| parse_column_date(cell) == Some(*period))?;
+
+    let row_len = html_data.data.iter().map(Vec::len).max().unwrap_or(0);
+    if header.len() == row_len {
+        return position.checked_sub(1);
+    }
+    return Some(position);
+}
+
+
+
+/// Compare the `period` column of a statement parsed from HTML against
+/// values from the XBRL API, keyed by label. Labels are matched after
+/// normalization (case and punctuation), HTML values are scaled to units
+/// first, see `StatementData::normalized_value()`.
+///
+/// The column is the first whose heading is `period`, so for an income
+/// statement with "3 Months Ended" and "9 Months Ended" columns the
+/// quarter is used. If no column matches, every XBRL value is reported as
+/// `xbrl_only`.
+pub fn reconcile_statement(html_data: &StatementData, xbrl_data: &HashMap<String, f64>,
+                           period: &NaiveDate) -> ReconciliationReport {
+    let mut report = ReconciliationReport::default();
+
+    let column = period_column(html_data, period);
+
+    let xbrl: HashMap<String, (&String, f64)> = xbrl_data.iter()
+        .map(|(label, value)| (normalize_label(label), (label, *value))).collect();
+    let mut seen: Vec<String> = vec![];
+
+    if let Some(column) = column {
+        for (i, row) in html_data.data.iter().enumerate() {
+            let label = row.first().cloned().unwrap_or_default();
+            let key = normalize_label(&label);
+            let html_value = match html_data.normalized_value(i, column) {
+                Some(value) if !seen.contains(&key) => value,
+                _ => continue,
+            };
+            seen.push(key.clone());
+
+            match xbrl.get(&key) {
+                Some((_, xbrl_value)) => {
+                    report.matched_values.push((label.clone(), html_value, *xbrl_value));
+
+                    let pct_diff = if *xbrl_value == 0.0 {
+                        if html_value == 0.0 { 0.0 } else { f64::INFINITY }
+                    } else {
+                        (html_value - xbrl_value).abs() / xbrl_value.abs() * 100.0
+                    };
+                    if pct_diff > DISCREPANCY_PCT {
+                        report.discrepancies.push((label, html_value, *xbrl_value, pct_diff));
+                    }
+                }
+                None => report.html_only.push((label, html_value)),
+            }
+        }
+    }
+
+    for (key, (label, value)) in &xbrl {
+        if !seen.contains(key) {
+            report.xbrl_only.push((label.to_string(), *value));
+        }
+    }
+    report.xbrl_only.sort_by(|a, b| a.0.cmp(&b.0));
+
+    return report;
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn date(text: &str) -> NaiveDate {
+        return NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap();
+    }
+
+
+    fn statement(headers: &[&[&str]], data: &[&[&str]]) -> StatementData {
+        let strings = |cells: &[&str]| cells.iter().map(|cell| cell.to_string()).collect();
+        return StatementData { headers: headers.iter().map(|row| strings(row)).collect(),
+                               data: data.iter().map(|row| strings(row)).collect(),
+                               ..StatementData::default() };
+    }
+
+
+
+    #[test]
+    fn period_column_with_label_cell() {
+        let data = statement(&[&["Balance Sheets - USD ($)", "Dec. 30, 2023", "Sep. 30, 2023"]],
+                             &[&["Total assets", "353,514", "352,583"]]);
+
+        assert_eq!(period_column(&data, &date("2023-12-30")), Some(0));
+        assert_eq!(period_column(&data, &date("2023-09-30")), Some(1));
+        assert_eq!(period_column(&data, &date("2022-09-30")), None);
+    }
+
+
+    #[test]
+    fn period_column_under_spanning_title() {
+        // the title cell spans both header rows, the last holds dates only
+        let data = statement(&[&["Operations - USD ($)", "3 Months Ended"],
+                               &["Dec. 30, 2023", "Dec. 31, 2022"]],
+                             &[&["Net income", "$ 33,916", "$ 29,998"]]);
+
+        assert_eq!(period_column(&data, &date("2023-12-30")), Some(0));
+        assert_eq!(period_column(&data, &date("2022-12-31")), Some(1));
+    }
+
+
+    #[test]
+    fn period_column_counts_undated_headings() {
+        // an undated heading before the period shifts the column
+        let data = statement(&[&["", "Total", "Dec. 30, 2023"]],
+                             &[&["Shares", "10", "20"]]);
+
+        assert_eq!(period_column(&data, &date("2023-12-30")), Some(1));
+    }
+
+
+    #[test]
+    fn reconcile_statement_uses_the_period_column() {
+        let data = statement(&[&["Operations - USD ($)", "3 Months Ended"],
+                               &["Dec. 30, 2023", "Dec. 31, 2022"]],
+                             &[&["Net income", "$ 33,916", "$ 29,998"],
+                               &["Other income", "(50)", "(393)"]]);
+        let xbrl = HashMap::from([("Net Income".to_string(), 29_998.0),
+                                  ("Other income".to_string(), -400.0),
+                                  ("Revenue".to_string(), 1.0)]);
+
+        let report = reconcile_statement(&data, &xbrl, &date("2022-12-31"));
+        assert_eq!(report.matched_values, [("Net income".to_string(), 29_998.0, 29_998.0),
+                                           ("Other income".to_string(), -393.0, -400.0)]);
+        assert_eq!(report.discrepancies.len(), 1);
+        assert_eq!(report.discrepancies[0].0, "Other income");
+        assert_eq!(report.xbrl_only, [("Revenue".to_string(), 1.0)]);
+
+        let report = reconcile_statement(&data, &xbrl, &date("2021-12-31"));
+        assert!(report.matched_values.is_empty());
+        assert_eq!(report.xbrl_only.len(), 3);
+    }
+}
@@ -0,0 +1,134 @@
+//! Archives documents routed to the archive mirror, and to sec.gov when the
+//! mirror lacks them or is down. Two local servers stand in for the mirror
+//! and for sec.gov.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+use common::{MockServer, Reply};
+use sec_filings::{SecClient, SecClientBuilder};
+
+
+const DOCUMENT: &str = "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/R2.htm";
+const PATH: &str = "/edgar/data/320193/000032019324000006/R2.htm";
+
+
+/// Serves `body` for the document, 404 for anything else.
+fn serving(body: &'static str) -> MockServer {
+    return MockServer::start(move |request| {
+        if request.path.ends_with(PATH) {
+            return Reply::ok(body);
+        }
+        return Reply::status(404, "Not Found");
+    });
+}
+
+
+fn builder(mirror: &str, sec_gov: &MockServer) -> SecClientBuilder {
+    return SecClient::builder().user_agent("Test Suite test@example.com")
+                               .archive_mirror(mirror)
+                               .archives_base(&format!("{}/Archives/", sec_gov.url))
+                               .retry_base_delay(std::time::Duration::from_millis(1));
+}
+
+
+/// A local address nothing listens on.
+fn closed_port() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    return url;
+}
+
+
+fn cache_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sec_filings_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    return dir;
+}
+
+
+
+#[test]
+fn documents_come_from_the_mirror() {
+    let mirror = serving("mirrored");
+    let sec_gov = serving("from sec.gov");
+    let client = builder(&mirror.url, &sec_gov).build().unwrap();
+
+    assert_eq!(client.get(DOCUMENT).unwrap(), "mirrored");
+
+    let requests = mirror.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, PATH);
+    assert!(sec_gov.requests().is_empty());
+}
+
+
+#[test]
+fn documents_missing_from_the_mirror_come_from_sec_gov() {
+    let mirror = MockServer::start(|_| Reply::status(404, "Not Found"));
+    let sec_gov = serving("from sec.gov");
+    let client = builder(&mirror.url, &sec_gov).build().unwrap();
+
+    assert_eq!(client.get(DOCUMENT).unwrap(), "from sec.gov");
+
+    assert_eq!(mirror.requests().len(), 1);
+    let requests = sec_gov.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, format!("/Archives{}", PATH));
+}
+
+
+#[test]
+fn an_unreachable_mirror_falls_back_to_sec_gov() {
+    let sec_gov = serving("from sec.gov");
+    let client = builder(&closed_port(), &sec_gov).build().unwrap();
+
+    assert_eq!(client.get(DOCUMENT).unwrap(), "from sec.gov");
+    assert_eq!(sec_gov.requests().len(), 1);
+}
+
+
+#[test]
+fn mirror_errors_other_than_404_fail() {
+    let mirror = MockServer::start(|_| Reply::status(500, "broken"));
+    let sec_gov = serving("from sec.gov");
+    let client = builder(&mirror.url, &sec_gov).build().unwrap();
+
+    assert!(client.get(DOCUMENT).is_err());
+    assert!(sec_gov.requests().is_empty());
+}
+
+
+#[test]
+fn cache_keys_do_not_depend_on_the_host() {
+    let dir = cache_dir("mirror_cache");
+    let mirror = serving("cached body");
+    let sec_gov = serving("from sec.gov");
+
+    let client = builder(&mirror.url, &sec_gov).cache_dir(&dir).build().unwrap();
+    assert_eq!(client.get(DOCUMENT).unwrap(), "cached body");
+
+    // another mirror, and another sec.gov, find it under the same key
+    let other_mirror = MockServer::start(|_| Reply::status(404, "Not Found"));
+    let other_sec_gov = MockServer::start(|_| Reply::status(500, "offline"));
+    let other = builder(&other_mirror.url, &other_sec_gov).cache_dir(&dir).build().unwrap();
+
+    assert_eq!(other.get(DOCUMENT).unwrap(), "cached body");
+    assert!(other_mirror.requests().is_empty());
+    assert!(other_sec_gov.requests().is_empty());
+
+    // and a document fetched from sec.gov is cached like one from the mirror
+    other.cache().unwrap().invalidate_all().unwrap();
+    let fallback = builder(&other_mirror.url, &sec_gov).cache_dir(&dir).build().unwrap();
+    assert_eq!(fallback.get(DOCUMENT).unwrap(), "from sec.gov");
+    assert_eq!(client.get(DOCUMENT).unwrap(), "from sec.gov");
+    assert_eq!(mirror.requests().len(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,118 @@
+//! `AsyncSecClient` against a local server, run with `--features async`.
+
+#![cfg(feature = "async")]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{MockServer, Reply};
+use sec_filings::{AsyncSecClient, SecFilingsError, SharedLimiter};
+
+
+fn client() -> AsyncSecClient {
+    return AsyncSecClient::with_user_agent("Test Suite test@example.com").unwrap()
+                          .retry_base_delay(Duration::from_millis(1));
+}
+
+
+
+#[tokio::test]
+async fn get_returns_the_body() {
+    let server = MockServer::start(|request| Reply::ok(&format!("body of {}", request.path)));
+
+    let body = client().get(&format!("{}/R2.htm", server.url)).await.unwrap();
+    assert_eq!(body, "body of /R2.htm");
+
+    let query = [("action", "getcompany"), ("ticker", "aapl")];
+    let body = client().get_with_params(&server.url, &query).await.unwrap();
+    assert_eq!(body, "body of /?action=getcompany&ticker=aapl");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].user_agent.as_deref(), Some("Test Suite test@example.com"));
+}
+
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let server = MockServer::start(|request| match request.index {
+        0 => Reply::status(503, "busy").header("Retry-After", "0"),
+        1 => Reply::status(429, "slow down"),
+        _ => Reply::ok("fine"),
+    });
+
+    assert_eq!(client().get(&server.url).await.unwrap(), "fine");
+    assert_eq!(server.requests().len(), 3);
+}
+
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let server = MockServer::start(|_| Reply::status(429, "slow down"));
+
+    let result = client().max_retries(2).get(&server.url).await;
+    assert!(matches!(result, Err(SecFilingsError::RateLimited)), "{:?}", result);
+    assert_eq!(server.requests().len(), 3);
+}
+
+
+#[tokio::test]
+async fn statuses_map_like_the_blocking_client() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/tool" => Reply::status(403, "Undeclared Automated Tool"),
+        _ => Reply::status(404, "Not Found"),
+    });
+
+    let result = client().get(&format!("{}/tool", server.url)).await;
+    assert!(matches!(result, Err(SecFilingsError::UndeclaredAutomatedTool)), "{:?}", result);
+
+    // not retried
+    let result = client().get(&format!("{}/missing", server.url)).await;
+    match result {
+        Err(SecFilingsError::HttpStatus { status, body }) => {
+            assert_eq!(status, 404);
+            assert_eq!(body, "Not Found");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 2);
+}
+
+
+#[tokio::test]
+async fn concurrent_tasks_share_the_limit() {
+    let server = MockServer::start(|_| Reply::ok("ok"));
+    let client = Arc::new(client());
+
+    let tasks: Vec<_> = (0..15).map(|i| {
+        let client = client.clone();
+        let url = format!("{}/R{}.htm", server.url, i);
+        tokio::spawn(async move { client.get(&url).await })
+    }).collect();
+
+    for task in tasks {
+        assert_eq!(task.await.unwrap().unwrap(), "ok");
+    }
+
+    assert_eq!(server.requests().len(), 15);
+    assert!(server.busiest_second() <= 10, "{} requests in a second", server.busiest_second());
+}
+
+
+#[tokio::test]
+async fn shared_limiter_paces_several_clients() {
+    let server = MockServer::start(|_| Reply::ok("ok"));
+    let limiter = Arc::new(SharedLimiter::new(4));
+    let clients: Vec<_> = (0..3).map(|_| client().shared_limiter(limiter.clone())).collect();
+
+    for round in 0..2 {
+        for client in &clients {
+            client.get(&format!("{}/{}", server.url, round)).await.unwrap();
+        }
+    }
+
+    assert_eq!(server.requests().len(), 6);
+    assert!(server.busiest_second() <= 4, "{} requests in a second", server.busiest_second());
+}
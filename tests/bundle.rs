@@ -0,0 +1,109 @@
+//! `company_bundle_by_cik()` on Apple's submissions and 10-Q, served from
+//! tests/fixtures/aapl_10q, against tests/fixtures/golden/company_bundle.json.
+//! Run with `UPDATE_GOLDEN=1` to rewrite the golden file.
+
+#![cfg(feature = "serde")]
+#![allow(clippy::needless_return)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use sec_filings::{company_bundle_by_cik, BundleOptions, CompanyBundle, FixtureClient,
+                  SecFilingsError, BUNDLE_SCHEMA_VERSION};
+
+
+const FOLDER_URL: &str = "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006";
+
+
+fn fixture(name: &str) -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+}
+
+
+fn aapl_client() -> FixtureClient {
+    return FixtureClient::new()
+        .with_response("https://data.sec.gov/submissions/CIK0000320193.json",
+                       fixture("aapl_10q/submissions.json"))
+        .with_response("https://data.sec.gov/submissions/CIK0000320193-submissions-001.json",
+                       fixture("aapl_10q/submissions-001.json"))
+        .with_response(&format!("{}/index.json", FOLDER_URL), fixture("aapl_10q/index.json"))
+        .with_response(&format!("{}/FilingSummary.xml", FOLDER_URL),
+                       fixture("aapl_10q/FilingSummary.xml"))
+        .with_response(&format!("{}/R2.htm", FOLDER_URL), fixture("aapl_10q/R2.htm"))
+        .with_response(&format!("{}/R4.htm", FOLDER_URL), fixture("aapl_10q/R4.htm"));
+}
+
+
+/// Metrics of the latest filing only, the other filings' documents are
+/// not among the fixtures.
+fn options() -> BundleOptions {
+    return BundleOptions { max_metric_filings: 1, ..BundleOptions::default() };
+}
+
+
+
+#[test]
+fn bundle_matches_golden_file() {
+    let bundle = company_bundle_by_cik(&aapl_client(), 320193, &options()).unwrap();
+    let json = bundle.to_json() + "\n";
+
+    let path = fixture("golden/company_bundle.json");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &json).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    assert!(json == expected, "{} differs, got:\n{}", path.display(), json);
+}
+
+
+#[test]
+fn bundle_contents() {
+    let bundle = company_bundle_by_cik(&aapl_client(), 320193, &options()).unwrap();
+
+    assert_eq!(bundle.schema_version, BUNDLE_SCHEMA_VERSION);
+    assert_eq!(bundle.company.name, "Apple Inc.");
+    assert_eq!(bundle.company.sic.as_deref(), Some("3571"));
+    assert_eq!(bundle.company.tickers, ["AAPL"]);
+
+    // five recent filings, then five from the overflow file
+    assert_eq!(bundle.filings.len(), 10);
+    assert_eq!(bundle.filings[0].accession, "0000320193-24-000006");
+    assert_eq!(bundle.filings[9].accession, "0000320193-22-000107");
+
+    assert_eq!(bundle.metrics.len(), 1);
+    let metrics = &bundle.metrics[0];
+    assert_eq!(metrics.accession, "0000320193-24-000006");
+
+    let revenue = metrics.revenue.as_ref().unwrap();
+    assert_eq!(revenue.value, 119_575_000_000.0);
+    assert_eq!(revenue.source.url, format!("{}/R2.htm", FOLDER_URL));
+    assert_eq!(revenue.source.label, "Total net sales");
+    assert_eq!(metrics.net_income.as_ref().unwrap().value, 33_916_000_000.0);
+    assert_eq!(metrics.total_assets.as_ref().unwrap().value, 353_514_000_000.0);
+    assert_eq!(metrics.total_assets.as_ref().unwrap().source.report,
+               "CONDENSED CONSOLIDATED BALANCE SHEETS (Unaudited)");
+    // the 10-Q has no cash flow statement among its reports
+    assert_eq!(metrics.operating_cash_flow, None);
+}
+
+
+#[test]
+fn bundle_round_trips_through_json() {
+    let bundle = company_bundle_by_cik(&aapl_client(), 320193, &options()).unwrap();
+    assert_eq!(CompanyBundle::from_json(&bundle.to_json()).unwrap(), bundle);
+
+    let path = std::env::temp_dir().join(format!("sec_filings_bundle_{}.json",
+                                                 std::process::id()));
+    bundle.write_bundle(&path).unwrap();
+    let written = CompanyBundle::from_json(&fs::read_to_string(&path).unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(written, bundle);
+
+    // a bundle of another schema version is refused
+    let newer = bundle.to_json().replacen(&format!("\"schema_version\": {}",
+                                                  BUNDLE_SCHEMA_VERSION),
+                                          "\"schema_version\": 999", 1);
+    assert!(matches!(CompanyBundle::from_json(&newer),
+                     Err(SecFilingsError::UnexpectedStructure(_))));
+}
@@ -0,0 +1,181 @@
+//! A bare HTTP/1.1 server on localhost standing in for EDGAR, keeping
+//! connections alive like sec.gov does.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+
+/// A request as the server saw it.
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// Position among all requests the server received, from 0.
+    pub index: usize,
+    /// Path with the query, e.g. "/cgi-bin/browse-edgar?action=getcompany".
+    pub path: String,
+    pub user_agent: Option<String>,
+    pub received: Instant,
+}
+
+
+/// What to answer a request with.
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+
+impl Reply {
+
+    pub fn ok(body: &str) -> Self {
+        return Reply::status(200, body);
+    }
+
+
+    pub fn status(status: u16, body: &str) -> Self {
+        return Reply { status, headers: vec![], body: body.to_string() };
+    }
+
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        return self;
+    }
+}
+
+
+type Responder = dyn Fn(&Request) -> Reply + Send + Sync;
+
+
+pub struct MockServer {
+    /// "http://127.0.0.1:<port>", without a trailing slash.
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+
+impl MockServer {
+
+    /// Serve every request with `respond`, on a thread per connection.
+    /// The server lives until the test process exits.
+    pub fn start<F>(respond: F) -> MockServer
+    where
+        F: Fn(&Request) -> Reply + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let connections = Arc::new(AtomicUsize::new(0));
+        let respond: Arc<Responder> = Arc::new(respond);
+
+        let server = MockServer { url, requests: requests.clone(),
+                                  connections: connections.clone() };
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                connections.fetch_add(1, Ordering::SeqCst);
+
+                let requests = requests.clone();
+                let respond = respond.clone();
+                thread::spawn(move || serve(stream, &requests, &*respond));
+            }
+        });
+
+        return server;
+    }
+
+
+    /// Requests received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        return self.requests.lock().unwrap().clone();
+    }
+
+
+    /// Connections accepted so far.
+    pub fn connections(&self) -> usize {
+        return self.connections.load(Ordering::SeqCst);
+    }
+
+
+    /// Most requests received within any one second, less a little for
+    /// requests sent a second apart arriving closer together.
+    pub fn busiest_second(&self) -> usize {
+        let window = Duration::from_millis(950);
+        let requests = self.requests();
+
+        return requests.iter().enumerate().map(|(i, first)| {
+            requests[i..].iter().take_while(|request| request.received
+                                                      .duration_since(first.received) < window)
+                         .count()
+        }).max().unwrap_or(0);
+    }
+}
+
+
+/// Answer requests on `stream` until the client hangs up.
+fn serve(stream: TcpStream, requests: &Mutex<Vec<Request>>, respond: &Responder) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+            return;
+        }
+
+        let mut user_agent = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("user-agent") {
+                    user_agent = Some(value.trim().to_string());
+                }
+            }
+        }
+
+        let request = {
+            let mut requests = requests.lock().unwrap();
+            let request = Request {
+                index: requests.len(),
+                path: request_line.split_whitespace().nth(1).unwrap_or("").to_string(),
+                user_agent,
+                received: Instant::now(),
+            };
+            requests.push(request.clone());
+            request
+        };
+
+        let reply = respond(&request);
+        let mut response = format!("HTTP/1.1 {} Mock\r\nContent-Length: {}\r\n", reply.status,
+                               reply.body.len());
+        for (name, value) in &reply.headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        response.push_str(&reply.body);
+
+        // in one write, a second small one would wait for a delayed ACK
+        if writer.write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Connection reuse of `SecClient`, counted against a local server.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use common::{MockServer, Reply};
+use sec_filings::{SecClient, SecClientBuilder};
+
+
+fn builder() -> SecClientBuilder {
+    return SecClient::builder().user_agent("Test Suite test@example.com").request_threshold(250);
+}
+
+
+fn get_n(client: &SecClient, server: &MockServer, n: usize) {
+    for i in 0..n {
+        let body = client.get(&format!("{}/R{}.htm", server.url, i)).unwrap();
+        assert_eq!(body, "<html></html>");
+    }
+}
+
+
+
+#[test]
+fn hundred_requests_share_one_connection() {
+    let server = MockServer::start(|_| Reply::ok("<html></html>"));
+    let client = builder().build().unwrap();
+
+    get_n(&client, &server, 100);
+
+    assert_eq!(server.requests().len(), 100);
+    assert_eq!(server.connections(), 1);
+    assert_eq!(client.connections_opened(), 1);
+    assert_eq!(client.connections_reused(), 99);
+}
+
+
+#[test]
+fn pool_max_idle_per_host_reaches_the_client() {
+    let server = MockServer::start(|_| Reply::ok("<html></html>"));
+    let client = builder().pool_max_idle_per_host(0).build().unwrap();
+
+    // nothing is kept idle, so every request needs a connection
+    get_n(&client, &server, 5);
+
+    assert_eq!(server.connections(), 5);
+    assert_eq!(client.connections_opened(), 5);
+    assert_eq!(client.connections_reused(), 0);
+}
+
+
+#[test]
+fn pool_idle_timeout_reaches_the_client() {
+    let server = MockServer::start(|_| Reply::ok("<html></html>"));
+    let client = builder().pool_idle_timeout(Some(Duration::from_millis(50))).build().unwrap();
+
+    for _ in 0..3 {
+        get_n(&client, &server, 2);
+        sleep(Duration::from_millis(300));
+    }
+
+    // two requests per connection, each closed while idle
+    assert_eq!(client.connections_opened(), 3);
+    assert_eq!(client.connections_reused(), 3);
+    assert_eq!(server.connections(), 3);
+}
+
+
+#[test]
+fn tcp_keepalive_does_not_stop_reuse() {
+    let server = MockServer::start(|_| Reply::ok("<html></html>"));
+    let client = builder().tcp_keepalive(Some(Duration::from_secs(1))).build().unwrap();
+
+    get_n(&client, &server, 10);
+
+    assert_eq!(client.connections_opened(), 1);
+    assert_eq!(client.connections_reused(), 9);
+}
+
+
+#[test]
+fn retries_are_counted_as_requests() {
+    let server = MockServer::start(|request| match request.index {
+        0 => Reply::status(503, "busy"),
+        _ => Reply::ok("<html></html>"),
+    });
+    let client = builder().retry_base_delay(Duration::from_millis(1)).build().unwrap();
+
+    get_n(&client, &server, 1);
+
+    assert_eq!(client.requests_sent(), 2);
+    assert_eq!(client.connections_opened(), 1);
+    assert_eq!(client.connections_reused(), 1);
+}
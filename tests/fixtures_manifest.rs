@@ -0,0 +1,102 @@
+//! tests/fixtures/manifest.toml lists every fixture, with checksums that
+//! match the files on disk.
+
+#![allow(clippy::needless_return)]
+
+mod manifest;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use manifest::{crate_dir, fixtures_dir, load, sha256_hex, snapshot, to_toml, Manifest,
+               PARSERS};
+
+
+/// Files under `dir`, relative to tests/fixtures, golden/ left out.
+fn fixture_files(dir: &Path, files: &mut BTreeSet<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let relative = path.strip_prefix(fixtures_dir()).unwrap().to_string_lossy()
+                           .replace('\\', "/");
+
+        if path.is_dir() {
+            if relative != "golden" {
+                fixture_files(&path, files);
+            }
+        } else if relative != "manifest.toml" {
+            files.insert(relative);
+        }
+    }
+}
+
+
+
+#[test]
+fn every_fixture_is_listed_once() {
+    let listed: Vec<String> = load().fixtures.into_iter().map(|fixture| fixture.path).collect();
+    let unique: BTreeSet<String> = listed.iter().cloned().collect();
+    assert_eq!(unique.len(), listed.len(), "listed twice in manifest.toml");
+
+    let mut on_disk = BTreeSet::new();
+    fixture_files(&fixtures_dir(), &mut on_disk);
+
+    let missing: Vec<&String> = unique.difference(&on_disk).collect();
+    let unlisted: Vec<&String> = on_disk.difference(&unique).collect();
+    assert!(missing.is_empty(), "listed but missing: {:?}", missing);
+    assert!(unlisted.is_empty(), "not in manifest.toml: {:?}", unlisted);
+}
+
+
+#[test]
+fn checksums_match() {
+    for fixture in load().fixtures {
+        let bytes = fs::read(fixtures_dir().join(&fixture.path)).unwrap();
+        assert_eq!(sha256_hex(&bytes), fixture.sha256,
+                   "{} changed without refreshing manifest.toml", fixture.path);
+    }
+}
+
+
+#[test]
+fn entries_are_complete() {
+    for fixture in load().fixtures {
+        assert!(chrono::NaiveDate::parse_from_str(&fixture.captured, "%Y-%m-%d").is_ok(),
+                "{}: captured '{}'", fixture.path, fixture.captured);
+        assert!(fixture.source.is_some() || !fixture.notes.is_empty(),
+                "{}: a hand-written fixture says what it is in notes", fixture.path);
+
+        // each user exists and names the fixture
+        assert!(!fixture.used_by.is_empty(), "{}: used by nothing", fixture.path);
+        let name = fixture.path.rsplit('/').next().unwrap();
+        for user in &fixture.used_by {
+            let source = fs::read_to_string(crate_dir().join(user))
+                         .unwrap_or_else(|e| panic!("{}: {}: {}", fixture.path, user, e));
+            assert!(source.contains(name), "{}: {} does not mention {}", fixture.path, user,
+                    name);
+        }
+    }
+}
+
+
+#[test]
+fn snapshots_parse() {
+    for fixture in load().fixtures {
+        if let Some(parser) = &fixture.parser {
+            assert!(PARSERS.contains(&parser.as_str()), "{}: parser '{}'", fixture.path, parser);
+        }
+
+        let path = fixtures_dir().join(&fixture.path);
+        if let Err(e) = snapshot(&fixture, &path) {
+            panic!("{}: {}", fixture.path, e);
+        }
+    }
+}
+
+
+#[test]
+fn manifest_round_trips() {
+    let manifest = load();
+    let written: Manifest = toml::from_str(&to_toml(&manifest)).unwrap();
+    assert_eq!(written, manifest);
+}
@@ -0,0 +1,98 @@
+//! Error statuses through `SecClient::get()`: which are retried, and the
+//! `SecFilingsError` each ends up as. A local server stands in for EDGAR.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{MockServer, Reply};
+use sec_filings::{SecClient, SecFilingsError};
+
+
+/// SEC's page for clients without a proper User-Agent, shortened.
+const UNDECLARED_TOOL_PAGE: &str = "<html><head><title>SEC.gov | Request Rate Threshold \
+                                    Exceeded</title></head><body><h1>Your Request Originates \
+                                    from an Undeclared Automated Tool</h1></body></html>";
+
+
+fn client(max_retries: u32) -> SecClient {
+    return SecClient::builder().user_agent("Test Suite test@example.com")
+                               .max_retries(max_retries)
+                               .retry_base_delay(Duration::from_millis(1))
+                               .build().unwrap();
+}
+
+
+/// The status and body of an `HttpStatus` error, panicking on others.
+fn http_status(result: Result<String, SecFilingsError>) -> (u16, String) {
+    return match result {
+        Err(SecFilingsError::HttpStatus { status, body }) => (status, body),
+        other => panic!("unexpected {:?}", other),
+    };
+}
+
+
+
+#[test]
+fn forbidden_is_not_retried() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/tool" => Reply::status(403, UNDECLARED_TOOL_PAGE),
+        _ => Reply::status(403, "Forbidden"),
+    });
+    let client = client(3);
+
+    let result = client.get(&format!("{}/tool", server.url));
+    assert!(matches!(result, Err(SecFilingsError::UndeclaredAutomatedTool)), "{:?}", result);
+
+    // any other 403 is an ordinary status error
+    assert_eq!(http_status(client.get(&format!("{}/R2.htm", server.url))),
+               (403, "Forbidden".to_string()));
+    assert_eq!(server.requests().len(), 2);
+}
+
+
+#[test]
+fn not_found_is_not_retried() {
+    let server = MockServer::start(|_| Reply::status(404, "Not Found"));
+
+    assert_eq!(http_status(client(3).get(&server.url)), (404, "Not Found".to_string()));
+    assert_eq!(server.requests().len(), 1);
+}
+
+
+#[test]
+fn server_errors_are_retried() {
+    let server = MockServer::start(|request| match request.index {
+        0 | 1 => Reply::status(500, "Internal Server Error"),
+        _ => Reply::ok("fine"),
+    });
+
+    assert_eq!(client(3).get(&server.url).unwrap(), "fine");
+    assert_eq!(server.requests().len(), 3);
+}
+
+
+#[test]
+fn server_errors_fail_once_retries_run_out() {
+    let server = MockServer::start(|_| Reply::status(500, "Internal Server Error"));
+
+    assert_eq!(http_status(client(2).get(&server.url)),
+               (500, "Internal Server Error".to_string()));
+    // the first attempt and two retries
+    assert_eq!(server.requests().len(), 3);
+}
+
+
+#[test]
+fn too_many_requests_is_rate_limited() {
+    let server = MockServer::start(|request| match request.index {
+        0 => Reply::status(429, "slow down").header("Retry-After", "0"),
+        _ => Reply::status(429, "slow down"),
+    });
+
+    let result = client(1).get(&server.url);
+    assert!(matches!(result, Err(SecFilingsError::RateLimited)), "{:?}", result);
+    assert_eq!(server.requests().len(), 2);
+}
@@ -0,0 +1,126 @@
+//! tests/fixtures/manifest.toml: where each fixture came from, which
+//! tests read it and its checksum. Checked by tests/fixtures_manifest.rs,
+//! updated by `cargo run --example refresh-fixtures`.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use sec_filings::{filing_index, master_reports, parse_documents_feed,
+                  parse_html_statement_data, FixtureClient, SecFilingsError};
+
+
+/// Written above the entries, `toml` keeps no comments.
+const HEADER: &str = "\
+# Every file under tests/fixtures but golden/, which holds expected outputs.
+# Checked by tests/fixtures_manifest.rs. Refresh captured fixtures with
+# `cargo run --example refresh-fixtures -- [--accept] [path ...]`.
+
+";
+
+/// Parsers a fixture's snapshot can be taken with, see `snapshot()`.
+pub const PARSERS: [&str; 5] = ["statement", "feed", "filing_index", "filing_summary", "json"];
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "fixture")]
+    pub fixtures: Vec<Fixture>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Relative to tests/fixtures, e.g. "aapl_10q/R2.htm".
+    pub path: String,
+    /// URL the fixture was captured from, `None` for hand-written ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accession: Option<String>,
+    /// "YYYY-MM-DD" the fixture was captured or written.
+    pub captured: String,
+    /// One of `PARSERS`, for a fixture whose parse guards a refresh.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser: Option<String>,
+    /// Source files reading the fixture, relative to the crate root.
+    pub used_by: Vec<String>,
+    pub sha256: String,
+    /// How the fixture differs from what `source` serves, e.g. trimmed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+
+
+pub fn crate_dir() -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+}
+
+
+pub fn fixtures_dir() -> PathBuf {
+    return crate_dir().join("tests/fixtures");
+}
+
+
+pub fn manifest_path() -> PathBuf {
+    return fixtures_dir().join("manifest.toml");
+}
+
+
+pub fn load() -> Manifest {
+    let text = fs::read_to_string(manifest_path()).expect("tests/fixtures/manifest.toml");
+    return toml::from_str(&text).expect("manifest.toml parses");
+}
+
+
+/// `manifest` as manifest.toml holds it.
+pub fn to_toml(manifest: &Manifest) -> String {
+    let text = toml::to_string_pretty(manifest).expect("manifest serializes");
+    return format!("{}{}", HEADER, text);
+}
+
+
+pub fn save(manifest: &Manifest) {
+    fs::write(manifest_path(), to_toml(manifest)).expect("manifest.toml written");
+}
+
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    return Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+
+
+/// What the crate parses the file at `path` into, standing in for
+/// `fixture`, or `None` if the fixture names no parser. A refresh that
+/// changes it changes what the tests see.
+pub fn snapshot(fixture: &Fixture, path: &Path) -> Result<Option<String>, SecFilingsError> {
+    let body = fs::read_to_string(path)?;
+    // the pipeline functions fetch by URL, so serve the file at its source
+    let source = fixture.source.clone().unwrap_or_else(|| fixture.path.clone());
+    let client = FixtureClient::new().with_response(&source, path);
+
+    let snapshot = match fixture.parser.as_deref() {
+        None => return Ok(None),
+        Some("statement") => format!("{:#?}", parse_html_statement_data(&body)?),
+        Some("feed") => format!("{:#?}", parse_documents_feed(&body)?),
+        Some("filing_index") => format!("{:#?}", filing_index(&client, &source)?),
+        Some("filing_summary") => format!("{:#?}", master_reports(&client, &[source])?),
+        Some("json") => {
+            let value: serde_json::Value = serde_json::from_str(&body)?;
+            serde_json::to_string_pretty(&value)?
+        }
+        Some(other) => {
+            return Err(SecFilingsError::InvalidInput(format!("{}: unknown parser '{}'",
+                                                             fixture.path, other)));
+        }
+    };
+
+    return Ok(Some(snapshot));
+}
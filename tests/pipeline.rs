@@ -0,0 +1,126 @@
+//! `documents()` through `balance_sheet_first()` on Apple's 10-Q for the
+//! quarter ended Dec. 30, 2023, served from tests/fixtures/aapl_10q.
+
+#![allow(clippy::needless_return)]
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use sec_filings::{balance_sheet_first, balance_sheets_all, documents, filing_summaries,
+                  income_statement, master_reports, reconcile_statement, DocumentsQuery,
+                  FilingType, FixtureClient, Scale, StatementKind};
+
+
+const FEED_URL: &str = "https://www.sec.gov/cgi-bin/browse-edgar?action=getcompany&ticker=aapl\
+                        &type=10-Q&datea=&dateb=&owner=exclude&start=0&output=atom&count=100";
+const INDEX_URL: &str = "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006/\
+                         index.json";
+const FOLDER_URL: &str = "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006";
+
+
+fn fixture(name: &str) -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aapl_10q").join(name);
+}
+
+
+fn aapl_client() -> FixtureClient {
+    return FixtureClient::new()
+        .with_response(FEED_URL, fixture("browse_edgar.xml"))
+        .with_response(INDEX_URL, fixture("index.json"))
+        .with_response(&format!("{}/FilingSummary.xml", FOLDER_URL), fixture("FilingSummary.xml"))
+        .with_response(&format!("{}/R2.htm", FOLDER_URL), fixture("R2.htm"))
+        .with_response(&format!("{}/R4.htm", FOLDER_URL), fixture("R4.htm"));
+}
+
+
+
+#[test]
+fn balance_sheet_from_fixtures() {
+    let client = aapl_client();
+
+    let filings = documents(&client, "aapl", &DocumentsQuery::new(), FilingType::TenQ).unwrap();
+    assert_eq!(filings.len(), 1);
+    assert_eq!(filings[0].url, INDEX_URL);
+    assert_eq!(filings[0].accession.to_string(), "0000320193-24-000006");
+    assert_eq!(filings[0].form_type, "10-Q");
+    assert_eq!(filings[0].filed_on, NaiveDate::from_ymd_opt(2024, 2, 2));
+
+    let urls: Vec<String> = filings.into_iter().map(|filing| filing.url).collect();
+    let summaries = filing_summaries(&client, &urls).unwrap();
+    assert_eq!(summaries, vec![format!("{}/FilingSummary.xml", FOLDER_URL)]);
+
+    // the trailing "All Reports" entry is dropped
+    let reports = master_reports(&client, &summaries).unwrap();
+    assert_eq!(reports.len(), 5);
+    assert_eq!(reports[3].statement_kind, Some(StatementKind::BalanceSheet));
+    assert_eq!(reports[4].statement_kind, None);
+
+    let balance_sheet = balance_sheet_first(&client, &reports).unwrap();
+    assert_eq!(balance_sheet.source_url.as_deref(), Some(&*format!("{}/R4.htm", FOLDER_URL)));
+    assert_eq!(balance_sheet.report_shortname.as_deref(),
+               Some("CONDENSED CONSOLIDATED BALANCE SHEETS (Unaudited)"));
+    assert_eq!(balance_sheet.monetary_scale, Scale::Millions);
+    assert_eq!(&balance_sheet.headers[0][1..], ["Dec. 30, 2023", "Sep. 30, 2023"]);
+
+    assert_eq!(balance_sheet.get_row_exact("Total assets").unwrap(),
+               ["Total assets", "353,514", "352,583"]);
+    assert_eq!(balance_sheet.get_row("total liabilities and shareholders").unwrap()[1..],
+               ["$ 353,514", "$ 352,583"]);
+
+    let total_assets = balance_sheet.data.iter()
+                                    .position(|row| row[0] == "Total assets").unwrap();
+    assert_eq!(balance_sheet.normalized_value(total_assets, 0), Some(353_514_000_000.0));
+    assert_eq!(balance_sheet.normalized_value(total_assets, 1), Some(352_583_000_000.0));
+}
+
+
+
+#[test]
+fn balance_sheet_first_is_first_of_all() {
+    let client = aapl_client();
+    let reports = master_reports(&client, &[format!("{}/FilingSummary.xml", FOLDER_URL)])
+                  .unwrap();
+
+    let first = balance_sheet_first(&client, &reports).unwrap();
+    let all = balance_sheets_all(&client, &reports).unwrap();
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0], first);
+}
+
+
+
+#[test]
+fn income_statement_from_fixtures() {
+    let client = aapl_client();
+    let reports = master_reports(&client, &[format!("{}/FilingSummary.xml", FOLDER_URL)])
+                  .unwrap();
+
+    // the plain statement of operations, not comprehensive income (R3)
+    let income = income_statement(&client, &reports).unwrap();
+    assert_eq!(income.source_url.as_deref(), Some(&*format!("{}/R2.htm", FOLDER_URL)));
+    assert_eq!(income.headers[0][1], "3 Months Ended");
+    assert_eq!(income.headers[1], ["Dec. 30, 2023", "Dec. 31, 2022"]);
+    assert_eq!(income.get_row_exact("Net income").unwrap()[1..], ["$ 33,916", "$ 29,998"]);
+    assert_eq!(income.get_row("other income").unwrap()[1..], ["(50)", "(393)"]);
+}
+
+
+
+#[test]
+fn reconcile_income_statement_by_period() {
+    let client = aapl_client();
+    let reports = master_reports(&client, &[format!("{}/FilingSummary.xml", FOLDER_URL)])
+                  .unwrap();
+    let income = income_statement(&client, &reports).unwrap();
+
+    // the second header row holds only the dates, under the title cell
+    let xbrl = HashMap::from([("NetIncomeLoss".to_string(), 29_998_000_000.0),
+                              ("Net income".to_string(), 29_998_000_000.0)]);
+    let prior = NaiveDate::from_ymd_opt(2022, 12, 31).unwrap();
+    let report = reconcile_statement(&income, &xbrl, &prior);
+
+    assert_eq!(report.matched_values,
+               [("Net income".to_string(), 29_998_000_000.0, 29_998_000_000.0)]);
+    assert!(report.discrepancies.is_empty());
+}
@@ -0,0 +1,84 @@
+//! Clients sharing a `SharedLimiter` stay under its rate as a whole,
+//! counted against a local server.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::{MockServer, Reply};
+use sec_filings::{SecClient, SecClientBuilder, SharedLimiter};
+
+
+fn builder() -> SecClientBuilder {
+    return SecClient::builder().user_agent("Test Suite test@example.com");
+}
+
+
+/// Send `per_client` requests from each of `clients` on its own thread.
+fn get_from_threads(clients: Vec<SecClient>, server: &MockServer, per_client: usize) {
+    thread::scope(|scope| {
+        for (c, client) in clients.iter().enumerate() {
+            scope.spawn(move || {
+                for i in 0..per_client {
+                    let body = client.get(&format!("{}/{}/R{}.htm", server.url, c, i)).unwrap();
+                    assert_eq!(body, "ok");
+                }
+            });
+        }
+    });
+}
+
+
+
+#[test]
+fn clients_sharing_a_limiter_stay_under_it() {
+    let server = MockServer::start(|_| Reply::ok("ok"));
+    let limiter = Arc::new(SharedLimiter::new(10));
+    let clients = (0..3).map(|_| builder().shared_limiter(limiter.clone()).build().unwrap())
+                        .collect();
+
+    let start = Instant::now();
+    get_from_threads(clients, &server, 8);
+
+    // 24 requests at 10 per second take two windows at least
+    assert_eq!(server.requests().len(), 24);
+    assert!(server.busiest_second() <= 10, "{} requests in a second", server.busiest_second());
+    assert!(start.elapsed() >= Duration::from_secs(2));
+}
+
+
+#[test]
+fn clients_with_their_own_limiters_do_not_share() {
+    let server = MockServer::start(|_| Reply::ok("ok"));
+    let clients = (0..3).map(|_| builder().request_threshold(4).build().unwrap()).collect();
+
+    get_from_threads(clients, &server, 4);
+
+    // each client paces only itself
+    assert_eq!(server.requests().len(), 12);
+    assert!(server.busiest_second() > 4);
+}
+
+
+#[test]
+fn with_shared_limiter_takes_the_user_agent_from_the_environment() {
+    // the only test of this binary reading the environment
+    std::env::set_var("SEC_USER_AGENT", "Test Suite test@example.com");
+
+    let server = MockServer::start(|_| Reply::ok("ok"));
+    let limiter = Arc::new(SharedLimiter::new(4));
+    let clients = (0..2).map(|_| SecClient::with_shared_limiter(limiter.clone()).unwrap())
+                        .collect();
+
+    get_from_threads(clients, &server, 4);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 8);
+    assert!(requests.iter().all(|request| request.user_agent.as_deref()
+                                           == Some("Test Suite test@example.com")));
+    assert!(server.busiest_second() <= 4, "{} requests in a second", server.busiest_second());
+}
@@ -0,0 +1,114 @@
+//! Reports `filing_reports()` keeps in the cache: served from there once
+//! the documents they came from are evicted, parsed again when made by
+//! another version of the parser. A local server stands in for EDGAR as
+//! the archive mirror.
+
+#![cfg(feature = "serde")]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{MockServer, Reply};
+use sec_filings::{filing_reports, parse_documents_feed, FilingRef, SecClient,
+                  FILING_SUMMARY_PARSER_VERSION};
+
+
+const FOLDER: &str = "/edgar/data/320193/000032019324000006";
+
+
+fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aapl_10q")
+                                                         .join(name);
+    return fs::read_to_string(path).unwrap();
+}
+
+
+/// Apple's 10-Q folder, its index.json and FilingSummary.xml.
+fn edgar() -> MockServer {
+    return MockServer::start(|request| match request.path.strip_prefix(FOLDER) {
+        Some("/index.json") => Reply::ok(&fixture("index.json")),
+        Some("/FilingSummary.xml") => Reply::ok(&fixture("FilingSummary.xml")),
+        _ => Reply::status(404, "Not Found"),
+    });
+}
+
+
+fn client(mirror: &MockServer, cache: &Path) -> SecClient {
+    return SecClient::builder().user_agent("Test Suite test@example.com")
+                               .archive_mirror(&mirror.url)
+                               .cache_dir(cache)
+                               .build().unwrap();
+}
+
+
+/// The 10-Q listed in browse_edgar.xml.
+fn latest_filing() -> FilingRef {
+    return parse_documents_feed(&fixture("browse_edgar.xml")).unwrap().remove(0);
+}
+
+
+fn cache_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sec_filings_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    return dir;
+}
+
+
+
+#[test]
+fn cached_reports_are_served_offline() {
+    let dir = cache_dir("reports_offline");
+    let server = edgar();
+    let filing = latest_filing();
+
+    let warm = filing_reports(&client(&server, &dir), &filing).unwrap();
+    assert_eq!(warm.len(), 5);
+    assert_eq!(server.requests().len(), 2);
+
+    // the documents evicted, and a mirror that fails whatever is asked
+    let offline = MockServer::start(|_| Reply::status(500, "offline"));
+    let offline_client = client(&offline, &dir);
+    offline_client.cache().unwrap().invalidate_all().unwrap();
+
+    assert_eq!(filing_reports(&offline_client, &filing).unwrap(), warm);
+    assert!(offline.requests().is_empty());
+
+    // without the parsed reports, they are fetched again
+    offline_client.cache().unwrap().invalidate_reports().unwrap();
+    assert!(filing_reports(&offline_client, &filing).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn reports_of_another_parser_version_are_parsed_again() {
+    let dir = cache_dir("reports_version");
+    let server = edgar();
+    let filing = latest_filing();
+    let client = client(&server, &dir);
+
+    let warm = filing_reports(&client, &filing).unwrap();
+    client.cache().unwrap().invalidate_all().unwrap();
+
+    // as if the parser had been bumped since the reports were stored
+    let stored = dir.join("reports").join(format!("{}.json", filing.accession));
+    let mut set: serde_json::Value = serde_json::from_str(&fs::read_to_string(&stored).unwrap())
+                                     .unwrap();
+    assert_eq!(set["parser_version"], FILING_SUMMARY_PARSER_VERSION);
+    set["parser_version"] = (FILING_SUMMARY_PARSER_VERSION + 1).into();
+    fs::write(&stored, set.to_string()).unwrap();
+
+    assert_eq!(filing_reports(&client, &filing).unwrap(), warm);
+    assert_eq!(server.requests().len(), 4);
+
+    // and stored under the current version again
+    let set: serde_json::Value = serde_json::from_str(&fs::read_to_string(&stored).unwrap())
+                                 .unwrap();
+    assert_eq!(set["parser_version"], FILING_SUMMARY_PARSER_VERSION);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,113 @@
+//! Responses `cache_dir()` keeps: served without reaching the server,
+//! refetched once past the `cache_ttl` or after `invalidate()`. A local
+//! server stands in for sec.gov.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use common::{MockServer, Reply};
+use sec_filings::{SecClient, SecClientBuilder};
+
+
+const FOLDER: &str = "https://www.sec.gov/Archives/edgar/data/320193/000032019324000006";
+
+
+fn sec_gov() -> MockServer {
+    return MockServer::start(|request| Reply::ok(&format!("body of {}", request.path)));
+}
+
+
+fn builder(server: &MockServer, cache: &Path) -> SecClientBuilder {
+    return SecClient::builder().user_agent("Test Suite test@example.com")
+                               .archives_base(&format!("{}/Archives/", server.url))
+                               .cache_dir(cache);
+}
+
+
+fn cache_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sec_filings_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    return dir;
+}
+
+
+
+#[test]
+fn cached_documents_never_reach_the_server() {
+    let dir = cache_dir("cache_hit");
+    let server = sec_gov();
+    let client = builder(&server, &dir).build().unwrap();
+    let url = format!("{}/R2.htm", FOLDER);
+
+    let first = client.get(&url).unwrap();
+    assert_eq!(client.get(&url).unwrap(), first);
+    assert_eq!(server.requests().len(), 1);
+
+    // nor for another client of the same directory
+    let other = builder(&server, &dir).build().unwrap();
+    assert_eq!(other.get(&url).unwrap(), first);
+    assert_eq!(server.requests().len(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn metadata_is_refetched_past_its_ttl() {
+    let dir = cache_dir("cache_ttl");
+    let server = sec_gov();
+    let url = format!("{}/index.json", FOLDER);
+
+    // not cached at all without a TTL
+    let client = builder(&server, &dir).build().unwrap();
+    client.get(&url).unwrap();
+    client.get(&url).unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    let client = builder(&server, &dir).cache_ttl(Duration::from_millis(300)).build().unwrap();
+    client.get(&url).unwrap();
+    client.get(&url).unwrap();
+    assert_eq!(server.requests().len(), 3);
+
+    thread::sleep(Duration::from_millis(400));
+    client.get(&url).unwrap();
+    assert_eq!(server.requests().len(), 4);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn invalidated_documents_are_fetched_again() {
+    let dir = cache_dir("cache_invalidate");
+    let server = sec_gov();
+    let client = builder(&server, &dir).build().unwrap();
+    let cache = client.cache().unwrap();
+    let (r2, r4) = (format!("{}/R2.htm", FOLDER), format!("{}/R4.htm", FOLDER));
+
+    client.get(&r2).unwrap();
+    client.get(&r4).unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    // only the invalidated one
+    cache.invalidate(&r2).unwrap();
+    client.get(&r2).unwrap();
+    client.get(&r4).unwrap();
+    assert_eq!(server.requests().len(), 3);
+
+    // a miss is not an error
+    cache.invalidate(&format!("{}/R9.htm", FOLDER)).unwrap();
+
+    cache.invalidate_all().unwrap();
+    client.get(&r2).unwrap();
+    client.get(&r4).unwrap();
+    assert_eq!(server.requests().len(), 5);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
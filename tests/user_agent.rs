@@ -0,0 +1,45 @@
+//! The `User-Agent` every request carries, exactly as configured. A local
+//! server stands in for EDGAR and records the header.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::{MockServer, Reply};
+use sec_filings::SecClient;
+
+
+
+#[test]
+fn every_request_carries_the_contact() {
+    let server = MockServer::start(|_| Reply::ok("{}"));
+    let client = SecClient::builder().contact("  Acme Research Corp ", " ops@acme.example\n")
+                                     .build().unwrap();
+    assert_eq!(client.user_agent(), "Acme Research Corp ops@acme.example");
+
+    let url = format!("{}/Archives/edgar/data/320193/index.json", server.url);
+    client.get(&url).unwrap();
+    client.get_with_params(&format!("{}/cgi-bin/browse-edgar", server.url),
+                           &[("action", "getcompany")]).unwrap();
+    client.get_bytes(&url).unwrap();
+    client.get_response(&url).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    for request in requests {
+        assert_eq!(request.user_agent.as_deref(), Some("Acme Research Corp ops@acme.example"),
+                   "{}", request.path);
+    }
+}
+
+
+#[test]
+fn user_agent_is_sent_as_given() {
+    let server = MockServer::start(|_| Reply::ok(""));
+    let client = SecClient::builder().user_agent("Sample Company Name AdminContact@example.com")
+                                     .build().unwrap();
+
+    client.get(&server.url).unwrap();
+    assert_eq!(server.requests()[0].user_agent.as_deref(),
+               Some("Sample Company Name AdminContact@example.com"));
+}